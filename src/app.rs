@@ -4,9 +4,23 @@ use crate::{
     get_log_spec,
     menu::{menu_wrapper, MenuPosition},
     modules::{
-        self, clipboard, clock::Clock, keyboard_layout::KeyboardLayout,
-        keyboard_submap::KeyboardSubmap, launcher, privacy::PrivacyMessage, settings::Settings,
-        system_info::SystemInfo, title::Title, updates::Updates, workspaces::Workspaces,
+        self, bluetooth, clipboard,
+        clock::Clock,
+        keyboard_layout::KeyboardLayout,
+        keyboard_submap::KeyboardSubmap,
+        launcher,
+        media_player::MediaPlayer,
+        microphone,
+        network_speed::NetworkSpeed,
+        notifications::Notifications,
+        privacy::PrivacyMessage,
+        settings::{self, idle_inhibitor::IdleInhibitorMessage, Settings},
+        system_info::SystemInfo,
+        timer::Timer,
+        title::Title,
+        tray::Tray,
+        updates::Updates,
+        workspaces::Workspaces,
     },
     outputs::{HasOutput, Outputs},
     services::{privacy::PrivacyService, ReadOnlyService, ServiceEvent},
@@ -32,8 +46,13 @@ pub struct App {
     workspaces: Workspaces,
     window_title: Title,
     system_info: SystemInfo,
+    network_speed: NetworkSpeed,
+    media_player: MediaPlayer,
+    notifications: Notifications,
+    tray: Tray,
     keyboard_layout: KeyboardLayout,
     keyboard_submap: KeyboardSubmap,
+    timer: Timer,
     clock: Clock,
     privacy: Option<PrivacyService>,
     pub settings: Settings,
@@ -42,6 +61,12 @@ pub struct App {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuType {
     Updates,
+    NetworkSpeed,
+    MediaPlayer,
+    Notifications,
+    Tray,
+    Timer,
+    Calendar,
     Settings,
 }
 
@@ -56,8 +81,13 @@ pub enum Message {
     Workspaces(modules::workspaces::Message),
     Title(modules::title::Message),
     SystemInfo(modules::system_info::Message),
+    NetworkSpeed(modules::network_speed::Message),
+    MediaPlayer(modules::media_player::Message),
+    Notifications(modules::notifications::Message),
+    Tray(modules::tray::Message),
     KeyboardLayout(modules::keyboard_layout::Message),
     KeyboardSubmap(modules::keyboard_submap::Message),
+    Timer(modules::timer::Message),
     Clock(modules::clock::Message),
     Privacy(modules::privacy::PrivacyMessage),
     Settings(modules::settings::Message),
@@ -77,8 +107,13 @@ impl App {
                     workspaces: Workspaces::default(),
                     window_title: Title::default(),
                     system_info: SystemInfo::default(),
+                    network_speed: NetworkSpeed::default(),
+                    media_player: MediaPlayer::default(),
+                    notifications: Notifications::default(),
+                    tray: Tray::default(),
                     keyboard_layout: KeyboardLayout::default(),
                     keyboard_submap: KeyboardSubmap::default(),
+                    timer: Timer::default(),
                     clock: Clock::default(),
                     privacy: None,
                     settings: Settings::default(),
@@ -119,6 +154,10 @@ impl App {
                     warn!("Outputs changed, syncing");
                     tasks.push(self.outputs.sync(&config.outputs, config.position));
                 }
+                if !config.notifications.daemon && self.notifications.is_running() {
+                    tasks.push(self.notifications.stop());
+                    self.settings.set_notifications_daemon(false);
+                }
                 self.config = *config;
                 self.logger
                     .set_new_spec(get_log_spec(&self.config.log_level));
@@ -160,6 +199,43 @@ impl App {
                 self.system_info.update(message);
                 Task::none()
             }
+            Message::NetworkSpeed(message) => self.network_speed.update(message, &mut self.outputs),
+            Message::MediaPlayer(message) => {
+                let was_playing = self.media_player.is_playing();
+                let task =
+                    self.media_player
+                        .update(message, &self.config.media_player, &mut self.outputs);
+
+                let playing = self.media_player.is_playing();
+                if playing != was_playing {
+                    Task::batch(vec![
+                        task,
+                        self.settings.update(
+                            settings::Message::IdleInhibitor(IdleInhibitorMessage::MediaPlaying(
+                                playing,
+                            )),
+                            &self.config.settings,
+                            &mut self.outputs,
+                        ),
+                    ])
+                } else {
+                    task
+                }
+            }
+            Message::Notifications(message) => {
+                let task = self.notifications.update(
+                    message,
+                    &self.config.notifications,
+                    self.settings.do_not_disturb(),
+                    self.config.position,
+                    &mut self.outputs,
+                );
+                self.settings
+                    .set_notifications_daemon(self.notifications.is_running());
+
+                task
+            }
+            Message::Tray(message) => self.tray.update(message, &mut self.outputs),
             Message::KeyboardLayout(message) => {
                 self.keyboard_layout.update(message);
                 Task::none()
@@ -168,9 +244,13 @@ impl App {
                 self.keyboard_submap.update(message);
                 Task::none()
             }
+            Message::Timer(message) => {
+                self.timer
+                    .update(message, &self.config.timer, &mut self.outputs)
+            }
             Message::Clock(message) => {
-                self.clock.update(message);
-                Task::none()
+                self.clock
+                    .update(message, &self.config.clock, &mut self.outputs)
             }
             Message::Privacy(msg) => match msg {
                 PrivacyMessage::Event(event) => match event {
@@ -218,6 +298,16 @@ impl App {
         }
     }
 
+    /// Whether the module is in one of the bar sections
+    fn has_module(&self, module: Module) -> bool {
+        self.config
+            .left
+            .iter()
+            .chain(self.config.center.iter())
+            .chain(self.config.right.iter())
+            .any(|m| *m == module)
+    }
+
     fn match_module(&self, module: &Module, id: Id) -> Option<Element<Message>> {
         match module {
             Module::Launcher => self
@@ -248,6 +338,23 @@ impl App {
                 .system_info
                 .view(&self.config.system)
                 .map(|c| c.map(Message::SystemInfo)),
+            Module::NetworkSpeed => self
+                .network_speed
+                .view(id, &self.config.network_speed)
+                .map(|c| c.map(Message::NetworkSpeed)),
+            Module::Bluetooth => self.settings.bluetooth().and_then(|data| {
+                bluetooth::bluetooth(id, data, &self.config.bluetooth)
+                    .map(|e| e.map(Message::Settings))
+            }),
+            Module::MediaPlayer => self
+                .media_player
+                .view(id, &self.config.media_player)
+                .map(|e| e.map(Message::MediaPlayer)),
+            Module::Notifications => self
+                .notifications
+                .view(id, self.settings.do_not_disturb())
+                .map(|e| e.map(Message::Notifications)),
+            Module::Tray => self.tray.view(id).map(|e| e.map(Message::Tray)),
             Module::KeyboardSubmap => self
                 .keyboard_submap
                 .view(&self.config.keyboard.submap)
@@ -256,16 +363,17 @@ impl App {
                 .keyboard_layout
                 .view(&self.config.keyboard.layout)
                 .map(|l| l.map(Message::KeyboardLayout)),
-            Module::Clock => Some(
-                self.clock
-                    .view(&self.config.clock.format)
-                    .map(Message::Clock),
-            ),
+            Module::Timer => Some(self.timer.view(id).map(Message::Timer)),
+            Module::Clock => Some(self.clock.view(id, &self.config.clock).map(Message::Clock)),
             Module::Privacy => self
                 .privacy
                 .as_ref()
                 .and_then(|privacy| privacy.view())
                 .map(|e| e.map(Message::Privacy)),
+            Module::Microphone => self
+                .settings
+                .audio()
+                .and_then(|audio| microphone::microphone(audio, self.privacy.as_deref())),
             Module::Settings => Some(self.settings.view(id).map(Message::Settings)),
         }
     }
@@ -306,6 +414,44 @@ impl App {
                     MenuPosition::Left,
                     self.config.position,
                 ),
+                Some(MenuType::NetworkSpeed) => menu_wrapper(
+                    id,
+                    self.network_speed
+                        .menu_view(&self.config.network_speed)
+                        .map(Message::NetworkSpeed),
+                    MenuPosition::Right,
+                    self.config.position,
+                ),
+                Some(MenuType::MediaPlayer) => menu_wrapper(
+                    id,
+                    self.media_player.menu_view().map(Message::MediaPlayer),
+                    MenuPosition::Center,
+                    self.config.position,
+                ),
+                Some(MenuType::Notifications) => menu_wrapper(
+                    id,
+                    self.notifications.menu_view().map(Message::Notifications),
+                    MenuPosition::Right,
+                    self.config.position,
+                ),
+                Some(MenuType::Tray) => menu_wrapper(
+                    id,
+                    self.tray.menu_view(id).map(Message::Tray),
+                    MenuPosition::Right,
+                    self.config.position,
+                ),
+                Some(MenuType::Timer) => menu_wrapper(
+                    id,
+                    self.timer.menu_view(&self.config.timer).map(Message::Timer),
+                    MenuPosition::Right,
+                    self.config.position,
+                ),
+                Some(MenuType::Calendar) => menu_wrapper(
+                    id,
+                    self.clock.menu_view(&self.config.clock).map(Message::Clock),
+                    MenuPosition::Right,
+                    self.config.position,
+                ),
                 Some(MenuType::Settings) => menu_wrapper(
                     id,
                     self.settings
@@ -316,6 +462,9 @@ impl App {
                 ),
                 None => Row::new().into(),
             },
+            None if self.notifications.popup_surface() == Some(id) => {
+                self.notifications.popups_view().map(Message::Notifications)
+            }
             None => Row::new().into(),
         }
     }
@@ -331,6 +480,21 @@ impl App {
                 Some(self.workspaces.subscription().map(Message::Workspaces)),
                 Some(self.window_title.subscription().map(Message::Title)),
                 Some(self.system_info.subscription().map(Message::SystemInfo)),
+                // the counters are sampled only when the module is shown
+                self.has_module(Module::NetworkSpeed)
+                    .then(|| self.network_speed.subscription().map(Message::NetworkSpeed)),
+                Some(self.media_player.subscription().map(Message::MediaPlayer)),
+                Some(
+                    self.notifications
+                        .subscription(&self.config.notifications)
+                        .map(Message::Notifications),
+                ),
+                // only a bar showing the tray should take over the watcher
+                self.has_module(Module::Tray)
+                    .then(|| self.tray.subscription().map(Message::Tray)),
+                // the timer commands are served only when the module is shown
+                self.has_module(Module::Timer)
+                    .then(|| self.timer.subscription().map(Message::Timer)),
                 Some(
                     self.keyboard_layout
                         .subscription()
@@ -341,11 +505,19 @@ impl App {
                         .subscription()
                         .map(Message::KeyboardSubmap),
                 ),
-                Some(self.clock.subscription().map(Message::Clock)),
+                Some(
+                    self.clock
+                        .subscription(&self.config.clock)
+                        .map(Message::Clock),
+                ),
                 Some(
                     PrivacyService::subscribe().map(|e| Message::Privacy(PrivacyMessage::Event(e))),
                 ),
-                Some(self.settings.subscription().map(Message::Settings)),
+                Some(
+                    self.settings
+                        .subscription(&self.config.settings)
+                        .map(Message::Settings),
+                ),
                 Some(config::subscription()),
                 Some(listen_with(|evt, _, _| {
                     if let iced::Event::PlatformSpecific(iced::event::PlatformSpecific::Wayland(
@@ -17,6 +17,8 @@ pub enum Icons {
     Cpu,
     Mem,
     Temp,
+    Download,
+    Upload,
     Speaker0,
     Speaker1,
     Speaker2,
@@ -46,22 +48,48 @@ pub enum Icons {
     WifiLock4,
     WifiLock5,
     Ethernet,
+    Cellular0,
+    Cellular1,
+    Cellular2,
+    Cellular3,
     Vpn,
+    Portal,
     Bluetooth,
+    BluetoothConnected,
+    Mouse,
+    Keyboard,
+    Gamepad,
+    Phone,
+    Music,
+    MediaPlay,
+    MediaPause,
+    MediaPrevious,
+    MediaNext,
+    Bell,
+    BellOff,
     PowerSaver,
     Balanced,
     Performance,
     EyeOpened,
     EyeClosed,
+    NightLight,
     Lock,
     Power,
     Reboot,
     Suspend,
     Logout,
     RightArrow,
+    LeftArrow,
+    DownArrow,
+    Check,
+    Info,
     Brightness,
     Point,
     Close,
+    Timer,
+    Stopwatch,
+    Coffee,
+    Stop,
     VerticalDots,
     Airplane,
     Webcam,
@@ -81,6 +109,8 @@ impl From<Icons> for &'static str {
             Icons::Cpu => "󰔂",
             Icons::Mem => "󰘚",
             Icons::Temp => "󰔏",
+            Icons::Download => "󰇚",
+            Icons::Upload => "󰕒",
             Icons::Speaker0 => "󰸈",
             Icons::Speaker1 => "󰕿",
             Icons::Speaker2 => "󰖀",
@@ -110,22 +140,48 @@ impl From<Icons> for &'static str {
             Icons::WifiLock4 => "󰤧",
             Icons::WifiLock5 => "󰤪",
             Icons::Ethernet => "󰈀",
+            Icons::Cellular0 => "󰢿",
+            Icons::Cellular1 => "󰢼",
+            Icons::Cellular2 => "󰢽",
+            Icons::Cellular3 => "󰢾",
             Icons::Vpn => "󰖂",
+            Icons::Portal => "󰖟",
             Icons::Bluetooth => "󰂯",
+            Icons::BluetoothConnected => "󰂱",
+            Icons::Mouse => "󰍽",
+            Icons::Keyboard => "󰌌",
+            Icons::Gamepad => "󰊴",
+            Icons::Phone => "󰄜",
+            Icons::Music => "󰝚",
+            Icons::MediaPlay => "󰐊",
+            Icons::MediaPause => "󰏤",
+            Icons::MediaPrevious => "󰒮",
+            Icons::MediaNext => "󰒭",
+            Icons::Bell => "󰂚",
+            Icons::BellOff => "󰂛",
             Icons::PowerSaver => "󰾆",
             Icons::Balanced => "󰾅",
             Icons::Performance => "󰓅",
             Icons::EyeOpened => "󰈈",
             Icons::EyeClosed => "󰈉",
+            Icons::NightLight => "󰖔",
             Icons::Lock => "󰌾",
             Icons::Power => "󰐥",
             Icons::Reboot => "󰑐",
             Icons::Suspend => "󰤄",
             Icons::Logout => "󰗽",
             Icons::RightArrow => "󰁔",
+            Icons::LeftArrow => "󰁍",
+            Icons::DownArrow => "󰁅",
+            Icons::Check => "󰄬",
+            Icons::Info => "󰋽",
             Icons::Brightness => "󰃠",
             Icons::Point => "",
             Icons::Close => "󰅖",
+            Icons::Timer => "󰔛",
+            Icons::Stopwatch => "󱎫",
+            Icons::Coffee => "󰅶",
+            Icons::Stop => "󰓛",
             Icons::VerticalDots => "󰇙",
             Icons::Airplane => "󰀝",
             Icons::Webcam => "",
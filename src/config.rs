@@ -1,3 +1,4 @@
+use chrono_tz::Tz;
 use hex_color::HexColor;
 use iced::{
     futures::{SinkExt, StreamExt},
@@ -78,6 +79,66 @@ impl Default for SystemModuleConfig {
     }
 }
 
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSpeedModuleConfig {
+    pub interface: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BluetoothModuleConfig {
+    #[serde(default = "default_bluetooth_show_battery")]
+    pub show_battery: bool,
+    #[serde(default = "default_bluetooth_battery_warn_threshold")]
+    pub battery_warn_threshold: u8,
+}
+
+fn default_bluetooth_show_battery() -> bool {
+    true
+}
+
+fn default_bluetooth_battery_warn_threshold() -> u8 {
+    20
+}
+
+impl Default for BluetoothModuleConfig {
+    fn default() -> Self {
+        Self {
+            show_battery: default_bluetooth_show_battery(),
+            battery_warn_threshold: default_bluetooth_battery_warn_threshold(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaPlayerScrollAction {
+    Volume,
+    Player,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaPlayerModuleConfig {
+    #[serde(default = "default_media_player_max_title_length")]
+    pub max_title_length: u32,
+    pub scroll_action: Option<MediaPlayerScrollAction>,
+}
+
+fn default_media_player_max_title_length() -> u32 {
+    50
+}
+
+impl Default for MediaPlayerModuleConfig {
+    fn default() -> Self {
+        Self {
+            max_title_length: default_media_player_max_title_length(),
+            scroll_action: None,
+        }
+    }
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct KeyboardLayoutModule {
@@ -122,16 +183,125 @@ impl Default for KeyboardModuleConfig {
 #[serde(rename_all = "camelCase")]
 pub struct ClockModuleConfig {
     pub format: String,
+    /// `.ics` files or vdir directories with the events shown in the calendar
+    #[serde(default)]
+    pub calendars: Vec<String>,
+    #[serde(default)]
+    pub timezones: Vec<ClockTimezone>,
+    /// Scrolling on the clock cycles the shown timezone
+    #[serde(default)]
+    pub cycle_timezones: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockTimezone {
+    pub timezone: Tz,
+    pub label: Option<String>,
+    /// Overrides the clock format for this timezone
+    pub format: Option<String>,
+}
+
+impl ClockTimezone {
+    /// The label or the city of the IANA name, `America/New_York` is `New York`
+    pub fn label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| {
+            let name = self.timezone.name();
+            name.rsplit('/').next().unwrap_or(name).replace('_', " ")
+        })
+    }
 }
 
 impl Default for ClockModuleConfig {
     fn default() -> Self {
         Self {
             format: "%a %d %b %R".to_string(),
+            calendars: Vec::new(),
+            timezones: Vec::new(),
+            cycle_timezones: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CriticalBatteryAction {
+    Suspend,
+    Hibernate,
+    Command,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryConfig {
+    #[serde(default = "default_battery_warning_threshold")]
+    pub warning_threshold: i64,
+    #[serde(default = "default_battery_critical_threshold")]
+    pub critical_threshold: i64,
+    pub critical_action: Option<CriticalBatteryAction>,
+    pub critical_cmd: Option<String>,
+    #[serde(default = "default_battery_critical_countdown")]
+    pub critical_countdown: u64,
+    pub charge_limit_cmd: Option<String>,
+}
+
+fn default_battery_warning_threshold() -> i64 {
+    20
+}
+
+fn default_battery_critical_threshold() -> i64 {
+    5
+}
+
+fn default_battery_critical_countdown() -> u64 {
+    60
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            warning_threshold: default_battery_warning_threshold(),
+            critical_threshold: default_battery_critical_threshold(),
+            critical_action: None,
+            critical_cmd: None,
+            critical_countdown: default_battery_critical_countdown(),
+            charge_limit_cmd: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NightLightConfig {
+    #[serde(default = "default_night_light_temperature")]
+    pub temperature: u32,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+fn default_night_light_temperature() -> u32 {
+    4500
+}
+
+impl Default for NightLightConfig {
+    fn default() -> Self {
+        Self {
+            temperature: default_night_light_temperature(),
+            latitude: None,
+            longitude: None,
         }
     }
 }
 
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleInhibitorConfig {
+    #[serde(default)]
+    pub auto_on_media: bool,
+    #[serde(default)]
+    pub auto_on_fullscreen: bool,
+}
+
 #[derive(Deserialize, Default, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsModuleConfig {
@@ -141,6 +311,15 @@ pub struct SettingsModuleConfig {
     pub wifi_more_cmd: Option<String>,
     pub vpn_more_cmd: Option<String>,
     pub bluetooth_more_cmd: Option<String>,
+    pub browser_cmd: Option<String>,
+    #[serde(default)]
+    pub brightness_devices: Vec<String>,
+    #[serde(default)]
+    pub battery: BatteryConfig,
+    #[serde(default)]
+    pub night_light: NightLightConfig,
+    #[serde(default)]
+    pub idle_inhibitor: IdleInhibitorConfig,
 }
 
 #[derive(Deserialize, Clone, Copy, Debug)]
@@ -289,6 +468,77 @@ impl Default for Appearance {
     }
 }
 
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationsModuleConfig {
+    #[serde(default)]
+    pub daemon: bool,
+    #[serde(default = "default_notifications_timeout")]
+    pub timeout: u64,
+}
+
+fn default_notifications_timeout() -> u64 {
+    5
+}
+
+impl Default for NotificationsModuleConfig {
+    fn default() -> Self {
+        Self {
+            daemon: false,
+            timeout: default_notifications_timeout(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerModuleConfig {
+    /// Countdown lengths offered in the menu, in minutes
+    #[serde(default = "default_timer_presets")]
+    pub presets: Vec<u64>,
+    #[serde(default = "default_pomodoro_work")]
+    pub pomodoro_work: u64,
+    #[serde(default = "default_pomodoro_short_break")]
+    pub pomodoro_short_break: u64,
+    #[serde(default = "default_pomodoro_long_break")]
+    pub pomodoro_long_break: u64,
+    /// Work phases before a long break
+    #[serde(default = "default_pomodoro_long_break_interval")]
+    pub pomodoro_long_break_interval: u32,
+}
+
+fn default_timer_presets() -> Vec<u64> {
+    vec![5, 10, 25]
+}
+
+fn default_pomodoro_work() -> u64 {
+    25
+}
+
+fn default_pomodoro_short_break() -> u64 {
+    5
+}
+
+fn default_pomodoro_long_break() -> u64 {
+    15
+}
+
+fn default_pomodoro_long_break_interval() -> u32 {
+    4
+}
+
+impl Default for TimerModuleConfig {
+    fn default() -> Self {
+        Self {
+            presets: default_timer_presets(),
+            pomodoro_work: default_pomodoro_work(),
+            pomodoro_short_break: default_pomodoro_short_break(),
+            pomodoro_long_break: default_pomodoro_long_break(),
+            pomodoro_long_break_interval: default_pomodoro_long_break_interval(),
+        }
+    }
+}
+
 #[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Position {
     #[default]
@@ -305,10 +555,17 @@ pub enum Module {
     Workspaces,
     Title,
     SystemInfo,
+    NetworkSpeed,
+    Bluetooth,
+    MediaPlayer,
+    Notifications,
+    Tray,
     KeyboardSubmap,
     KeyboardLayout,
+    Timer,
     Clock,
     Privacy,
+    Microphone,
     Settings,
 }
 
@@ -336,10 +593,20 @@ pub struct Config {
     #[serde(default)]
     pub system: SystemModuleConfig,
     #[serde(default)]
+    pub network_speed: NetworkSpeedModuleConfig,
+    #[serde(default)]
+    pub bluetooth: BluetoothModuleConfig,
+    #[serde(default)]
+    pub media_player: MediaPlayerModuleConfig,
+    #[serde(default)]
+    pub notifications: NotificationsModuleConfig,
+    #[serde(default)]
     pub keyboard: KeyboardModuleConfig,
     #[serde(default)]
     pub clock: ClockModuleConfig,
     #[serde(default)]
+    pub timer: TimerModuleConfig,
+    #[serde(default)]
     pub settings: SettingsModuleConfig,
     #[serde(default)]
     pub appearance: Appearance,
@@ -376,8 +643,13 @@ impl Default for Config {
             truncate_title_after_length: default_truncate_title_after_length(),
             updates: None,
             system: SystemModuleConfig::default(),
+            network_speed: NetworkSpeedModuleConfig::default(),
+            bluetooth: BluetoothModuleConfig::default(),
+            media_player: MediaPlayerModuleConfig::default(),
+            notifications: NotificationsModuleConfig::default(),
             keyboard: KeyboardModuleConfig::default(),
             clock: ClockModuleConfig::default(),
+            timer: TimerModuleConfig::default(),
             settings: SettingsModuleConfig::default(),
             appearance: Appearance::default(),
             left: default_left(),
@@ -13,7 +13,9 @@ mod components;
 mod config;
 mod menu;
 mod modules;
+mod network_dialog;
 mod outputs;
+mod pairing_dialog;
 mod password_dialog;
 mod services;
 mod style;
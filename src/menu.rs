@@ -80,6 +80,7 @@ impl Menu {
 
 pub enum MenuPosition {
     Left,
+    Center,
     Right,
 }
 
@@ -113,6 +114,7 @@ pub fn menu_wrapper(
         })
         .align_x(match position {
             MenuPosition::Left => Horizontal::Left,
+            MenuPosition::Center => Horizontal::Center,
             MenuPosition::Right => Horizontal::Right,
         })
         .padding(Padding::new(8.).top(0))
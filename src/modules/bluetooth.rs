@@ -0,0 +1,79 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::BluetoothModuleConfig,
+    modules::settings::{Message, SubMenu},
+    services::bluetooth::{BluetoothData, BluetoothDevice, BluetoothState},
+    style::HeaderButtonStyle,
+};
+use iced::{
+    widget::{button, container, row, text, Row},
+    window::Id,
+    Alignment, Element, Theme,
+};
+
+fn device_icon(device: &BluetoothDevice) -> Icons {
+    // icon names follow the freedesktop naming spec used by BlueZ
+    match device.icon.as_deref() {
+        Some("audio-headphones") => Icons::Headphones1,
+        Some("audio-headset") => Icons::Headset,
+        Some("audio-card") => Icons::Speaker3,
+        Some("input-mouse") | Some("input-tablet") => Icons::Mouse,
+        Some("input-keyboard") => Icons::Keyboard,
+        Some("input-gaming") => Icons::Gamepad,
+        Some("phone") => Icons::Phone,
+        _ => Icons::BluetoothConnected,
+    }
+}
+
+pub fn bluetooth<'a>(
+    id: Id,
+    data: &'a BluetoothData,
+    config: &'a BluetoothModuleConfig,
+) -> Option<Element<'a, Message>> {
+    if data.state != BluetoothState::Active {
+        return None;
+    }
+
+    let devices = data
+        .devices
+        .iter()
+        .filter(|d| d.connected)
+        .map(|d| {
+            let low_battery = d
+                .battery
+                .is_some_and(|battery| battery <= config.battery_warn_threshold);
+
+            container(
+                row!(icon(device_icon(d)))
+                    .push_maybe(
+                        d.battery
+                            .filter(|_| config.show_battery)
+                            .map(|battery| text(format!("{}%", battery))),
+                    )
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+            )
+            .style(move |theme: &Theme| container::Style {
+                text_color: if low_battery {
+                    Some(theme.palette().danger)
+                } else {
+                    None
+                },
+                ..Default::default()
+            })
+            .into()
+        })
+        .collect::<Vec<Element<Message>>>();
+
+    if devices.is_empty() {
+        return None;
+    }
+
+    Some(
+        button(Row::with_children(devices).spacing(8))
+            .padding([2, 8])
+            .on_press(Message::OpenSubMenu(id, SubMenu::Bluetooth))
+            .style(HeaderButtonStyle::Full.into_style())
+            .into(),
+    )
+}
@@ -1,44 +1,409 @@
-use crate::style::left_header_pills;
-use chrono::{DateTime, Local};
+use crate::{
+    app::{self, MenuType},
+    components::icons::{icon, Icons},
+    config::{ClockModuleConfig, ClockTimezone},
+    outputs::Outputs,
+    style::{CalendarDayButtonStyle, GhostButtonStyle, HeaderButtonStyle},
+    utils::ical::{self, CalendarEvent},
+};
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate};
 use iced::{
-    time::every,
-    widget::{container, text},
-    Element, Subscription,
+    alignment::Horizontal,
+    futures::SinkExt,
+    mouse::ScrollDelta,
+    stream::channel,
+    widget::{button, column, container, horizontal_rule, mouse_area, row, text, Column, Row},
+    window::Id,
+    Alignment, Element, Length, Subscription, Task,
 };
-use std::time::Duration;
+use log::error;
+use std::{any::TypeId, collections::HashSet, time::Duration};
+
+const DAY_SIZE: f32 = 32.;
 
 pub struct Clock {
     date: DateTime<Local>,
+    /// Timezone shown in the bar, 0 is the local time and the others index the configured ones
+    zone: usize,
+    /// First day of the month shown in the calendar
+    month: NaiveDate,
+    selected: NaiveDate,
+    events: Vec<CalendarEvent>,
+    days_with_events: HashSet<NaiveDate>,
 }
 
 impl Default for Clock {
     fn default() -> Self {
-        Self { date: Local::now() }
+        let date = Local::now();
+        let today = date.date_naive();
+
+        Self {
+            date,
+            zone: 0,
+            month: first_of_month(today),
+            selected: today,
+            events: Vec::new(),
+            days_with_events: HashSet::new(),
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Update,
+    ToggleMenu(Id),
+    Scroll(ScrollDelta),
+    PreviousMonth,
+    NextMonth,
+    CurrentMonth,
+    SelectDay(NaiveDate),
+    EventsLoaded(Vec<CalendarEvent>),
 }
 
 impl Clock {
-    pub fn update(&mut self, message: Message) {
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &ClockModuleConfig,
+        outputs: &mut Outputs,
+    ) -> Task<app::Message> {
         match message {
             Message::Update => {
                 self.date = Local::now();
+                Task::none()
+            }
+            Message::ToggleMenu(id) => {
+                self.show_today();
+
+                let menu_task = outputs.toggle_menu(id, MenuType::Calendar);
+                if config.calendars.is_empty() {
+                    return menu_task;
+                }
+
+                // the calendars are read again every time the menu opens
+                let calendars = config.calendars.clone();
+                Task::batch(vec![
+                    menu_task,
+                    Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || ical::load_events(&calendars))
+                                .await
+                                .unwrap_or_else(|e| {
+                                    error!("Failed to load calendar events: {}", e);
+                                    Vec::new()
+                                })
+                        },
+                        |events| app::Message::Clock(Message::EventsLoaded(events)),
+                    ),
+                ])
+            }
+            Message::Scroll(delta) => {
+                let y = match delta {
+                    ScrollDelta::Lines { y, .. } | ScrollDelta::Pixels { y, .. } => y,
+                };
+                let zones = config.timezones.len() + 1;
+
+                if config.cycle_timezones && y != 0. {
+                    self.zone = if y > 0. {
+                        (self.zone + zones - 1) % zones
+                    } else {
+                        (self.zone + 1) % zones
+                    };
+                }
+                Task::none()
+            }
+            Message::PreviousMonth => {
+                self.set_month(self.month - Months::new(1));
+                Task::none()
+            }
+            Message::NextMonth => {
+                self.set_month(self.month + Months::new(1));
+                Task::none()
+            }
+            Message::CurrentMonth => {
+                self.show_today();
+                Task::none()
             }
+            Message::SelectDay(day) => {
+                self.selected = day;
+                self.set_month(first_of_month(day));
+                Task::none()
+            }
+            Message::EventsLoaded(events) => {
+                self.events = events;
+                self.update_days_with_events();
+                Task::none()
+            }
+        }
+    }
+
+    fn show_today(&mut self) {
+        let today = Local::now().date_naive();
+        self.selected = today;
+        self.set_month(first_of_month(today));
+    }
+
+    fn set_month(&mut self, month: NaiveDate) {
+        if self.month != month {
+            self.month = month;
+            self.update_days_with_events();
         }
     }
 
-    pub fn view(&self, format: &str) -> Element<Message> {
-        container(text(self.date.format(format).to_string()))
+    fn update_days_with_events(&mut self) {
+        self.days_with_events = month_grid(self.month)
+            .into_iter()
+            .flatten()
+            .filter(|day| self.events.iter().any(|event| event.occurs_on(*day)))
+            .collect();
+    }
+
+    fn zone_time(&self, timezone: &ClockTimezone, format: &str) -> String {
+        self.date
+            .with_timezone(&timezone.timezone)
+            .format(timezone.format.as_deref().unwrap_or(format))
+            .to_string()
+    }
+
+    pub fn view(&self, id: Id, config: &ClockModuleConfig) -> Element<Message> {
+        // a removed timezone falls back to the local time
+        let time = match self
+            .zone
+            .checked_sub(1)
+            .and_then(|zone| config.timezones.get(zone))
+        {
+            Some(timezone) => format!(
+                "{} {}",
+                timezone.label(),
+                self.zone_time(timezone, &config.format)
+            ),
+            None => self.date.format(&config.format).to_string(),
+        };
+
+        let clock = button(text(time))
             .padding([2, 8])
-            .style(left_header_pills)
+            .on_press(Message::ToggleMenu(id))
+            .style(HeaderButtonStyle::Left.into_style());
+
+        if config.cycle_timezones && !config.timezones.is_empty() {
+            mouse_area(clock).on_scroll(Message::Scroll).into()
+        } else {
+            clock.into()
+        }
+    }
+
+    pub fn menu_view(&self, config: &ClockModuleConfig) -> Element<Message> {
+        let today = Local::now().date_naive();
+
+        let world_clocks = (!config.timezones.is_empty()).then(|| {
+            Column::with_children(config.timezones.iter().map(|timezone| {
+                row!(
+                    text(timezone.label()).size(12).width(Length::Fill),
+                    text(self.zone_time(timezone, &config.format)).size(12),
+                )
+                .spacing(8)
+                .into()
+            }))
+            .spacing(4)
+        });
+
+        let header = row!(
+            button(icon(Icons::LeftArrow))
+                .padding([4, 8])
+                .on_press(Message::PreviousMonth)
+                .style(GhostButtonStyle.into_style()),
+            button(
+                text(self.month.format("%B %Y").to_string())
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Center)
+            )
+            .padding([4, 8])
+            .width(Length::Fill)
+            .on_press(Message::CurrentMonth)
+            .style(GhostButtonStyle.into_style()),
+            button(icon(Icons::RightArrow))
+                .padding([4, 8])
+                .on_press(Message::NextMonth)
+                .style(GhostButtonStyle.into_style()),
+        )
+        .align_y(Alignment::Center);
+
+        let weekdays = Row::with_children(
+            ["Wk", "Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+                .into_iter()
+                .map(|day| day_label(day.to_owned())),
+        );
+
+        let weeks = Column::with_children(month_grid(self.month).into_iter().map(|week| {
+            Row::new()
+                .push(day_label(week[0].iso_week().week().to_string()))
+                .extend(week.into_iter().map(|day| {
+                    let has_events = self.days_with_events.contains(&day);
+
+                    button(
+                        column!(text(day.day()).size(12))
+                            .push_maybe(has_events.then(|| text("•").size(8)))
+                            .width(Length::Fill)
+                            .align_x(Alignment::Center),
+                    )
+                    .width(Length::Fixed(DAY_SIZE))
+                    .height(Length::Fixed(DAY_SIZE))
+                    .padding(2)
+                    .on_press(Message::SelectDay(day))
+                    .style(
+                        CalendarDayButtonStyle {
+                            today: day == today,
+                            selected: day == self.selected,
+                            outside: day.month() != self.month.month(),
+                        }
+                        .into_style(),
+                    )
+                    .into()
+                }))
+                .into()
+        }));
+
+        let mut events = self
+            .events
+            .iter()
+            .filter(|event| event.occurs_on(self.selected))
+            .collect::<Vec<_>>();
+
+        let agenda: Element<Message> = if events.is_empty() {
+            text("No events").size(12).into()
+        } else {
+            events.sort_by_key(|event| (!event.all_day, event.start.time()));
+
+            Column::with_children(events.into_iter().map(|event| {
+                row!(
+                    text(if event.all_day {
+                        "All day".to_owned()
+                    } else {
+                        event.start.format("%H:%M").to_string()
+                    })
+                    .size(12)
+                    .width(Length::Fixed(50.)),
+                    text(event.summary.clone()).size(12),
+                )
+                .spacing(8)
+                .into()
+            }))
+            .spacing(4)
             .into()
+        };
+
+        Column::new()
+            .push_maybe(world_clocks)
+            .push_maybe((!config.timezones.is_empty()).then(|| horizontal_rule(1)))
+            .push(header)
+            .push(weekdays)
+            .push(weeks)
+            .push_maybe((!self.events.is_empty()).then(|| horizontal_rule(1)))
+            .push_maybe((!self.events.is_empty()).then(|| {
+                column!(
+                    text(self.selected.format("%A %d %B").to_string()).size(14),
+                    agenda
+                )
+                .spacing(8)
+            }))
+            .spacing(8)
+            .padding(16)
+            .width(Length::Fixed(DAY_SIZE * 8. + 32.))
+            .into()
+    }
+
+    pub fn subscription(&self, config: &ClockModuleConfig) -> Subscription<Message> {
+        let interval = config
+            .timezones
+            .iter()
+            .filter_map(|timezone| timezone.format.as_deref())
+            .chain(std::iter::once(config.format.as_str()))
+            .map(refresh_interval)
+            .min()
+            .unwrap_or(Duration::from_secs(60));
+
+        Subscription::run_with_id(
+            (TypeId::of::<Self>(), interval),
+            channel(10, move |mut output| async move {
+                loop {
+                    // wake up right after the displayed value changes
+                    let millis = interval.as_millis() as i64;
+                    let elapsed = Local::now().timestamp_millis().rem_euclid(millis);
+                    tokio::time::sleep(Duration::from_millis((millis - elapsed) as u64)).await;
+
+                    let _ = output.send(Message::Update).await;
+                }
+            }),
+        )
+    }
+}
+
+fn day_label<'a>(label: String) -> Element<'a, Message> {
+    container(text(label).size(12))
+        .width(Length::Fixed(DAY_SIZE))
+        .align_x(Horizontal::Center)
+        .into()
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// The weeks, starting on monday, that cover the month of `month`
+fn month_grid(month: NaiveDate) -> Vec<[NaiveDate; 7]> {
+    let mut day = month - Days::new(month.weekday().num_days_from_monday() as u64);
+    let mut weeks = Vec::new();
+
+    while weeks.is_empty() || day.month() == month.month() {
+        let week = std::array::from_fn(|offset| day + Days::new(offset as u64));
+        day = day + Days::new(7);
+        weeks.push(week);
+    }
+
+    weeks
+}
+
+/// Refresh every second only when the format shows seconds
+fn refresh_interval(format: &str) -> Duration {
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        // skip padding flags and widths, like `%-S` or `%3f`
+        let specifier = chars
+            .by_ref()
+            .find(|c| !matches!(c, '-' | '_' | '0'..='9' | '.' | ':' | '#'));
+
+        if matches!(
+            specifier,
+            Some('S' | 'T' | 'X' | 'r' | 's' | 'c' | '+' | 'f')
+        ) {
+            return Duration::from_secs(1);
+        }
+    }
+
+    Duration::from_secs(60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_interval_minutes() {
+        assert_eq!(refresh_interval("%H:%M"), Duration::from_secs(60));
+        assert_eq!(refresh_interval("%a %d %b %R"), Duration::from_secs(60));
+        assert_eq!(refresh_interval("100%% %H:%M"), Duration::from_secs(60));
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        every(Duration::from_secs(5)).map(|_| Message::Update)
+    #[test]
+    fn refresh_interval_seconds() {
+        assert_eq!(refresh_interval("%H:%M:%S"), Duration::from_secs(1));
+        assert_eq!(refresh_interval("%T"), Duration::from_secs(1));
+        assert_eq!(refresh_interval("%-S"), Duration::from_secs(1));
+        assert_eq!(refresh_interval("%H:%M:%3f"), Duration::from_secs(1));
     }
 }
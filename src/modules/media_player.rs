@@ -0,0 +1,352 @@
+use crate::{
+    app::MenuType,
+    components::icons::{icon, Icons},
+    config::{MediaPlayerModuleConfig, MediaPlayerScrollAction},
+    outputs::Outputs,
+    services::{
+        mpris::{MprisCommand, MprisPlayer, MprisService, PlaybackStatus},
+        ReadOnlyService, Service, ServiceEvent,
+    },
+    style::{GhostButtonStyle, HeaderButtonStyle, OutlineButtonStyle},
+    utils::format_clock,
+};
+use iced::{
+    alignment::Horizontal,
+    mouse::ScrollDelta,
+    time::every,
+    widget::{
+        button, column, container, image, mouse_area, progress_bar, row, slider, text, Column, Row,
+    },
+    window::Id,
+    Alignment, Element, Length, Subscription, Task,
+};
+use std::time::Duration;
+
+const VOLUME_STEP: f64 = 0.05;
+
+fn truncate(value: String, max_length: u32) -> String {
+    if value.chars().count() > max_length as usize {
+        format!(
+            "{}...",
+            value.chars().take(max_length as usize).collect::<String>()
+        )
+    } else {
+        value
+    }
+}
+
+#[derive(Default)]
+pub struct MediaPlayer {
+    service: Option<MprisService>,
+    selected: Option<String>,
+    seeking: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<MprisService>),
+    ToggleMenu(Id),
+    PlayPause,
+    Previous,
+    Next,
+    Seek(f64),
+    SeekRelease,
+    SelectPlayer(String),
+    Scroll(ScrollDelta),
+    Tick,
+}
+
+impl MediaPlayer {
+    pub fn is_playing(&self) -> bool {
+        self.service
+            .as_ref()
+            .is_some_and(|service| service.is_playing())
+    }
+
+    fn player(&self) -> Option<&MprisPlayer> {
+        self.service.as_ref()?.player(self.selected.as_deref())
+    }
+
+    fn command(
+        &mut self,
+        command: impl FnOnce(String) -> MprisCommand,
+    ) -> Task<crate::app::Message> {
+        let Some(name) = self.player().map(|player| player.service.clone()) else {
+            return Task::none();
+        };
+
+        match self.service.as_mut() {
+            Some(service) => service
+                .command(command(name))
+                .map(|event| crate::app::Message::MediaPlayer(Message::Event(event))),
+            None => Task::none(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &MediaPlayerModuleConfig,
+        outputs: &mut Outputs,
+    ) -> Task<crate::app::Message> {
+        match message {
+            Message::Event(event) => {
+                match event {
+                    ServiceEvent::Init(service) => {
+                        self.service = Some(service);
+                    }
+                    ServiceEvent::Update(data) => {
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(data);
+                        }
+                    }
+                    ServiceEvent::Error(_) => {}
+                }
+                Task::none()
+            }
+            Message::ToggleMenu(id) => {
+                self.seeking = None;
+                outputs.toggle_menu(id, MenuType::MediaPlayer)
+            }
+            Message::PlayPause => self.command(MprisCommand::PlayPause),
+            Message::Previous => self.command(MprisCommand::Previous),
+            Message::Next => self.command(MprisCommand::Next),
+            Message::Seek(position) => {
+                self.seeking = Some(position);
+                Task::none()
+            }
+            Message::SeekRelease => match self.seeking.take() {
+                Some(position) => self.command(|name| {
+                    MprisCommand::SetPosition(name, Duration::from_secs_f64(position))
+                }),
+                None => Task::none(),
+            },
+            Message::SelectPlayer(name) => {
+                self.selected = Some(name);
+                self.seeking = None;
+                Task::none()
+            }
+            Message::Scroll(delta) => {
+                let y = match delta {
+                    ScrollDelta::Lines { y, .. } | ScrollDelta::Pixels { y, .. } => y,
+                };
+                if y == 0. {
+                    return Task::none();
+                }
+
+                match config.scroll_action {
+                    Some(MediaPlayerScrollAction::Volume) => {
+                        let Some(volume) = self.player().and_then(|player| player.volume) else {
+                            return Task::none();
+                        };
+                        let volume = if y > 0. {
+                            volume + VOLUME_STEP
+                        } else {
+                            volume - VOLUME_STEP
+                        };
+
+                        self.command(|name| MprisCommand::SetVolume(name, volume))
+                    }
+                    Some(MediaPlayerScrollAction::Player) => {
+                        let players = self
+                            .service
+                            .as_ref()
+                            .map(|service| service.players.as_slice())
+                            .unwrap_or_default();
+                        let current = self.player().and_then(|player| {
+                            players.iter().position(|p| p.service == player.service)
+                        });
+
+                        if let Some(current) = current {
+                            let next = if y > 0. {
+                                (current + players.len() - 1) % players.len()
+                            } else {
+                                (current + 1) % players.len()
+                            };
+                            self.selected = Some(players[next].service.clone());
+                        }
+
+                        Task::none()
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::Tick => Task::none(),
+        }
+    }
+
+    pub fn view(&self, id: Id, config: &MediaPlayerModuleConfig) -> Option<Element<Message>> {
+        let player = self
+            .player()
+            .filter(|player| player.status != PlaybackStatus::Stopped)?;
+
+        let label = player
+            .metadata
+            .label()
+            .unwrap_or_else(|| player.identity.clone());
+
+        let content = button(
+            row!(
+                icon(if player.status == PlaybackStatus::Playing {
+                    Icons::MediaPlay
+                } else {
+                    Icons::MediaPause
+                }),
+                text(truncate(label, config.max_title_length)).size(12),
+            )
+            .spacing(8)
+            .align_y(Alignment::Center),
+        )
+        .padding([2, 8])
+        .on_press(Message::ToggleMenu(id))
+        .style(HeaderButtonStyle::Full.into_style());
+
+        Some(match config.scroll_action {
+            Some(_) => mouse_area(content).on_scroll(Message::Scroll).into(),
+            None => content.into(),
+        })
+    }
+
+    pub fn menu_view(&self) -> Element<Message> {
+        let Some(player) = self.player() else {
+            return container(text("No media player running"))
+                .padding(16)
+                .into();
+        };
+        let players = self
+            .service
+            .as_ref()
+            .map(|service| service.players.as_slice())
+            .unwrap_or_default();
+
+        let switcher = (players.len() > 1).then(|| {
+            Row::with_children(players.iter().map(|p| {
+                let button = button(text(p.identity.clone()).size(12))
+                    .padding([4, 8])
+                    .on_press(Message::SelectPlayer(p.service.clone()));
+
+                if p.service == player.service {
+                    button.style(OutlineButtonStyle.into_style()).into()
+                } else {
+                    button.style(GhostButtonStyle.into_style()).into()
+                }
+            }))
+            .spacing(4)
+        });
+
+        let track = row!()
+            .push_maybe(player.metadata.art_path().map(|path| {
+                image(image::Handle::from_path(path))
+                    .width(Length::Fixed(96.))
+                    .height(Length::Fixed(96.))
+            }))
+            .push(
+                Column::new()
+                    .push(
+                        text(
+                            player
+                                .metadata
+                                .title
+                                .clone()
+                                .unwrap_or_else(|| player.identity.clone()),
+                        )
+                        .size(16),
+                    )
+                    .push_maybe(
+                        (!player.metadata.artists.is_empty())
+                            .then(|| text(player.metadata.artists.join(", ")).size(12)),
+                    )
+                    .push_maybe(
+                        player
+                            .metadata
+                            .album
+                            .clone()
+                            .map(|album| text(album).size(12)),
+                    )
+                    .spacing(4)
+                    .width(Length::Fill),
+            )
+            .spacing(16)
+            .align_y(Alignment::Center);
+
+        let progress = player
+            .metadata
+            .length
+            .zip(player.position())
+            .map(|(length, position)| {
+                let length = length.as_secs_f64();
+                let position = self.seeking.unwrap_or(position.as_secs_f64());
+
+                let bar: Element<Message> = if player.can_seek {
+                    slider(0.0..=length, position, Message::Seek)
+                        .on_release(Message::SeekRelease)
+                        .step(1.)
+                        .width(Length::Fill)
+                        .into()
+                } else {
+                    progress_bar(0.0..=length as f32, position as f32)
+                        .height(Length::Fixed(4.))
+                        .into()
+                };
+
+                row!(
+                    text(format_clock(Duration::from_secs_f64(position))).size(12),
+                    bar,
+                    text(format_clock(Duration::from_secs_f64(length))).size(12),
+                )
+                .spacing(8)
+                .align_y(Alignment::Center)
+            });
+
+        let control = |icon_type: Icons, message: Message, enabled: bool| {
+            button(icon(icon_type).size(20))
+                .padding([8, 12])
+                .on_press_maybe(enabled.then_some(message))
+                .style(GhostButtonStyle.into_style())
+        };
+        let controls = container(
+            row!(
+                control(
+                    Icons::MediaPrevious,
+                    Message::Previous,
+                    player.can_go_previous
+                ),
+                control(
+                    if player.status == PlaybackStatus::Playing {
+                        Icons::MediaPause
+                    } else {
+                        Icons::MediaPlay
+                    },
+                    Message::PlayPause,
+                    true
+                ),
+                control(Icons::MediaNext, Message::Next, player.can_go_next),
+            )
+            .spacing(16)
+            .align_y(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .align_x(Horizontal::Center);
+
+        column!()
+            .push_maybe(switcher)
+            .push(track)
+            .push_maybe(progress)
+            .push(controls)
+            .spacing(16)
+            .padding(16)
+            .width(Length::Fixed(350.))
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        // the position isn't notified while playing, redraw the progress every second
+        let tick = if self.is_playing() {
+            every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![MprisService::subscribe().map(Message::Event), tick])
+    }
+}
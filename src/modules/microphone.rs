@@ -0,0 +1,43 @@
+use crate::{
+    app::Message,
+    components::icons::{icon, Icons},
+    modules::settings::{self, audio::AudioMessage},
+    services::{audio::AudioData, privacy::PrivacyData},
+    style::HeaderButtonStyle,
+};
+use iced::{widget::button, Element, Theme};
+
+pub fn microphone<'a>(
+    audio: &AudioData,
+    privacy: Option<&PrivacyData>,
+) -> Option<Element<'a, Message>> {
+    let source = audio
+        .sources
+        .iter()
+        .find(|source| source.name == audio.server_info.default_source)?;
+
+    let is_mute = source.is_mute;
+    let is_capturing = privacy.is_some_and(|privacy| privacy.microphone_access());
+
+    Some(
+        button(icon(if is_mute { Icons::Mic0 } else { Icons::Mic1 }))
+            .padding([2, 7])
+            .on_press(Message::Settings(settings::Message::Audio(
+                AudioMessage::ToggleSourceMute,
+            )))
+            .style(move |theme: &Theme, status| {
+                let mut style = HeaderButtonStyle::Full.into_style()(theme, status);
+                // an app is listening: red when the mic is live,
+                // warning color when it's muted (e.g. waiting for push-to-talk)
+                if is_capturing {
+                    style.text_color = if is_mute {
+                        theme.extended_palette().danger.weak.color
+                    } else {
+                        theme.palette().danger
+                    };
+                }
+                style
+            })
+            .into(),
+    )
+}
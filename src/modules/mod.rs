@@ -1,11 +1,18 @@
+pub mod bluetooth;
 pub mod clipboard;
 pub mod clock;
 pub mod keyboard_layout;
 pub mod keyboard_submap;
 pub mod launcher;
+pub mod media_player;
+pub mod microphone;
+pub mod network_speed;
+pub mod notifications;
 pub mod privacy;
 pub mod settings;
 pub mod system_info;
+pub mod timer;
 pub mod title;
+pub mod tray;
 pub mod updates;
 pub mod workspaces;
@@ -0,0 +1,214 @@
+use crate::{
+    app::MenuType,
+    components::icons::{icon, Icons},
+    config::NetworkSpeedModuleConfig,
+    outputs::Outputs,
+    style::HeaderButtonStyle,
+};
+use iced::{
+    time::every,
+    widget::{button, column, container, horizontal_rule, row, text, Column},
+    window::Id,
+    Alignment, Element, Length, Subscription, Task, Theme,
+};
+use std::time::{Duration, Instant};
+use sysinfo::Networks;
+
+const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+fn format_bytes(bytes: f64) -> String {
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec))
+}
+
+/// The interface used by the default route
+fn default_route_interface() -> Option<String> {
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let interface = fields.next()?;
+        let destination = fields.next()?;
+
+        (destination == "00000000").then(|| interface.to_string())
+    })
+}
+
+#[derive(Debug, Clone)]
+struct InterfaceData {
+    name: String,
+    rx_rate: f64,
+    tx_rate: f64,
+    total_received: u64,
+    total_transmitted: u64,
+}
+
+pub struct NetworkSpeed {
+    networks: Networks,
+    last_refresh: Instant,
+    interfaces: Vec<InterfaceData>,
+    default_interface: Option<String>,
+}
+
+impl Default for NetworkSpeed {
+    fn default() -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            last_refresh: Instant::now(),
+            interfaces: Vec::new(),
+            default_interface: default_route_interface(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Update,
+    ToggleMenu(Id),
+}
+
+impl NetworkSpeed {
+    pub fn update(&mut self, message: Message, outputs: &mut Outputs) -> Task<crate::app::Message> {
+        match message {
+            Message::Update => {
+                self.networks.refresh_list();
+                let elapsed = self.last_refresh.elapsed().as_secs_f64().max(0.1);
+                self.last_refresh = Instant::now();
+
+                let mut interfaces = self
+                    .networks
+                    .iter()
+                    .filter(|(name, _)| name.as_str() != "lo")
+                    .map(|(name, data)| InterfaceData {
+                        name: name.clone(),
+                        rx_rate: data.received() as f64 / elapsed,
+                        tx_rate: data.transmitted() as f64 / elapsed,
+                        total_received: data.total_received(),
+                        total_transmitted: data.total_transmitted(),
+                    })
+                    .collect::<Vec<_>>();
+                interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+                self.interfaces = interfaces;
+                self.default_interface = default_route_interface();
+
+                Task::none()
+            }
+            Message::ToggleMenu(id) => outputs.toggle_menu(id, MenuType::NetworkSpeed),
+        }
+    }
+
+    fn primary_interface(&self, config: &NetworkSpeedModuleConfig) -> Option<&InterfaceData> {
+        let name = config
+            .interface
+            .as_ref()
+            .or(self.default_interface.as_ref())?;
+
+        self.interfaces.iter().find(|i| &i.name == name)
+    }
+
+    pub fn view(&self, id: Id, config: &NetworkSpeedModuleConfig) -> Option<Element<Message>> {
+        let interface = self.primary_interface(config)?;
+
+        Some(
+            button(
+                row!(
+                    icon(Icons::Download),
+                    text(format_rate(interface.rx_rate)),
+                    icon(Icons::Upload),
+                    text(format_rate(interface.tx_rate)),
+                )
+                .align_y(Alignment::Center)
+                .spacing(4),
+            )
+            .padding([2, 7])
+            .style(HeaderButtonStyle::Full.into_style())
+            .on_press(Message::ToggleMenu(id))
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self, config: &NetworkSpeedModuleConfig) -> Element<Message> {
+        let primary = self.primary_interface(config).map(|i| i.name.as_str());
+
+        Column::with_children(
+            self.interfaces
+                .iter()
+                .enumerate()
+                .flat_map(|(index, interface)| {
+                    let is_primary = primary == Some(interface.name.as_str());
+
+                    let details: Element<Message> = column!(
+                        container(text(interface.name.clone())).style(move |theme: &Theme| {
+                            container::Style {
+                                text_color: if is_primary {
+                                    Some(theme.palette().success)
+                                } else {
+                                    None
+                                },
+                                ..Default::default()
+                            }
+                        }),
+                        row!(
+                            icon(Icons::Download),
+                            text(format_rate(interface.rx_rate)).width(Length::Fill),
+                            text(format!(
+                                "Total {}",
+                                format_bytes(interface.total_received as f64)
+                            ))
+                            .size(12),
+                        )
+                        .align_y(Alignment::Center)
+                        .spacing(8),
+                        row!(
+                            icon(Icons::Upload),
+                            text(format_rate(interface.tx_rate)).width(Length::Fill),
+                            text(format!(
+                                "Total {}",
+                                format_bytes(interface.total_transmitted as f64)
+                            ))
+                            .size(12),
+                        )
+                        .align_y(Alignment::Center)
+                        .spacing(8),
+                    )
+                    .spacing(4)
+                    .into();
+
+                    if index > 0 {
+                        vec![horizontal_rule(1).into(), details]
+                    } else {
+                        vec![details]
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+        .push_maybe(
+            self.interfaces
+                .is_empty()
+                .then(|| text("No network interfaces")),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(300)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        every(Duration::from_secs(1)).map(|_| Message::Update)
+    }
+}
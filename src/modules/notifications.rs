@@ -0,0 +1,384 @@
+use crate::{
+    app::MenuType,
+    components::icons::{icon, Icons},
+    config::{NotificationsModuleConfig, Position},
+    outputs::Outputs,
+    services::{
+        notifications::{
+            Notification, NotificationEvent, NotificationsCommand, NotificationsService,
+        },
+        ReadOnlyService, Service, ServiceEvent,
+    },
+    style::{GhostButtonStyle, HeaderButtonStyle, OutlineButtonStyle},
+    utils::notifications::Urgency,
+};
+use iced::{
+    alignment::Horizontal,
+    platform_specific::shell::commands::layer_surface::{
+        destroy_layer_surface, get_layer_surface, set_size, Anchor, KeyboardInteractivity, Layer,
+    },
+    runtime::platform_specific::wayland::layer_surface::{IcedMargin, SctkLayerSurfaceSettings},
+    time::every,
+    widget::{button, column, container, mouse_area, row, scrollable, text, Column, Row},
+    window::Id,
+    Alignment, Background, Border, Element, Length, Subscription, Task, Theme,
+};
+use std::time::{Duration, Instant};
+
+const POPUP_WIDTH: u32 = 350;
+const POPUP_HEIGHT: u32 = 110;
+const POPUP_SPACING: u32 = 8;
+const MAX_POPUPS: usize = 4;
+
+struct Popup {
+    id: u32,
+    deadline: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct Notifications {
+    service: Option<NotificationsService>,
+    popups: Vec<Popup>,
+    popup_surface: Option<Id>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<NotificationsService>),
+    ToggleMenu(Id),
+    Dismiss(u32),
+    DismissAll,
+    InvokeAction(u32, String),
+    ClosePopup(u32),
+    Tick,
+}
+
+impl Notifications {
+    pub fn is_running(&self) -> bool {
+        self.service.is_some()
+    }
+
+    pub fn popup_surface(&self) -> Option<Id> {
+        self.popup_surface
+    }
+
+    /// Release the notification daemon and its popups
+    pub fn stop<Message: 'static>(&mut self) -> Task<Message> {
+        self.service = None;
+        self.popups.clear();
+
+        self.popup_surface
+            .take()
+            .map_or_else(Task::none, destroy_layer_surface)
+    }
+
+    fn command(&mut self, command: NotificationsCommand) -> Task<crate::app::Message> {
+        match self.service.as_mut() {
+            Some(service) => service
+                .command(command)
+                .map(|event| crate::app::Message::Notifications(Message::Event(event))),
+            None => Task::none(),
+        }
+    }
+
+    /// Create, resize or destroy the popup surface to fit the visible popups
+    fn sync_popup_surface<Message: 'static>(
+        &mut self,
+        position: Position,
+        outputs: &Outputs,
+    ) -> Task<Message> {
+        let count = self.popups.len() as u32;
+        let height = count * POPUP_HEIGHT + count.saturating_sub(1) * POPUP_SPACING;
+
+        match self.popup_surface {
+            Some(id) if count == 0 => {
+                self.popup_surface = None;
+                destroy_layer_surface(id)
+            }
+            Some(id) => set_size(id, Some(POPUP_WIDTH), Some(height)),
+            None if count == 0 => Task::none(),
+            None => {
+                let id = Id::unique();
+                self.popup_surface = Some(id);
+
+                // a zero exclusive zone makes the compositor place the popups under the bar
+                get_layer_surface(SctkLayerSurfaceSettings {
+                    id,
+                    size: Some((Some(POPUP_WIDTH), Some(height))),
+                    layer: Layer::Overlay,
+                    pointer_interactivity: true,
+                    keyboard_interactivity: KeyboardInteractivity::None,
+                    exclusive_zone: 0,
+                    output: outputs.first_output(),
+                    namespace: "ashell-notifications".to_owned(),
+                    anchor: match position {
+                        Position::Top => Anchor::TOP,
+                        Position::Bottom => Anchor::BOTTOM,
+                    } | Anchor::RIGHT,
+                    margin: IcedMargin {
+                        top: 8,
+                        right: 8,
+                        bottom: 8,
+                        left: 0,
+                    },
+                })
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &NotificationsModuleConfig,
+        do_not_disturb: bool,
+        position: Position,
+        outputs: &mut Outputs,
+    ) -> Task<crate::app::Message> {
+        match message {
+            Message::Event(event) => {
+                match event {
+                    ServiceEvent::Init(service) => {
+                        self.service = Some(service);
+                    }
+                    ServiceEvent::Update(event) => {
+                        match &event {
+                            NotificationEvent::Received(notification) => {
+                                self.popups.retain(|popup| popup.id != notification.id);
+
+                                if !do_not_disturb || notification.urgency == Urgency::Critical {
+                                    self.popups.insert(
+                                        0,
+                                        Popup {
+                                            id: notification.id,
+                                            deadline: notification
+                                                .timeout(Duration::from_secs(config.timeout))
+                                                .map(|timeout| Instant::now() + timeout),
+                                        },
+                                    );
+                                    self.popups.truncate(MAX_POPUPS);
+                                }
+                            }
+                            NotificationEvent::Closed(id) => {
+                                self.popups.retain(|popup| popup.id != *id);
+                            }
+                            NotificationEvent::Cleared => self.popups.clear(),
+                        }
+
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(event);
+                        }
+                    }
+                    ServiceEvent::Error(_) => {}
+                }
+
+                self.sync_popup_surface(position, outputs)
+            }
+            Message::ToggleMenu(id) => outputs.toggle_menu(id, MenuType::Notifications),
+            Message::Dismiss(id) => self.command(NotificationsCommand::Dismiss(id)),
+            Message::DismissAll => {
+                let ids = self
+                    .service
+                    .as_ref()
+                    .map(|service| service.notifications.iter().map(|n| n.id).collect())
+                    .unwrap_or_default();
+
+                self.command(NotificationsCommand::DismissAll(ids))
+            }
+            Message::InvokeAction(id, key) => {
+                self.command(NotificationsCommand::InvokeAction(id, key))
+            }
+            Message::ClosePopup(id) => {
+                self.popups.retain(|popup| popup.id != id);
+
+                self.sync_popup_surface(position, outputs)
+            }
+            Message::Tick => {
+                let now = Instant::now();
+                let count = self.popups.len();
+                self.popups.retain(|popup| match popup.deadline {
+                    Some(deadline) => deadline > now,
+                    None => true,
+                });
+
+                if self.popups.len() != count {
+                    self.sync_popup_surface(position, outputs)
+                } else {
+                    Task::none()
+                }
+            }
+        }
+    }
+
+    pub fn view(&self, id: Id, do_not_disturb: bool) -> Option<Element<Message>> {
+        let count = self.service.as_ref()?.notifications.len();
+
+        Some(
+            button(
+                row!(icon(if do_not_disturb {
+                    Icons::BellOff
+                } else {
+                    Icons::Bell
+                }))
+                .push_maybe((count > 0).then(|| text(count).size(12)))
+                .spacing(4)
+                .align_y(Alignment::Center),
+            )
+            .padding([2, 8])
+            .on_press(Message::ToggleMenu(id))
+            .style(HeaderButtonStyle::Full.into_style())
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self) -> Element<Message> {
+        let notifications = self
+            .service
+            .as_ref()
+            .map(|service| service.notifications.as_slice())
+            .unwrap_or_default();
+
+        let header = row!(
+            text("Notifications").size(16).width(Length::Fill),
+            button(text("Clear all").size(12))
+                .padding([4, 8])
+                .on_press_maybe((!notifications.is_empty()).then_some(Message::DismissAll))
+                .style(GhostButtonStyle.into_style()),
+        )
+        .align_y(Alignment::Center);
+
+        let content: Element<Message> = if notifications.is_empty() {
+            container(text("No notifications").size(12))
+                .width(Length::Fill)
+                .align_x(Horizontal::Center)
+                .padding(16)
+                .into()
+        } else {
+            container(scrollable(
+                Column::with_children(
+                    notifications
+                        .iter()
+                        .map(|notification| notification_card(notification, false)),
+                )
+                .spacing(8),
+            ))
+            .max_height(500.)
+            .into()
+        };
+
+        column!(header, content)
+            .spacing(16)
+            .padding(16)
+            .width(Length::Fixed(POPUP_WIDTH as f32))
+            .into()
+    }
+
+    pub fn popups_view(&self) -> Element<Message> {
+        let notifications = self
+            .service
+            .as_ref()
+            .map(|service| service.notifications.as_slice())
+            .unwrap_or_default();
+
+        Column::with_children(self.popups.iter().filter_map(|popup| {
+            notifications
+                .iter()
+                .find(|notification| notification.id == popup.id)
+                .map(|notification| {
+                    container(notification_card(notification, true))
+                        .height(Length::Fixed(POPUP_HEIGHT as f32))
+                        .clip(true)
+                        .into()
+                })
+        }))
+        .spacing(POPUP_SPACING as f32)
+        .into()
+    }
+
+    pub fn subscription(&self, config: &NotificationsModuleConfig) -> Subscription<Message> {
+        if !config.daemon {
+            return Subscription::none();
+        }
+
+        let tick = if self.popups.iter().any(|popup| popup.deadline.is_some()) {
+            every(Duration::from_millis(500)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![
+            NotificationsService::subscribe().map(Message::Event),
+            tick,
+        ])
+    }
+}
+
+/// A popup closes only its bubble, the history entry stays until dismissed
+fn notification_card(notification: &Notification, popup: bool) -> Element<Message> {
+    let close = if popup {
+        Message::ClosePopup(notification.id)
+    } else {
+        Message::Dismiss(notification.id)
+    };
+
+    let actions = notification
+        .actions
+        .iter()
+        .filter(|(key, _)| key != "default")
+        .map(|(key, label)| {
+            button(text(label.clone()).size(12))
+                .padding([4, 8])
+                .on_press(Message::InvokeAction(notification.id, key.clone()))
+                .style(OutlineButtonStyle.into_style())
+                .into()
+        })
+        .collect::<Vec<_>>();
+
+    let content = Column::new()
+        .push(
+            row!(
+                text(notification.app_name.clone())
+                    .size(10)
+                    .width(Length::Fill),
+                text(notification.timestamp.format("%H:%M").to_string()).size(10),
+                button(icon(Icons::Close).size(12))
+                    .padding([2, 4])
+                    .on_press(close)
+                    .style(GhostButtonStyle.into_style()),
+            )
+            .spacing(8)
+            .align_y(Alignment::Center),
+        )
+        .push(text(notification.summary.clone()).size(14))
+        .push_maybe(
+            (!notification.body.is_empty()).then(|| text(notification.body.clone()).size(12)),
+        )
+        .push_maybe((!actions.is_empty()).then(|| Row::with_children(actions).spacing(8)))
+        .spacing(4);
+
+    let urgency = notification.urgency;
+    let card = container(content)
+        .padding(12)
+        .width(Length::Fill)
+        .style(move |theme: &Theme| container::Style {
+            background: Background::Color(theme.extended_palette().secondary.strong.color).into(),
+            border: Border::default()
+                .rounded(16)
+                .width(1)
+                .color(if urgency == Urgency::Critical {
+                    theme.palette().danger
+                } else {
+                    theme.extended_palette().secondary.base.color
+                }),
+            text_color: (urgency == Urgency::Low)
+                .then(|| theme.extended_palette().secondary.base.text),
+            ..Default::default()
+        });
+
+    if notification.has_default_action() {
+        mouse_area(card)
+            .on_press(Message::InvokeAction(notification.id, "default".to_owned()))
+            .into()
+    } else {
+        card.into()
+    }
+}
@@ -2,36 +2,48 @@ use super::{quick_setting_button, sub_menu_wrapper, Message, SubMenu};
 use crate::{
     components::icons::{icon, Icons},
     services::{
-        bluetooth::{BluetoothData, BluetoothService, BluetoothState},
+        bluetooth::{BluetoothData, BluetoothDevice, BluetoothService, BluetoothState},
         ServiceEvent,
     },
-    style::GhostButtonStyle,
+    style::{GhostButtonStyle, SettingsButtonStyle},
 };
 use iced::{
-    widget::{button, column, container, horizontal_rule, row, text, Column, Row},
+    widget::{button, column, container, horizontal_rule, row, scrollable, text, toggler, Column},
     window::Id,
-    Element, Length, Theme,
+    Alignment, Element, Length, Theme,
 };
+use zbus::zvariant::OwnedObjectPath;
 
 #[derive(Debug, Clone)]
 pub enum BluetoothMessage {
     Event(ServiceEvent<BluetoothService>),
     Toggle,
+    ToggleDiscovery,
+    SelectDevice(OwnedObjectPath),
+    Pair(OwnedObjectPath),
+    Connect(OwnedObjectPath),
+    Disconnect(OwnedObjectPath),
+    SetTrusted(OwnedObjectPath, bool),
+    Remove(OwnedObjectPath),
     More(Id),
 }
 
 impl BluetoothData {
-    pub fn get_quick_setting_button(
-        &self,
+    pub fn get_quick_setting_button<'a>(
+        &'a self,
         id: Id,
         sub_menu: Option<SubMenu>,
+        selected_device: Option<&'a OwnedObjectPath>,
         show_more_button: bool,
-    ) -> Option<(Element<Message>, Option<Element<Message>>)> {
+    ) -> Option<(Element<'a, Message>, Option<Element<'a, Message>>)> {
         Some((
             quick_setting_button(
                 Icons::Bluetooth,
                 "Bluetooth".to_owned(),
-                None,
+                self.devices
+                    .iter()
+                    .find(|d| d.connected)
+                    .map(|d| d.name.clone()),
                 self.state == BluetoothState::Active,
                 Message::Bluetooth(BluetoothMessage::Toggle),
                 Some((
@@ -43,28 +55,63 @@ impl BluetoothData {
             ),
             sub_menu
                 .filter(|menu_type| *menu_type == SubMenu::Bluetooth)
-                .map(|_| sub_menu_wrapper(self.bluetooth_menu(id, show_more_button))),
+                .map(|_| {
+                    sub_menu_wrapper(self.bluetooth_menu(id, selected_device, show_more_button))
+                }),
         ))
     }
 
-    pub fn bluetooth_menu(&self, id: Id, show_more_button: bool) -> Element<Message> {
-        let main = if self.devices.is_empty() {
-            text("No devices connected").into()
-        } else {
-            Column::with_children(
-                self.devices
-                    .iter()
-                    .map(|d| {
-                        Row::new()
-                            .push(text(d.name.to_string()).width(Length::Fill))
-                            .push_maybe(d.battery.map(Self::battery_level))
-                            .into()
-                    })
-                    .collect::<Vec<Element<Message>>>(),
+    pub fn bluetooth_menu<'a>(
+        &'a self,
+        id: Id,
+        selected_device: Option<&'a OwnedObjectPath>,
+        show_more_button: bool,
+    ) -> Element<'a, Message> {
+        let paired = self
+            .devices
+            .iter()
+            .filter(|d| d.paired)
+            .map(|d| Self::paired_device_view(d, selected_device == Some(&d.path)))
+            .collect::<Vec<_>>();
+        let available = self
+            .devices
+            .iter()
+            .filter(|d| !d.paired)
+            .map(Self::available_device_view)
+            .collect::<Vec<_>>();
+
+        let main = column!(
+            row!(
+                text("Devices").width(Length::Fill),
+                text(if self.discovering { "Searching..." } else { "" }).size(12),
+                button(icon(Icons::Refresh))
+                    .padding([4, 10])
+                    .style(SettingsButtonStyle.into_style())
+                    .on_press(Message::Bluetooth(BluetoothMessage::ToggleDiscovery)),
             )
             .spacing(8)
-            .into()
-        };
+            .width(Length::Fill)
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            container(scrollable(
+                Column::new()
+                    .push(if paired.is_empty() {
+                        Element::from(text("No paired devices"))
+                    } else {
+                        Column::with_children(paired).spacing(4).into()
+                    })
+                    .push_maybe((!available.is_empty()).then(|| {
+                        column!(
+                            text("Available devices").size(12),
+                            Column::with_children(available).spacing(4),
+                        )
+                        .spacing(8)
+                    }))
+                    .spacing(12),
+            ))
+            .max_height(300),
+        )
+        .spacing(8);
 
         if show_more_button {
             column!(
@@ -79,10 +126,87 @@ impl BluetoothData {
             .spacing(12)
             .into()
         } else {
-            main
+            main.into()
         }
     }
 
+    fn paired_device_view(device: &BluetoothDevice, is_selected: bool) -> Element<Message> {
+        let connected = device.connected;
+
+        column!(row!(button(
+            container(text(device.name.clone())).style(move |theme: &Theme| {
+                container::Style {
+                    text_color: if connected {
+                        Some(theme.palette().success)
+                    } else {
+                        None
+                    },
+                    ..Default::default()
+                }
+            })
+        )
+        .style(GhostButtonStyle.into_style())
+        .padding([8, 8])
+        .width(Length::Fill)
+        .on_press(Message::Bluetooth(BluetoothMessage::SelectDevice(
+            device.path.clone()
+        ))),)
+        .push_maybe(device.battery.map(Self::battery_level))
+        .push(
+            button(if connected { "Disconnect" } else { "Connect" })
+                .padding([4, 12])
+                .style(SettingsButtonStyle.into_style())
+                .on_press(Message::Bluetooth(if connected {
+                    BluetoothMessage::Disconnect(device.path.clone())
+                } else {
+                    BluetoothMessage::Connect(device.path.clone())
+                })),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center),)
+        .push_maybe(is_selected.then(|| {
+            row!(
+                text(device.address.clone()).size(12).width(Length::Fill),
+                toggler(device.trusted)
+                    .label("Trusted")
+                    .on_toggle(|trusted| {
+                        Message::Bluetooth(BluetoothMessage::SetTrusted(
+                            device.path.clone(),
+                            trusted,
+                        ))
+                    })
+                    .width(Length::Shrink),
+                button("Remove")
+                    .padding([4, 12])
+                    .style(SettingsButtonStyle.into_style())
+                    .on_press(Message::Bluetooth(BluetoothMessage::Remove(
+                        device.path.clone()
+                    ))),
+            )
+            .spacing(8)
+            .padding([0, 8])
+            .align_y(Alignment::Center)
+        }))
+        .spacing(4)
+        .into()
+    }
+
+    fn available_device_view(device: &BluetoothDevice) -> Element<Message> {
+        row!(
+            text(device.name.clone()).width(Length::Fill),
+            button("Pair")
+                .padding([4, 12])
+                .style(SettingsButtonStyle.into_style())
+                .on_press(Message::Bluetooth(BluetoothMessage::Pair(
+                    device.path.clone()
+                ))),
+        )
+        .spacing(8)
+        .padding([0, 8])
+        .align_y(Alignment::Center)
+        .into()
+    }
+
     fn battery_level<'a>(battery: u8) -> Element<'a, Message> {
         container(
             row!(
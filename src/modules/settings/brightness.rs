@@ -1,12 +1,14 @@
 use crate::{
     components::icons::{icon, Icons},
     services::{
-        brightness::{BrightnessData, BrightnessService},
+        brightness::{BrightnessData, BrightnessDevice, BrightnessDeviceKind, BrightnessService},
+        keyboard_backlight::{KeyboardBacklightData, KeyboardBacklightService},
         ServiceEvent,
     },
+    style::GhostButtonStyle,
 };
 use iced::{
-    widget::{container, row, slider},
+    widget::{button, column, container, row, slider, text, Column},
     Alignment, Element, Length,
 };
 
@@ -15,15 +17,80 @@ use super::Message;
 #[derive(Debug, Clone)]
 pub enum BrightnessMessage {
     Event(ServiceEvent<BrightnessService>),
+    Change(String, u32),
+    Release(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum KeyboardBacklightMessage {
+    Event(ServiceEvent<KeyboardBacklightService>),
     Change(u32),
+    Toggle,
 }
 
-impl BrightnessData {
-    pub fn brightness_slider(&self) -> Element<Message> {
+impl BrightnessDevice {
+    fn slider(&self, show_name: bool) -> Element<Message> {
+        let max = self.max.max(1);
+        // DDC/CI writes are slow, the value is sent to the monitor when the slider is released
+        let is_ddc = matches!(self.kind, BrightnessDeviceKind::Ddc(_));
+
+        let mut slider = slider(0..=100, self.current * 100 / max, move |v| {
+            Message::Brightness(BrightnessMessage::Change(self.name.clone(), v * max / 100))
+        })
+        .step(1_u32)
+        .width(Length::Fill);
+        if is_ddc {
+            slider = slider.on_release(Message::Brightness(BrightnessMessage::Release(
+                self.name.clone(),
+            )));
+        }
+
         row!(
             container(icon(Icons::Brightness)).padding([8, 11]),
-            slider(0..=100, self.current * 100 / self.max, |v| {
-                Message::Brightness(BrightnessMessage::Change(v * self.max / 100))
+            column!()
+                .push_maybe(show_name.then(|| text(self.name.clone()).size(12)))
+                .push(slider)
+                .width(Length::Fill),
+        )
+        .align_y(Alignment::Center)
+        .spacing(8)
+        .into()
+    }
+}
+
+impl BrightnessData {
+    /// One slider for each device, `devices` restricts the shown ones when not empty
+    pub fn brightness_sliders(&self, devices: &[String]) -> Option<Element<Message>> {
+        let visible = self
+            .devices
+            .iter()
+            .filter(|d| devices.is_empty() || devices.contains(&d.name))
+            .collect::<Vec<_>>();
+        let show_name = visible.len() > 1;
+
+        if visible.is_empty() {
+            None
+        } else {
+            Some(
+                Column::with_children(visible.into_iter().map(|d| d.slider(show_name)))
+                    .spacing(8)
+                    .into(),
+            )
+        }
+    }
+}
+
+impl KeyboardBacklightData {
+    /// Keyboards usually expose only a few levels, the slider moves on the raw
+    /// values and the icon switches the backlight off and back to the max level
+    pub fn keyboard_backlight_slider(&self) -> Element<Message> {
+        row!(
+            button(icon(Icons::Keyboard))
+                .padding([8, 11])
+                .on_press(Message::KeyboardBacklight(KeyboardBacklightMessage::Toggle))
+                .style(GhostButtonStyle.into_style()),
+            slider(0..=self.max, self.current, |v| {
+                Message::KeyboardBacklight(KeyboardBacklightMessage::Change(v))
             })
             .step(1_u32)
             .width(Length::Fill),
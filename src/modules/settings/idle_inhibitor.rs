@@ -0,0 +1,184 @@
+use super::{quick_setting_button, sub_menu_wrapper, Message, SubMenu};
+use crate::{
+    components::icons::{icon, Icons},
+    services::idle_inhibitor::{AutoInhibitReason, IdleInhibitorManager},
+    style::GhostButtonStyle,
+    utils::format_clock,
+};
+use hyprland::{
+    event_listener::AsyncEventListener,
+    shared::{HyprData, HyprDataVec},
+};
+use iced::{
+    futures::channel::mpsc::Sender,
+    stream::channel,
+    widget::{button, column, container, row, text},
+    Alignment, Element, Length, Subscription, Theme,
+};
+use log::{debug, error};
+use std::{
+    any::TypeId,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+#[derive(Debug, Clone)]
+pub enum IdleInhibitorMessage {
+    Toggle,
+    /// Minutes to keep the system awake, `None` until disabled
+    InhibitFor(Option<u64>),
+    Tick,
+    Fullscreen(bool),
+    /// Sent by the media player module when any player starts or stops playing
+    MediaPlaying(bool),
+}
+
+impl IdleInhibitorManager {
+    pub fn indicator<'a>(&self) -> Option<Element<'a, Message>> {
+        if !self.is_inhibited() {
+            return None;
+        }
+
+        Some(
+            container(
+                row!(icon(Icons::EyeOpened))
+                    .push_maybe(
+                        self.remaining()
+                            .map(|remaining| text(format!("{}m", remaining.as_secs() / 60 + 1))),
+                    )
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+            )
+            .style(|theme: &Theme| container::Style {
+                text_color: Some(theme.palette().danger),
+                ..Default::default()
+            })
+            .into(),
+        )
+    }
+
+    pub fn get_quick_setting_button(
+        &self,
+        sub_menu: Option<SubMenu>,
+    ) -> (Element<Message>, Option<Element<Message>>) {
+        let subtitle = match (self.remaining(), self.is_manual(), self.auto_reason()) {
+            (Some(remaining), _, _) => Some(format!("{} left", format_clock(remaining))),
+            (None, true, _) => Some("Until disabled".to_string()),
+            (None, false, Some(AutoInhibitReason::Media)) => Some("Media playing".to_string()),
+            (None, false, Some(AutoInhibitReason::Fullscreen)) => {
+                Some("Fullscreen window".to_string())
+            }
+            (None, false, None) => None,
+        };
+
+        (
+            quick_setting_button(
+                if self.is_inhibited() {
+                    Icons::EyeOpened
+                } else {
+                    Icons::EyeClosed
+                },
+                "Idle Inhibitor".to_string(),
+                subtitle,
+                self.is_inhibited(),
+                Message::IdleInhibitor(IdleInhibitorMessage::Toggle),
+                Some((
+                    SubMenu::IdleInhibitor,
+                    sub_menu,
+                    Message::ToggleSubMenu(SubMenu::IdleInhibitor),
+                )),
+            ),
+            sub_menu
+                .filter(|menu_type| *menu_type == SubMenu::IdleInhibitor)
+                .map(|_| sub_menu_wrapper(timeout_menu())),
+        )
+    }
+}
+
+fn timeout_menu<'a>() -> Element<'a, Message> {
+    let timeout_button = |label: &'static str, minutes: Option<u64>| {
+        button(text(label))
+            .padding([4, 12])
+            .on_press(Message::IdleInhibitor(IdleInhibitorMessage::InhibitFor(
+                minutes,
+            )))
+            .width(Length::Fill)
+            .style(GhostButtonStyle.into_style())
+    };
+
+    column!(
+        text("Keep awake for").size(12),
+        timeout_button("30 minutes", Some(30)),
+        timeout_button("1 hour", Some(60)),
+        timeout_button("Until disabled", None),
+    )
+    .padding(8)
+    .width(Length::Fill)
+    .spacing(8)
+    .into()
+}
+
+/// A fullscreen window counts only when its workspace is shown on a monitor
+fn fullscreen_visible() -> bool {
+    let monitors = hyprland::data::Monitors::get()
+        .map(|m| m.to_vec())
+        .unwrap_or_default();
+
+    hyprland::data::Workspaces::get()
+        .map(|w| w.to_vec())
+        .unwrap_or_default()
+        .iter()
+        .filter(|w| w.fullscreen)
+        .any(|w| {
+            monitors
+                .iter()
+                .any(|m| m.active_workspace.id == w.id || m.special_workspace.id == w.id)
+        })
+}
+
+/// Event handler that checks again if a fullscreen window is visible, whatever the event
+fn send_fullscreen<T>(
+    output: &Arc<RwLock<Sender<IdleInhibitorMessage>>>,
+) -> impl Fn(T) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static {
+    let output = output.clone();
+
+    move |_| {
+        let output = output.clone();
+        Box::pin(async move {
+            if let Ok(mut output) = output.write() {
+                let _ = output.try_send(IdleInhibitorMessage::Fullscreen(fullscreen_visible()));
+            }
+        })
+    }
+}
+
+pub fn fullscreen_subscription() -> Subscription<IdleInhibitorMessage> {
+    let id = TypeId::of::<IdleInhibitorMessage>();
+
+    Subscription::run_with_id(
+        id,
+        channel(10, |mut output| async move {
+            let _ = output.try_send(IdleInhibitorMessage::Fullscreen(fullscreen_visible()));
+
+            let output = Arc::new(RwLock::new(output));
+            loop {
+                let mut event_listener = AsyncEventListener::new();
+
+                event_listener.add_fullscreen_state_changed_handler(send_fullscreen(&output));
+                event_listener.add_workspace_changed_handler(send_fullscreen(&output));
+                event_listener.add_changed_special_handler(send_fullscreen(&output));
+                event_listener.add_window_closed_handler(send_fullscreen(&output));
+
+                debug!("Starting fullscreen listener");
+
+                let res = event_listener.start_listener_async().await;
+
+                if let Err(e) = res {
+                    error!("restarting fullscreen listener due to error: {:?}", e);
+                }
+            }
+        }),
+    )
+}
@@ -1,55 +1,85 @@
 use self::{
-    audio::AudioMessage, bluetooth::BluetoothMessage, network::NetworkMessage, power::PowerMessage,
+    audio::AudioMessage,
+    bluetooth::BluetoothMessage,
+    network::{NetworkMessage, VpnImport},
+    power::PowerMessage,
 };
 use crate::{
     app::MenuType,
     components::icons::{icon, Icons},
     config::SettingsModuleConfig,
     modules::settings::power::power_menu,
+    network_dialog::{self, NetworkDialog},
     outputs::Outputs,
-    password_dialog,
+    pairing_dialog, password_dialog,
     services::{
         audio::{AudioCommand, AudioService},
-        bluetooth::{BluetoothCommand, BluetoothService, BluetoothState},
-        brightness::{BrightnessCommand, BrightnessService},
-        idle_inhibitor::IdleInhibitorManager,
-        network::{NetworkCommand, NetworkEvent, NetworkService},
-        upower::{PowerProfileCommand, UPowerService},
+        bluetooth::{
+            BluetoothCommand, BluetoothEvent, BluetoothService, BluetoothState, PairingRequest,
+        },
+        brightness::{BrightnessCommand, BrightnessDeviceKind, BrightnessService},
+        idle_inhibitor::{AutoInhibitReason, IdleInhibitorManager},
+        keyboard_backlight::{KeyboardBacklightCommand, KeyboardBacklightService},
+        network::{vpn_import, NetworkCommand, NetworkEvent, NetworkService, SecretsRequest},
+        night_light::NightLightManager,
+        upower::{UPowerCommand, UPowerService},
         ReadOnlyService, Service, ServiceEvent,
     },
     style::{
-        HeaderButtonStyle, QuickSettingsButtonStyle, QuickSettingsSubMenuButtonStyle,
-        SettingsButtonStyle,
+        GhostButtonStyle, HeaderButtonStyle, QuickSettingsButtonStyle,
+        QuickSettingsSubMenuButtonStyle, SettingsButtonStyle,
     },
 };
-use brightness::BrightnessMessage;
+use brightness::{BrightnessMessage, KeyboardBacklightMessage};
 use iced::{
     alignment::{Horizontal, Vertical},
+    time::every,
     widget::{
         button, column, container, horizontal_space, row, text, vertical_rule, Column, Row, Space,
     },
     window::Id,
     Alignment, Background, Border, Element, Length, Padding, Subscription, Task, Theme,
 };
+use idle_inhibitor::IdleInhibitorMessage;
 use log::info;
-use upower::UPowerMessage;
+use night_light::NightLightMessage;
+use std::time::Duration;
+use upower::{BatteryAlert, UPowerMessage};
+use zbus::zvariant::OwnedObjectPath;
 
 pub mod audio;
 pub mod bluetooth;
 pub mod brightness;
+pub mod idle_inhibitor;
 pub mod network;
+mod night_light;
 mod power;
 mod upower;
 
 pub struct Settings {
     audio: Option<AudioService>,
     brightness: Option<BrightnessService>,
+    keyboard_backlight: Option<KeyboardBacklightService>,
     network: Option<NetworkService>,
     bluetooth: Option<BluetoothService>,
     idle_inhibitor: Option<IdleInhibitorManager>,
+    media_playing: bool,
+    fullscreen: bool,
+    night_light: Option<NightLightManager>,
+    night_light_temperature: Option<u32>,
+    night_light_scheduled: Option<bool>,
+    /// `None` while ashell isn't the notification daemon
+    do_not_disturb: Option<bool>,
     sub_menu: Option<SubMenu>,
     upower: Option<UPowerService>,
     pub password_dialog: Option<(String, String)>,
+    secrets_request: Option<SecretsRequest>,
+    pub network_dialog: Option<NetworkDialog>,
+    wireless_adapter: Option<OwnedObjectPath>,
+    vpn_import: Option<VpnImport>,
+    pairing_request: Option<(PairingRequest, String)>,
+    bluetooth_device: Option<OwnedObjectPath>,
+    battery_alert: BatteryAlert,
 }
 
 impl Default for Settings {
@@ -57,12 +87,26 @@ impl Default for Settings {
         Settings {
             audio: None,
             brightness: None,
+            keyboard_backlight: None,
             network: None,
             bluetooth: None,
             idle_inhibitor: IdleInhibitorManager::new(),
+            media_playing: false,
+            fullscreen: false,
+            night_light: NightLightManager::new(),
+            night_light_temperature: None,
+            night_light_scheduled: None,
+            do_not_disturb: None,
             sub_menu: None,
             upower: None,
             password_dialog: None,
+            secrets_request: None,
+            network_dialog: None,
+            wireless_adapter: None,
+            vpn_import: None,
+            pairing_request: None,
+            bluetooth_device: None,
+            battery_alert: BatteryAlert::default(),
         }
     }
 }
@@ -70,16 +114,22 @@ impl Default for Settings {
 #[derive(Debug, Clone)]
 pub enum Message {
     ToggleMenu(Id),
+    OpenSubMenu(Id, SubMenu),
     UPower(UPowerMessage),
     Network(NetworkMessage),
     Bluetooth(BluetoothMessage),
     Audio(AudioMessage),
     Brightness(BrightnessMessage),
-    ToggleInhibitIdle,
+    KeyboardBacklight(KeyboardBacklightMessage),
+    IdleInhibitor(IdleInhibitorMessage),
+    NightLight(NightLightMessage),
+    ToggleDoNotDisturb,
     Lock,
     Power(PowerMessage),
     ToggleSubMenu(SubMenu),
     PasswordDialog(password_dialog::Message),
+    NetworkDialog(network_dialog::Message),
+    PairingDialog(pairing_dialog::Message),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -88,11 +138,32 @@ pub enum SubMenu {
     Sinks,
     Sources,
     Wifi,
+    Ethernet,
     Vpn,
     Bluetooth,
+    Battery,
+    NightLight,
+    IdleInhibitor,
 }
 
 impl Settings {
+    pub fn audio(&self) -> Option<&AudioService> {
+        self.audio.as_ref()
+    }
+
+    pub fn bluetooth(&self) -> Option<&BluetoothService> {
+        self.bluetooth.as_ref()
+    }
+
+    pub fn do_not_disturb(&self) -> bool {
+        self.do_not_disturb == Some(true)
+    }
+
+    /// The do not disturb toggle is shown only while the notification daemon runs
+    pub fn set_notifications_daemon(&mut self, running: bool) {
+        self.do_not_disturb = running.then_some(self.do_not_disturb())
+    }
+
     pub fn update(
         &mut self,
         message: Message,
@@ -103,7 +174,26 @@ impl Settings {
             Message::ToggleMenu(id) => {
                 self.sub_menu = None;
                 self.password_dialog = None;
-                outputs.toggle_menu(id, MenuType::Settings)
+                self.network_dialog = None;
+                self.vpn_import = None;
+                Task::batch(vec![
+                    self.provide_secrets(None),
+                    self.reject_pairing(),
+                    self.stop_bluetooth_discovery(),
+                    outputs.toggle_menu(id, MenuType::Settings),
+                ])
+            }
+            Message::OpenSubMenu(id, menu_type) => {
+                let open = outputs.open_menu(id, MenuType::Settings);
+
+                if self.sub_menu == Some(menu_type) {
+                    open
+                } else {
+                    Task::batch(vec![
+                        self.update(Message::ToggleSubMenu(menu_type), config, outputs),
+                        open,
+                    ])
+                }
             }
             Message::Audio(msg) => match msg {
                 AudioMessage::Event(event) => match event {
@@ -175,12 +265,14 @@ impl Settings {
             Message::UPower(msg) => match msg {
                 UPowerMessage::Event(event) => match event {
                     ServiceEvent::Init(service) => {
+                        self.battery_alert.check(service.battery, &config.battery);
                         self.upower = Some(service);
                         Task::none()
                     }
                     ServiceEvent::Update(data) => {
                         if let Some(upower) = self.upower.as_mut() {
                             upower.update(data);
+                            self.battery_alert.check(upower.battery, &config.battery);
                         }
                         Task::none()
                     }
@@ -188,15 +280,42 @@ impl Settings {
                 },
                 UPowerMessage::TogglePowerProfile => {
                     if let Some(upower) = self.upower.as_mut() {
-                        upower.command(PowerProfileCommand::Toggle).map(|event| {
-                            crate::app::Message::Settings(Message::UPower(UPowerMessage::Event(
-                                event,
-                            )))
-                        })
+                        upower
+                            .command(UPowerCommand::TogglePowerProfile)
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::UPower(
+                                    UPowerMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
+                UPowerMessage::ToggleChargeLimit(device, enabled) => {
+                    if let Some(upower) = self.upower.as_mut() {
+                        upower
+                            .command(UPowerCommand::SetChargeLimit(
+                                device,
+                                enabled,
+                                config.battery.charge_limit_cmd.clone(),
+                            ))
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::UPower(
+                                    UPowerMessage::Event(event),
+                                ))
+                            })
                     } else {
                         Task::none()
                     }
                 }
+                UPowerMessage::CriticalActionTick => {
+                    self.battery_alert.tick(&config.battery);
+                    Task::none()
+                }
+                UPowerMessage::CancelCriticalAction => {
+                    self.battery_alert.cancel();
+                    Task::none()
+                }
             },
             Message::Network(msg) => match msg {
                 NetworkMessage::Event(event) => match event {
@@ -204,8 +323,46 @@ impl Settings {
                         self.network = Some(service);
                         Task::none()
                     }
-                    ServiceEvent::Update(NetworkEvent::RequestPasswordForSSID(ssid)) => {
-                        self.password_dialog = Some((ssid, "".to_string()));
+                    ServiceEvent::Update(NetworkEvent::SecretsRequested(request)) => {
+                        info!("Requesting secrets for {}", request.name);
+                        let cancel_previous = self.provide_secrets(None);
+
+                        self.sub_menu = None;
+                        self.network_dialog = None;
+                        self.password_dialog = Some((request.name.clone(), "".to_string()));
+                        self.secrets_request = Some(request);
+
+                        Task::batch(vec![
+                            cancel_previous,
+                            outputs.request_menu(MenuType::Settings),
+                        ])
+                    }
+                    ServiceEvent::Update(NetworkEvent::SecretsRequestClosed(
+                        connection_path,
+                        setting_name,
+                    )) => {
+                        if self.secrets_request.as_ref().is_some_and(|request| {
+                            request.connection_path == connection_path
+                                && request.setting_name == setting_name
+                        }) {
+                            self.secrets_request = None;
+                            self.password_dialog = None;
+                        }
+                        Task::none()
+                    }
+                    ServiceEvent::Update(NetworkEvent::VpnImported(res)) => {
+                        match &res {
+                            Ok(_) => self.vpn_import = None,
+                            Err(err) => {
+                                if let Some(vpn_import) = self.vpn_import.as_mut() {
+                                    vpn_import.importing = false;
+                                    vpn_import.error = Some(err.clone());
+                                }
+                            }
+                        }
+                        if let Some(network) = self.network.as_mut() {
+                            network.update(NetworkEvent::VpnImported(res));
+                        }
                         Task::none()
                     }
                     ServiceEvent::Update(data) => {
@@ -216,6 +373,42 @@ impl Settings {
                     }
                     _ => Task::none(),
                 },
+                NetworkMessage::ImportVpn(id) => {
+                    self.vpn_import = Some(VpnImport::default());
+                    outputs.request_keyboard(id)
+                }
+                NetworkMessage::VpnImportPathChanged(path) => {
+                    if let Some(vpn_import) = self.vpn_import.as_mut() {
+                        vpn_import.path = path;
+                        vpn_import.error = None;
+                    }
+                    Task::none()
+                }
+                NetworkMessage::ConfirmVpnImport(id) => {
+                    match (self.vpn_import.as_mut(), self.network.as_mut()) {
+                        (Some(vpn_import), Some(network)) => {
+                            vpn_import.importing = true;
+                            vpn_import.error = None;
+                            let path = vpn_import::expand_home(&vpn_import.path);
+
+                            Task::batch(vec![
+                                network
+                                    .command(NetworkCommand::ImportVpn(path))
+                                    .map(|event| {
+                                        crate::app::Message::Settings(Message::Network(
+                                            NetworkMessage::Event(event),
+                                        ))
+                                    }),
+                                outputs.release_keyboard(id),
+                            ])
+                        }
+                        _ => outputs.release_keyboard(id),
+                    }
+                }
+                NetworkMessage::CancelVpnImport(id) => {
+                    self.vpn_import = None;
+                    outputs.release_keyboard(id)
+                }
                 NetworkMessage::ToggleAirplaneMode => {
                     if let Some(network) = self.network.as_mut() {
                         network
@@ -229,6 +422,49 @@ impl Settings {
                         Task::none()
                     }
                 }
+                NetworkMessage::ToggleMobileData => {
+                    if let Some(network) = self.network.as_mut() {
+                        network
+                            .command(NetworkCommand::ToggleMobileData)
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::Network(
+                                    NetworkMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
+                NetworkMessage::SelectWirelessAdapter(path) => {
+                    self.wireless_adapter = Some(path);
+                    Task::none()
+                }
+                NetworkMessage::ActivateWiredProfile(device, profile) => {
+                    if let Some(network) = self.network.as_mut() {
+                        network
+                            .command(NetworkCommand::ActivateWiredProfile(device, profile))
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::Network(
+                                    NetworkMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
+                NetworkMessage::DisconnectDevice(device) => {
+                    if let Some(network) = self.network.as_mut() {
+                        network
+                            .command(NetworkCommand::DisconnectDevice(device))
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::Network(
+                                    NetworkMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
                 NetworkMessage::ToggleWiFi => {
                     if let Some(network) = self.network.as_mut() {
                         network.command(NetworkCommand::ToggleWiFi).map(|event| {
@@ -258,6 +494,16 @@ impl Settings {
                     self.password_dialog = Some((ssid, "".to_string()));
                     outputs.request_keyboard(id)
                 }
+                NetworkMessage::ConnectToOtherNetwork(id, ssid) => {
+                    self.network_dialog = Some(match ssid {
+                        Some(ssid) => {
+                            info!("Requesting credentials for {}", ssid);
+                            NetworkDialog::enterprise(ssid)
+                        }
+                        None => NetworkDialog::hidden(),
+                    });
+                    outputs.request_keyboard(id)
+                }
                 NetworkMessage::ScanNearByWiFi => {
                     if let Some(network) = self.network.as_mut() {
                         network
@@ -279,6 +525,19 @@ impl Settings {
                         Task::none()
                     }
                 }
+                NetworkMessage::SignInToNetwork(id) => {
+                    if let Some(network) = self.network.as_ref() {
+                        let browser = config.browser_cmd.as_deref().unwrap_or("xdg-open");
+                        info!("Opening captive portal page {}", network.portal_url);
+                        crate::utils::launcher::execute_command(format!(
+                            "{} '{}'",
+                            browser, network.portal_url
+                        ));
+                        outputs.close_menu(id)
+                    } else {
+                        Task::none()
+                    }
+                }
                 NetworkMessage::VpnMore(id) => {
                     if let Some(cmd) = &config.vpn_more_cmd {
                         crate::utils::launcher::execute_command(cmd.to_string());
@@ -287,6 +546,39 @@ impl Settings {
                         Task::none()
                     }
                 }
+                NetworkMessage::ShowConnectionDetails(access_point) => {
+                    if let Some(network) = self.network.as_mut() {
+                        network
+                            .command(NetworkCommand::GetConnectionDetails(access_point))
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::Network(
+                                    NetworkMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
+                NetworkMessage::CloseConnectionDetails => {
+                    if let Some(network) = self.network.as_mut() {
+                        network.update(NetworkEvent::ConnectionDetails(None));
+                    }
+                    Task::none()
+                }
+                NetworkMessage::ForgetConnection(ssid) => {
+                    if let Some(network) = self.network.as_mut() {
+                        network.update(NetworkEvent::ConnectionDetails(None));
+                        network
+                            .command(NetworkCommand::ForgetConnection(ssid))
+                            .map(|event| {
+                                crate::app::Message::Settings(Message::Network(
+                                    NetworkMessage::Event(event),
+                                ))
+                            })
+                    } else {
+                        Task::none()
+                    }
+                }
                 NetworkMessage::ToggleVpn(vpn) => {
                     if let Some(network) = self.network.as_mut() {
                         network
@@ -307,24 +599,73 @@ impl Settings {
                         self.bluetooth = Some(service);
                         Task::none()
                     }
+                    ServiceEvent::Update(BluetoothEvent::PairingRequested(request)) => {
+                        info!("Pairing requested for {}", request.device_name);
+                        let reject_previous = self.reject_pairing();
+
+                        self.pairing_request = Some((request, "".to_string()));
+
+                        Task::batch(vec![
+                            reject_previous,
+                            outputs.request_menu(MenuType::Settings),
+                        ])
+                    }
+                    ServiceEvent::Update(BluetoothEvent::PairingCanceled) => {
+                        self.pairing_request = None;
+                        Task::none()
+                    }
                     ServiceEvent::Update(data) => {
                         if let Some(bluetooth) = self.bluetooth.as_mut() {
                             bluetooth.update(data);
+
+                            // Display only requests end when the device gets paired
+                            if self.pairing_request.as_ref().is_some_and(|(request, _)| {
+                                !request.needs_reply()
+                                    && bluetooth
+                                        .devices
+                                        .iter()
+                                        .any(|d| d.path == request.device && d.paired)
+                            }) {
+                                self.pairing_request = None;
+                            }
                         }
                         Task::none()
                     }
                     _ => Task::none(),
                 },
-                BluetoothMessage::Toggle => {
-                    if let Some(bluetooth) = self.bluetooth.as_mut() {
-                        bluetooth.command(BluetoothCommand::Toggle).map(|event| {
-                            crate::app::Message::Settings(Message::Bluetooth(
-                                BluetoothMessage::Event(event),
-                            ))
-                        })
+                BluetoothMessage::Toggle => self.bluetooth_command(BluetoothCommand::Toggle),
+                BluetoothMessage::ToggleDiscovery => {
+                    let discovering = self.bluetooth.as_ref().is_some_and(|b| b.discovering);
+
+                    self.bluetooth_command(if discovering {
+                        BluetoothCommand::StopDiscovery
                     } else {
-                        Task::none()
+                        BluetoothCommand::StartDiscovery
+                    })
+                }
+                BluetoothMessage::SelectDevice(device) => {
+                    if self.bluetooth_device.as_ref() == Some(&device) {
+                        self.bluetooth_device = None;
+                    } else {
+                        self.bluetooth_device = Some(device);
                     }
+                    Task::none()
+                }
+                BluetoothMessage::Pair(device) => {
+                    self.bluetooth_command(BluetoothCommand::Pair(device))
+                }
+                BluetoothMessage::Connect(device) => {
+                    self.bluetooth_command(BluetoothCommand::Connect(device))
+                }
+                BluetoothMessage::Disconnect(device) => {
+                    self.bluetooth_command(BluetoothCommand::Disconnect(device))
+                }
+                BluetoothMessage::SetTrusted(device, trusted) => {
+                    self.bluetooth_command(BluetoothCommand::SetTrusted(device, trusted))
+                }
+                BluetoothMessage::Remove(device) => {
+                    self.bluetooth_device = None;
+                    self.bluetooth_command(BluetoothCommand::Remove(device))
                 }
                 BluetoothMessage::More(id) => {
                     if let Some(cmd) = &config.bluetooth_more_cmd {
@@ -349,10 +690,18 @@ impl Settings {
                     }
                     _ => Task::none(),
                 },
-                BrightnessMessage::Change(value) => {
+                BrightnessMessage::Change(name, value) => {
                     if let Some(brightness) = self.brightness.as_mut() {
+                        let is_ddc = brightness.devices.iter().any(|d| {
+                            d.name == name && matches!(d.kind, BrightnessDeviceKind::Ddc(_))
+                        });
+
                         brightness
-                            .command(BrightnessCommand::Set(value))
+                            .command(if is_ddc {
+                                BrightnessCommand::Preview(name, value)
+                            } else {
+                                BrightnessCommand::Set(name, value)
+                            })
                             .map(|event| {
                                 crate::app::Message::Settings(Message::Brightness(
                                     BrightnessMessage::Event(event),
@@ -362,8 +711,62 @@ impl Settings {
                         Task::none()
                     }
                 }
+                BrightnessMessage::Release(name) => {
+                    if let Some(brightness) = self.brightness.as_mut() {
+                        let value = brightness
+                            .devices
+                            .iter()
+                            .find(|d| d.name == name)
+                            .map(|d| d.current);
+
+                        match value {
+                            Some(value) => brightness
+                                .command(BrightnessCommand::Set(name, value))
+                                .map(|event| {
+                                    crate::app::Message::Settings(Message::Brightness(
+                                        BrightnessMessage::Event(event),
+                                    ))
+                                }),
+                            None => Task::none(),
+                        }
+                    } else {
+                        Task::none()
+                    }
+                }
+            },
+            Message::KeyboardBacklight(msg) => match msg {
+                KeyboardBacklightMessage::Event(event) => match event {
+                    ServiceEvent::Init(service) => {
+                        self.keyboard_backlight = Some(service);
+                        Task::none()
+                    }
+                    ServiceEvent::Update(data) => {
+                        if let Some(keyboard_backlight) = self.keyboard_backlight.as_mut() {
+                            keyboard_backlight.update(data);
+                        }
+                        Task::none()
+                    }
+                    _ => Task::none(),
+                },
+                KeyboardBacklightMessage::Change(value) => {
+                    self.keyboard_backlight_command(|_| KeyboardBacklightCommand::Set(value))
+                }
+                KeyboardBacklightMessage::Toggle => {
+                    self.keyboard_backlight_command(|keyboard_backlight| {
+                        KeyboardBacklightCommand::Set(if keyboard_backlight.current > 0 {
+                            0
+                        } else {
+                            keyboard_backlight.max
+                        })
+                    })
+                }
             },
             Message::ToggleSubMenu(menu_type) => {
+                if let Some(network) = self.network.as_mut() {
+                    network.update(NetworkEvent::ConnectionDetails(None));
+                }
+                let stop_discovery = self.stop_bluetooth_discovery();
+
                 if self.sub_menu == Some(menu_type) {
                     self.sub_menu.take();
                 } else {
@@ -371,22 +774,101 @@ impl Settings {
 
                     if menu_type == SubMenu::Wifi {
                         if let Some(network) = self.network.as_mut() {
-                            return network
-                                .command(NetworkCommand::ScanNearByWiFi)
-                                .map(|event| {
-                                    crate::app::Message::Settings(Message::Network(
-                                        NetworkMessage::Event(event),
-                                    ))
-                                });
+                            return Task::batch(vec![
+                                stop_discovery,
+                                network
+                                    .command(NetworkCommand::ScanNearByWiFi)
+                                    .map(|event| {
+                                        crate::app::Message::Settings(Message::Network(
+                                            NetworkMessage::Event(event),
+                                        ))
+                                    }),
+                            ]);
                         }
                     }
+
+                    if menu_type == SubMenu::Bluetooth {
+                        return self.bluetooth_command(BluetoothCommand::StartDiscovery);
+                    }
                 }
 
+                stop_discovery
+            }
+            Message::IdleInhibitor(msg) => {
+                match msg {
+                    IdleInhibitorMessage::Toggle => {
+                        if let Some(idle_inhibitor) = &mut self.idle_inhibitor {
+                            idle_inhibitor.toggle();
+                        }
+                    }
+                    IdleInhibitorMessage::InhibitFor(minutes) => {
+                        if let Some(idle_inhibitor) = &mut self.idle_inhibitor {
+                            idle_inhibitor.inhibit_for(
+                                minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+                            );
+                        }
+                        self.sub_menu = None;
+                    }
+                    IdleInhibitorMessage::Tick => {
+                        if let Some(idle_inhibitor) = &mut self.idle_inhibitor {
+                            idle_inhibitor.tick();
+                        }
+                    }
+                    IdleInhibitorMessage::Fullscreen(fullscreen) => {
+                        self.fullscreen = fullscreen;
+                        self.update_auto_inhibit(config);
+                    }
+                    IdleInhibitorMessage::MediaPlaying(playing) => {
+                        self.media_playing = playing;
+                        self.update_auto_inhibit(config);
+                    }
+                }
                 Task::none()
             }
-            Message::ToggleInhibitIdle => {
-                if let Some(idle_inhibitor) = &mut self.idle_inhibitor {
-                    idle_inhibitor.toggle();
+            Message::NightLight(msg) => {
+                let temperature = self
+                    .night_light_temperature
+                    .unwrap_or(config.night_light.temperature);
+
+                if let Some(night_light) = self.night_light.as_mut() {
+                    match msg {
+                        NightLightMessage::Toggle => {
+                            if night_light.is_enabled() {
+                                night_light.disable();
+                            } else {
+                                night_light.enable(temperature);
+                            }
+                        }
+                        NightLightMessage::Temperature(value) => {
+                            self.night_light_temperature = Some(value);
+                            if night_light.is_enabled() {
+                                night_light.enable(value);
+                            }
+                        }
+                        NightLightMessage::ScheduleTick => {
+                            night_light.refresh();
+
+                            // the schedule switches the night light only at sunset and
+                            // sunrise, a manual toggle holds until the next one
+                            if let Some(sun_times) = night_light::schedule(&config.night_light) {
+                                let night = sun_times.is_night(chrono::Local::now());
+                                if self.night_light_scheduled != Some(night) {
+                                    self.night_light_scheduled = Some(night);
+                                    if night {
+                                        night_light.enable(temperature);
+                                    } else {
+                                        night_light.disable();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::ToggleDoNotDisturb => {
+                if let Some(do_not_disturb) = self.do_not_disturb.as_mut() {
+                    *do_not_disturb = !*do_not_disturb;
                 }
                 Task::none()
             }
@@ -410,7 +892,9 @@ impl Settings {
                 }
                 password_dialog::Message::DialogConfirmed(id) => {
                     if let Some((ssid, password)) = self.password_dialog.take() {
-                        let network_command = if let Some(network) = self.network.as_mut() {
+                        let network_command = if self.secrets_request.is_some() {
+                            self.provide_secrets(Some(password))
+                        } else if let Some(network) = self.network.as_mut() {
                             let ap = network
                                 .wireless_access_points
                                 .iter()
@@ -441,28 +925,157 @@ impl Settings {
                 password_dialog::Message::DialogCancelled(id) => {
                     self.password_dialog = None;
 
+                    Task::batch(vec![
+                        self.provide_secrets(None),
+                        outputs.release_keyboard(id),
+                    ])
+                }
+            },
+            Message::PairingDialog(msg) => match msg {
+                pairing_dialog::Message::InputChanged(input) => {
+                    if let Some((_, current_input)) = &mut self.pairing_request {
+                        *current_input = input;
+                    }
+                    Task::none()
+                }
+                pairing_dialog::Message::DialogConfirmed(id) => {
+                    let reply = match self.pairing_request.take() {
+                        Some((request, input)) if request.needs_reply() => self.bluetooth_command(
+                            BluetoothCommand::ReplyPairing(request.device, Some(input)),
+                        ),
+                        _ => Task::none(),
+                    };
+
+                    Task::batch(vec![reply, outputs.release_keyboard(id)])
+                }
+                pairing_dialog::Message::DialogCancelled(id) => {
+                    let cancel = match self.pairing_request.take() {
+                        Some((request, _)) if request.needs_reply() => self.bluetooth_command(
+                            BluetoothCommand::ReplyPairing(request.device, None),
+                        ),
+                        Some((request, _)) => {
+                            self.bluetooth_command(BluetoothCommand::CancelPairing(request.device))
+                        }
+                        None => Task::none(),
+                    };
+
+                    Task::batch(vec![cancel, outputs.release_keyboard(id)])
+                }
+            },
+            Message::NetworkDialog(msg) => match msg {
+                network_dialog::Message::DialogConfirmed(id) => {
+                    if let Some(dialog) = self.network_dialog.take() {
+                        let network_command = if let Some(network) = self.network.as_mut() {
+                            let device_path = network
+                                .wireless_adapter(self.wireless_adapter.as_ref())
+                                .map(|a| a.path.clone());
+
+                            network
+                                .command(NetworkCommand::ConnectToNetwork(
+                                    dialog.connection(device_path),
+                                ))
+                                .map(|event| {
+                                    crate::app::Message::Settings(Message::Network(
+                                        NetworkMessage::Event(event),
+                                    ))
+                                })
+                        } else {
+                            Task::none()
+                        };
+                        Task::batch(vec![network_command, outputs.release_keyboard(id)])
+                    } else {
+                        outputs.release_keyboard(id)
+                    }
+                }
+                network_dialog::Message::DialogCancelled(id) => {
+                    self.network_dialog = None;
+
                     outputs.release_keyboard(id)
                 }
+                msg => {
+                    if let Some(dialog) = self.network_dialog.as_mut() {
+                        dialog.update(msg);
+                    }
+
+                    Task::none()
+                }
             },
         }
     }
 
+    fn provide_secrets(&mut self, secret: Option<String>) -> Task<crate::app::Message> {
+        match (self.secrets_request.take(), self.network.as_mut()) {
+            (Some(request), Some(network)) => network
+                .command(NetworkCommand::ProvideSecrets(request, secret))
+                .map(|event| {
+                    crate::app::Message::Settings(Message::Network(NetworkMessage::Event(event)))
+                }),
+            _ => Task::none(),
+        }
+    }
+
+    fn bluetooth_command(&mut self, command: BluetoothCommand) -> Task<crate::app::Message> {
+        if let Some(bluetooth) = self.bluetooth.as_mut() {
+            bluetooth.command(command).map(|event| {
+                crate::app::Message::Settings(Message::Bluetooth(BluetoothMessage::Event(event)))
+            })
+        } else {
+            Task::none()
+        }
+    }
+
+    fn keyboard_backlight_command(
+        &mut self,
+        command: impl FnOnce(&KeyboardBacklightService) -> KeyboardBacklightCommand,
+    ) -> Task<crate::app::Message> {
+        if let Some(keyboard_backlight) = self.keyboard_backlight.as_mut() {
+            let command = command(keyboard_backlight);
+
+            keyboard_backlight.command(command).map(|event| {
+                crate::app::Message::Settings(Message::KeyboardBacklight(
+                    KeyboardBacklightMessage::Event(event),
+                ))
+            })
+        } else {
+            Task::none()
+        }
+    }
+
+    fn update_auto_inhibit(&mut self, config: &SettingsModuleConfig) {
+        let reason = if config.idle_inhibitor.auto_on_media && self.media_playing {
+            Some(AutoInhibitReason::Media)
+        } else if config.idle_inhibitor.auto_on_fullscreen && self.fullscreen {
+            Some(AutoInhibitReason::Fullscreen)
+        } else {
+            None
+        };
+
+        if let Some(idle_inhibitor) = &mut self.idle_inhibitor {
+            idle_inhibitor.set_auto_reason(reason);
+        }
+    }
+
+    fn reject_pairing(&mut self) -> Task<crate::app::Message> {
+        match self.pairing_request.take() {
+            Some((request, _)) if request.needs_reply() => {
+                self.bluetooth_command(BluetoothCommand::ReplyPairing(request.device, None))
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn stop_bluetooth_discovery(&mut self) -> Task<crate::app::Message> {
+        if self.bluetooth.as_ref().is_some_and(|b| b.discovering) {
+            self.bluetooth_command(BluetoothCommand::StopDiscovery)
+        } else {
+            Task::none()
+        }
+    }
+
     pub fn view(&self, id: Id) -> Element<Message> {
-        button(
+        let settings = button(
             Row::new()
-                .push_maybe(
-                    self.idle_inhibitor
-                        .as_ref()
-                        .filter(|i| i.is_inhibited())
-                        .map(|_| {
-                            container(icon(Icons::EyeOpened)).style(|theme: &Theme| {
-                                container::Style {
-                                    text_color: Some(theme.palette().danger),
-                                    ..Default::default()
-                                }
-                            })
-                        }),
-                )
+                .push_maybe(self.idle_inhibitor.as_ref().and_then(|i| i.indicator()))
                 .push_maybe(
                     self.upower
                         .as_ref()
@@ -476,6 +1089,7 @@ impl Settings {
                                 .as_ref()
                                 .and_then(|n| n.get_connection_indicator()),
                         )
+                        .push_maybe(self.network.as_ref().and_then(|n| n.get_wwan_indicator()))
                         .push_maybe(self.network.as_ref().and_then(|n| n.get_vpn_indicator()))
                         .spacing(4),
                 )
@@ -489,19 +1103,33 @@ impl Settings {
         )
         .style(HeaderButtonStyle::Right.into_style())
         .padding([2, 8])
-        .on_press(Message::ToggleMenu(id))
-        .into()
+        .on_press(Message::ToggleMenu(id));
+
+        match self.battery_alert.countdown_indicator() {
+            Some(countdown) => Row::new().push(countdown).push(settings).spacing(4).into(),
+            None => settings.into(),
+        }
     }
 
     pub fn menu_view(&self, id: Id, config: &SettingsModuleConfig) -> Element<Message> {
         if let Some((ssid, current_password)) = &self.password_dialog {
             password_dialog::view(id, ssid, current_password).map(Message::PasswordDialog)
+        } else if let Some(dialog) = &self.network_dialog {
+            dialog.view(id).map(Message::NetworkDialog)
+        } else if let Some((request, input)) = &self.pairing_request {
+            pairing_dialog::view(id, request, input).map(Message::PairingDialog)
         } else {
-            let battery_data = self
-                .upower
-                .as_ref()
-                .and_then(|upower| upower.battery)
-                .map(|battery| battery.settings_indicator());
+            let battery_data = self.upower.as_ref().and_then(|upower| {
+                upower.battery.map(|battery| {
+                    button(battery.settings_indicator())
+                        .padding(0)
+                        .style(GhostButtonStyle.into_style())
+                        .on_press_maybe(
+                            (!upower.devices.is_empty())
+                                .then_some(Message::ToggleSubMenu(SubMenu::Battery)),
+                        )
+                })
+            });
             let right_buttons = Row::new()
                 .push_maybe(config.lock_cmd.as_ref().map(|_| {
                     button(icon(Icons::Lock))
@@ -535,11 +1163,19 @@ impl Settings {
                 .unwrap_or((None, None));
 
             let wifi_setting_button = self.network.as_ref().and_then(|n| {
-                n.get_wifi_quick_setting_button(id, self.sub_menu, config.wifi_more_cmd.is_some())
+                n.get_wifi_quick_setting_button(
+                    id,
+                    self.sub_menu,
+                    self.wireless_adapter.as_ref(),
+                    config.wifi_more_cmd.is_some(),
+                )
             });
             let quick_settings = quick_settings_section(
                 vec![
                     wifi_setting_button,
+                    self.network
+                        .as_ref()
+                        .and_then(|n| n.get_ethernet_quick_setting_button(self.sub_menu)),
                     self.bluetooth
                         .as_ref()
                         .filter(|b| b.state != BluetoothState::Unavailable)
@@ -547,6 +1183,7 @@ impl Settings {
                             b.get_quick_setting_button(
                                 id,
                                 self.sub_menu,
+                                self.bluetooth_device.as_ref(),
                                 config.bluetooth_more_cmd.is_some(),
                             )
                         }),
@@ -554,24 +1191,35 @@ impl Settings {
                         n.get_vpn_quick_setting_button(
                             id,
                             self.sub_menu,
+                            self.vpn_import.as_ref(),
                             config.vpn_more_cmd.is_some(),
                         )
                     }),
+                    self.network
+                        .as_ref()
+                        .and_then(|n| n.get_mobile_data_quick_setting_button()),
                     self.network
                         .as_ref()
                         .map(|n| n.get_airplane_mode_quick_setting_button()),
-                    self.idle_inhibitor.as_ref().map(|idle_inhibitor| {
+                    self.idle_inhibitor
+                        .as_ref()
+                        .map(|i| i.get_quick_setting_button(self.sub_menu)),
+                    self.night_light.as_ref().map(|night_light| {
+                        night_light.get_quick_setting_button(
+                            self.sub_menu,
+                            self.night_light_temperature
+                                .unwrap_or(config.night_light.temperature),
+                            &config.night_light,
+                        )
+                    }),
+                    self.do_not_disturb.map(|active| {
                         (
                             quick_setting_button(
-                                if idle_inhibitor.is_inhibited() {
-                                    Icons::EyeOpened
-                                } else {
-                                    Icons::EyeClosed
-                                },
-                                "Idle Inhibitor".to_string(),
+                                if active { Icons::BellOff } else { Icons::Bell },
+                                "Do Not Disturb".to_string(),
                                 None,
-                                idle_inhibitor.is_inhibited(),
-                                Message::ToggleInhibitIdle,
+                                active,
+                                Message::ToggleDoNotDisturb,
                                 None,
                             ),
                             None,
@@ -588,11 +1236,26 @@ impl Settings {
 
             Column::new()
                 .push(header)
+                .push_maybe(self.network.as_ref().and_then(|n| {
+                    n.get_portal_login_button(id)
+                        .map(|button| button.map(Message::Network))
+                }))
                 .push_maybe(
                     self.sub_menu
                         .filter(|menu_type| *menu_type == SubMenu::Power)
                         .map(|_| sub_menu_wrapper(power_menu().map(Message::Power))),
                 )
+                .push_maybe(
+                    self.sub_menu
+                        .filter(|menu_type| *menu_type == SubMenu::Battery)
+                        .and_then(|_| {
+                            self.upower.as_ref().map(|upower| {
+                                sub_menu_wrapper(
+                                    upower.devices_menu(config.battery.charge_limit_cmd.is_some()),
+                                )
+                            })
+                        }),
+                )
                 .push_maybe(sink_slider)
                 .push_maybe(
                     self.sub_menu
@@ -617,7 +1280,16 @@ impl Settings {
                             })
                         }),
                 )
-                .push_maybe(self.brightness.as_ref().map(|b| b.brightness_slider()))
+                .push_maybe(
+                    self.brightness
+                        .as_ref()
+                        .and_then(|b| b.brightness_sliders(&config.brightness_devices)),
+                )
+                .push_maybe(
+                    self.keyboard_backlight
+                        .as_ref()
+                        .map(|k| k.keyboard_backlight_slider()),
+                )
                 .push(quick_settings)
                 .spacing(16)
                 .padding(16)
@@ -626,15 +1298,52 @@ impl Settings {
         }
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
+    pub fn subscription(&self, config: &SettingsModuleConfig) -> Subscription<Message> {
+        let critical_action_tick = if self.battery_alert.countdown_active() {
+            every(Duration::from_secs(1))
+                .map(|_| Message::UPower(UPowerMessage::CriticalActionTick))
+        } else {
+            Subscription::none()
+        };
+
+        let night_light_schedule = if self.night_light.is_some() {
+            every(Duration::from_secs(60))
+                .map(|_| Message::NightLight(NightLightMessage::ScheduleTick))
+        } else {
+            Subscription::none()
+        };
+
+        let idle_inhibitor_tick = if self
+            .idle_inhibitor
+            .as_ref()
+            .is_some_and(|i| i.remaining().is_some())
+        {
+            every(Duration::from_secs(1))
+                .map(|_| Message::IdleInhibitor(IdleInhibitorMessage::Tick))
+        } else {
+            Subscription::none()
+        };
+
+        let fullscreen = if config.idle_inhibitor.auto_on_fullscreen {
+            idle_inhibitor::fullscreen_subscription().map(Message::IdleInhibitor)
+        } else {
+            Subscription::none()
+        };
+
         Subscription::batch(vec![
             UPowerService::subscribe().map(|event| Message::UPower(UPowerMessage::Event(event))),
             AudioService::subscribe().map(|evenet| Message::Audio(AudioMessage::Event(evenet))),
-            BrightnessService::subscribe()
+            BrightnessService::subscribe_with_devices(config.brightness_devices.clone())
                 .map(|event| Message::Brightness(BrightnessMessage::Event(event))),
+            KeyboardBacklightService::subscribe()
+                .map(|event| Message::KeyboardBacklight(KeyboardBacklightMessage::Event(event))),
             NetworkService::subscribe().map(|event| Message::Network(NetworkMessage::Event(event))),
             BluetoothService::subscribe()
                 .map(|event| Message::Bluetooth(BluetoothMessage::Event(event))),
+            critical_action_tick,
+            night_light_schedule,
+            idle_inhibitor_tick,
+            fullscreen,
         ])
     }
 }
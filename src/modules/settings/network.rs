@@ -3,31 +3,58 @@ use crate::{
     components::icons::{icon, Icons},
     services::{
         network::{
-            dbus::ConnectivityState, AccessPoint, ActiveConnectionInfo, KnownConnection,
-            NetworkData, NetworkService, Vpn,
+            dbus::ConnectivityState, AccessPoint, ActiveConnectionInfo, ConnectionDetails,
+            KnownConnection, NetworkData, NetworkService, Vpn, VpnState, WiredDevice,
+            WirelessAdapter,
         },
         ServiceEvent,
     },
-    style::{GhostButtonStyle, SettingsButtonStyle},
+    style::{GhostButtonStyle, SettingsButtonStyle, TextInputStyle},
     utils::IndicatorState,
 };
+use chrono::{DateTime, Local};
 use iced::{
-    widget::{button, column, container, horizontal_rule, row, scrollable, text, toggler, Column},
+    widget::{
+        button, column, container, horizontal_rule, row, scrollable, text, text_input, toggler,
+        Column, Row,
+    },
     window::Id,
     Alignment, Element, Length, Theme,
 };
+use zbus::zvariant::OwnedObjectPath;
+
+/// State of the VPN import form shown in the VPN submenu
+#[derive(Debug, Default, Clone)]
+pub struct VpnImport {
+    pub path: String,
+    pub importing: bool,
+    pub error: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub enum NetworkMessage {
     Event(ServiceEvent<NetworkService>),
     ToggleWiFi,
+    ToggleMobileData,
+    SelectWirelessAdapter(OwnedObjectPath),
+    ActivateWiredProfile(OwnedObjectPath, Option<OwnedObjectPath>),
+    DisconnectDevice(OwnedObjectPath),
     ScanNearByWiFi,
     WiFiMore(Id),
     VpnMore(Id),
     SelectAccessPoint(AccessPoint),
     RequestWiFiPassword(Id, String),
+    ConnectToOtherNetwork(Id, Option<String>),
     ToggleVpn(Vpn),
+    ImportVpn(Id),
+    VpnImportPathChanged(String),
+    ConfirmVpnImport(Id),
+    CancelVpnImport(Id),
     ToggleAirplaneMode,
+    SignInToNetwork(Id),
+    ShowConnectionDetails(AccessPoint),
+    CloseConnectionDetails,
+    ForgetConnection(String),
 }
 
 static WIFI_SIGNAL_ICONS: [Icons; 6] = [
@@ -39,6 +66,13 @@ static WIFI_SIGNAL_ICONS: [Icons; 6] = [
     Icons::Wifi5,
 ];
 
+static CELLULAR_SIGNAL_ICONS: [Icons; 4] = [
+    Icons::Cellular0,
+    Icons::Cellular1,
+    Icons::Cellular2,
+    Icons::Cellular3,
+];
+
 static WIFI_LOCK_SIGNAL_ICONS: [Icons; 5] = [
     Icons::WifiLock1,
     Icons::WifiLock2,
@@ -56,11 +90,16 @@ impl ActiveConnectionInfo {
         WIFI_LOCK_SIGNAL_ICONS[f32::round(signal as f32 / 100. * 4.) as usize]
     }
 
+    pub fn get_cellular_icon(signal: u8) -> Icons {
+        CELLULAR_SIGNAL_ICONS[f32::round(signal as f32 / 100. * 3.) as usize]
+    }
+
     pub fn get_icon(&self) -> Icons {
         match self {
             Self::WiFi { strength, .. } => Self::get_wifi_icon(*strength),
             Self::Wired { .. } => Icons::Ethernet,
             Self::Vpn { .. } => Icons::Vpn,
+            Self::Wwan { strength, .. } => Self::get_cellular_icon(*strength),
         }
     }
 
@@ -68,6 +107,9 @@ impl ActiveConnectionInfo {
         match self {
             Self::WiFi {
                 strength: 0 | 1, ..
+            }
+            | Self::Wwan {
+                strength: 0..=10, ..
             } => IndicatorState::Warning,
             _ => IndicatorState::Normal,
         }
@@ -76,6 +118,17 @@ impl ActiveConnectionInfo {
 
 impl NetworkData {
     pub fn get_connection_indicator<Message: 'static>(&self) -> Option<Element<Message>> {
+        if !self.airplane_mode && self.connectivity.needs_login() {
+            return Some(
+                container(icon(Icons::Portal))
+                    .style(|theme: &Theme| container::Style {
+                        text_color: Some(theme.extended_palette().danger.weak.color),
+                        ..Default::default()
+                    })
+                    .into(),
+            );
+        }
+
         if self.airplane_mode || !self.wifi_present {
             None
         } else {
@@ -110,6 +163,42 @@ impl NetworkData {
         }
     }
 
+    pub fn get_wwan_indicator<Message: 'static>(&self) -> Option<Element<Message>> {
+        if self.airplane_mode {
+            return None;
+        }
+
+        self.active_connections
+            .iter()
+            .find(|c| matches!(c, ActiveConnectionInfo::Wwan { .. }))
+            .map(|a| {
+                let access_technology = match a {
+                    ActiveConnectionInfo::Wwan {
+                        access_technology, ..
+                    } => access_technology.name(),
+                    _ => "",
+                };
+                let state = (self.connectivity, a.get_indicator_state());
+
+                container(
+                    row!(icon(a.get_icon()), text(access_technology).size(10))
+                        .align_y(Alignment::Center)
+                        .spacing(2),
+                )
+                .style(move |theme: &Theme| container::Style {
+                    text_color: match state {
+                        (ConnectivityState::Full, IndicatorState::Warning) => {
+                            Some(theme.extended_palette().danger.weak.color)
+                        }
+                        (ConnectivityState::Full, _) => None,
+                        _ => Some(theme.palette().danger),
+                    },
+                    ..Default::default()
+                })
+                .into()
+            })
+    }
+
     pub fn get_vpn_indicator<Message: 'static>(&self) -> Option<Element<Message>> {
         self.active_connections
             .iter()
@@ -126,10 +215,45 @@ impl NetworkData {
             })
     }
 
+    pub fn get_portal_login_button(&self, id: Id) -> Option<Element<NetworkMessage>> {
+        self.connectivity.needs_login().then(|| {
+            button(
+                row!(
+                    icon(Icons::Portal).size(20),
+                    column!(
+                        text("Sign in to network"),
+                        text(if self.connectivity == ConnectivityState::Portal {
+                            "This network requires a login"
+                        } else {
+                            "Limited connectivity"
+                        })
+                        .size(12),
+                    )
+                    .width(Length::Fill),
+                )
+                .spacing(12)
+                .align_y(Alignment::Center),
+            )
+            .padding([8, 12])
+            .width(Length::Fill)
+            .style(SettingsButtonStyle.into_style())
+            .on_press(NetworkMessage::SignInToNetwork(id))
+            .into()
+        })
+    }
+
+    /// The adapter shown in the Wi-Fi menu, the first one if none has been selected
+    pub fn wireless_adapter(&self, selected: Option<&OwnedObjectPath>) -> Option<&WirelessAdapter> {
+        selected
+            .and_then(|path| self.wireless_adapters.iter().find(|a| &a.path == path))
+            .or_else(|| self.wireless_adapters.first())
+    }
+
     pub fn get_wifi_quick_setting_button(
         &self,
         id: Id,
         sub_menu: Option<SubMenu>,
+        wireless_adapter: Option<&OwnedObjectPath>,
         show_more_button: bool,
     ) -> Option<(Element<Message>, Option<Element<Message>>)> {
         if self.wifi_present {
@@ -160,6 +284,7 @@ impl NetworkData {
                         sub_menu_wrapper(self.wifi_menu(
                             id,
                             active_connection.map(|(name, strengh, _)| (name.as_str(), *strengh)),
+                            wireless_adapter,
                             show_more_button,
                         ))
                         .map(Message::Network)
@@ -170,27 +295,32 @@ impl NetworkData {
         }
     }
 
-    pub fn get_vpn_quick_setting_button(
-        &self,
+    pub fn get_vpn_quick_setting_button<'a>(
+        &'a self,
         id: Id,
         sub_menu: Option<SubMenu>,
+        vpn_import: Option<&'a VpnImport>,
         show_more_button: bool,
-    ) -> (Element<Message>, Option<Element<Message>>) {
+    ) -> (Element<'a, Message>, Option<Element<'a, Message>>) {
+        let active_vpn = self
+            .active_connections
+            .iter()
+            .find(|c| matches!(c, ActiveConnectionInfo::Vpn { .. }));
+
         (
             quick_setting_button(
                 Icons::Vpn,
                 "Vpn".to_string(),
-                None,
-                self.active_connections
-                    .iter()
-                    .any(|c| matches!(c, ActiveConnectionInfo::Vpn { .. })),
+                active_vpn.map(|c| c.name()),
+                active_vpn.is_some(),
                 Message::ToggleSubMenu(SubMenu::Vpn),
                 None,
             ),
             sub_menu
                 .filter(|menu_type| *menu_type == SubMenu::Vpn)
                 .map(|_| {
-                    sub_menu_wrapper(self.vpn_menu(id, show_more_button)).map(Message::Network)
+                    sub_menu_wrapper(self.vpn_menu(id, vpn_import, show_more_button))
+                        .map(Message::Network)
                 }),
         )
     }
@@ -199,8 +329,55 @@ impl NetworkData {
         &self,
         id: Id,
         active_connection: Option<(&str, u8)>,
+        wireless_adapter: Option<&OwnedObjectPath>,
         show_more_button: bool,
     ) -> Element<NetworkMessage> {
+        if let Some(details) = &self.connection_details {
+            return self.connection_details_view(details);
+        }
+
+        let adapter = self.wireless_adapter(wireless_adapter);
+        let access_points = self
+            .wireless_access_points
+            .iter()
+            .filter(|ac| {
+                adapter.is_none()
+                    || adapter.map(|a| a.path.as_str()) == Some(ac.device_path.as_str())
+            })
+            .collect::<Vec<_>>();
+
+        let adapters = (self.wireless_adapters.len() > 1).then(|| {
+            Row::with_children(
+                self.wireless_adapters
+                    .iter()
+                    .map(|a| {
+                        let is_selected = adapter.is_some_and(|selected| selected.path == a.path);
+
+                        button(
+                            container(text(a.interface.clone())).style(move |theme: &Theme| {
+                                container::Style {
+                                    text_color: if is_selected {
+                                        Some(theme.palette().success)
+                                    } else {
+                                        None
+                                    },
+                                    ..Default::default()
+                                }
+                            }),
+                        )
+                        .style(GhostButtonStyle.into_style())
+                        .padding([4, 8])
+                        .on_press_maybe(
+                            (!is_selected)
+                                .then(|| NetworkMessage::SelectWirelessAdapter(a.path.clone())),
+                        )
+                        .into()
+                    })
+                    .collect::<Vec<Element<NetworkMessage>>>(),
+            )
+            .spacing(4)
+        });
+
         let main = column!(
             row!(
                 text("Nearby Wifi").width(Length::Fill),
@@ -218,15 +395,16 @@ impl NetworkData {
             .spacing(8)
             .width(Length::Fill)
             .align_y(Alignment::Center),
+            Column::new().push_maybe(adapters),
             horizontal_rule(1),
             container(scrollable(
                 Column::with_children(
-                    self.wireless_access_points
+                    access_points
                     .iter()
-                    .filter_map(|ac| if active_connection.is_some_and(|(ssid, _)| ssid == ac.ssid) {Some((ac, true))} else {None })
-                    .chain(self.wireless_access_points
+                    .filter_map(|ac| if active_connection.is_some_and(|(ssid, _)| ssid == ac.ssid) {Some((*ac, true))} else {None })
+                    .chain(access_points
                         .iter()
-                        .filter_map(|ac| if active_connection.is_some_and(|(ssid, _)| ssid == ac.ssid) {None} else {Some((ac, false))})
+                        .filter_map(|ac| if active_connection.is_some_and(|(ssid, _)| ssid == ac.ssid) {None} else {Some((*ac, false))})
                     )
                         .map(|(ac, is_active)| {
                             let is_known = self.known_connections.iter().any(|c| {
@@ -236,50 +414,68 @@ impl NetworkData {
                                 )
                             });
 
-                            button(
-                                container(
-                                    row!(
-                                        icon(if ac.public {
-                                            ActiveConnectionInfo::get_wifi_icon(ac.strength)
+                            Row::new()
+                                .push(
+                                    button(
+                                        container(
+                                            row!(
+                                                icon(if ac.public {
+                                                    ActiveConnectionInfo::get_wifi_icon(ac.strength)
+                                                } else {
+                                                    ActiveConnectionInfo::get_wifi_lock_icon(ac.strength)
+                                                })
+                                                .width(Length::Shrink),
+                                                text(ac.ssid.clone()).width(Length::Fill),
+                                            )
+                                            .align_y(Alignment::Center)
+                                            .spacing(8),
+                                        )
+                                        .style(move |theme: &Theme| {
+                                            container::Style {
+                                                text_color: if is_active {
+                                                    Some(theme.palette().success)
+                                                } else {
+                                                    None
+                                                },
+                                                ..Default::default()
+                                            }
+                                        }),
+                                    )
+                                    .style(GhostButtonStyle.into_style())
+                                    .padding([8, 8])
+                                    .on_press_maybe(if !is_active {
+                                        Some(if is_known {
+                                            NetworkMessage::SelectAccessPoint(ac.clone())
+                                        } else if ac.security.is_enterprise() {
+                                            NetworkMessage::ConnectToOtherNetwork(id, Some(ac.ssid.clone()))
                                         } else {
-                                            ActiveConnectionInfo::get_wifi_lock_icon(ac.strength)
+                                            NetworkMessage::RequestWiFiPassword(id, ac.ssid.clone())
                                         })
-                                        .width(Length::Shrink),
-                                        text(ac.ssid.clone()).width(Length::Fill),
-                                    )
-                                    .align_y(Alignment::Center)
-                                    .spacing(8),
+                                    } else {
+                                        None
+                                    })
+                                    .width(Length::Fill),
                                 )
-                                .style(move |theme: &Theme| {
-                                    container::Style {
-                                        text_color: if is_active {
-                                            Some(theme.palette().success)
-                                        } else {
-                                            None
-                                        },
-                                        ..Default::default()
-                                    }
-                                }),
-                            )
-                            .style(GhostButtonStyle.into_style())
-                            .padding([8, 8])
-                            .on_press_maybe(if !is_active {
-                                Some(if is_known {
-                                    NetworkMessage::SelectAccessPoint(ac.clone())
-                                } else {
-                                    NetworkMessage::RequestWiFiPassword(id, ac.ssid.clone())
-                                })
-                            } else {
-                                None
-                            })
-                            .width(Length::Fill)
-                            .into()
+                                .push_maybe((is_active || is_known).then(|| {
+                                    button(icon(Icons::Info))
+                                        .style(GhostButtonStyle.into_style())
+                                        .padding([8, 8])
+                                        .on_press(NetworkMessage::ShowConnectionDetails(ac.clone()))
+                                }))
+                                .align_y(Alignment::Center)
+                                .spacing(4)
+                                .into()
                         })
                         .collect::<Vec<Element<NetworkMessage>>>(),
                 )
                 .spacing(4)
             ))
             .max_height(200),
+            button("Connect to other network...")
+                .on_press(NetworkMessage::ConnectToOtherNetwork(id, None))
+                .padding([8, 8])
+                .width(Length::Fill)
+                .style(GhostButtonStyle.into_style()),
         )
         .spacing(8);
 
@@ -300,7 +496,78 @@ impl NetworkData {
         }
     }
 
-    pub fn vpn_menu(&self, id: Id, show_more_button: bool) -> Element<NetworkMessage> {
+    fn connection_details_view<'a>(
+        &'a self,
+        details: &'a ConnectionDetails,
+    ) -> Element<'a, NetworkMessage> {
+        let is_known = self.known_connections.iter().any(|c| {
+            matches!(
+                c,
+                KnownConnection::AccessPoint(AccessPoint { ssid, .. }) if ssid == &details.ssid
+            )
+        });
+
+        let mut info = Column::new()
+            .push(detail_row(
+                "Security".to_string(),
+                details.security.name().to_string(),
+            ))
+            .push(detail_row(
+                "Frequency".to_string(),
+                format!("{} MHz ({})", details.frequency, details.band()),
+            ))
+            .spacing(4);
+
+        for (version, ip_config) in [("IPv4", &details.ip4), ("IPv6", &details.ip6)] {
+            if let Some(ip_config) = ip_config {
+                for address in ip_config.addresses.iter() {
+                    info = info.push(detail_row(format!("{} address", version), address.clone()));
+                }
+                info =
+                    info.push_maybe(ip_config.gateway.as_ref().map(|gateway| {
+                        detail_row(format!("{} gateway", version), gateway.clone())
+                    }));
+                if !ip_config.dns.is_empty() {
+                    info = info.push(detail_row(
+                        format!("{} DNS", version),
+                        ip_config.dns.join(", "),
+                    ));
+                }
+            }
+        }
+
+        column!(
+            row!(
+                button(icon(Icons::LeftArrow))
+                    .padding([4, 10])
+                    .style(SettingsButtonStyle.into_style())
+                    .on_press(NetworkMessage::CloseConnectionDetails),
+                text(details.ssid.clone()).width(Length::Fill),
+            )
+            .spacing(8)
+            .width(Length::Fill)
+            .align_y(Alignment::Center),
+            horizontal_rule(1),
+            info,
+        )
+        .push_maybe(is_known.then(|| horizontal_rule(1)))
+        .push_maybe(is_known.then(|| {
+            button("Forget network")
+                .on_press(NetworkMessage::ForgetConnection(details.ssid.clone()))
+                .padding([4, 12])
+                .width(Length::Fill)
+                .style(GhostButtonStyle.into_style())
+        }))
+        .spacing(8)
+        .into()
+    }
+
+    pub fn vpn_menu<'a>(
+        &'a self,
+        id: Id,
+        vpn_import: Option<&'a VpnImport>,
+        show_more_button: bool,
+    ) -> Element<'a, NetworkMessage> {
         let main = Column::with_children(
             self.known_connections
                 .iter()
@@ -309,20 +576,82 @@ impl NetworkData {
                     _ => None,
                 })
                 .map(|vpn| {
-                    let is_active = self.active_connections.iter().any(
-                        |c| matches!(c, ActiveConnectionInfo::Vpn { name, .. } if name == &vpn.name),
-                    );
+                    let active = self.active_connections.iter().find_map(|c| match c {
+                        ActiveConnectionInfo::Vpn {
+                            name,
+                            state,
+                            remote,
+                            since,
+                            ..
+                        } if name == &vpn.name => Some((*state, remote, since)),
+                        _ => None,
+                    });
+
+                    let status = match active {
+                        Some((VpnState::Connecting, _, _)) => Some("Connecting...".to_string()),
+                        Some((VpnState::Disconnecting, _, _)) => {
+                            Some("Disconnecting...".to_string())
+                        }
+                        Some((VpnState::Connected, remote, since)) => Some(
+                            [
+                                Some(since.map_or_else(
+                                    || "Connected".to_string(),
+                                    |since| {
+                                        format!(
+                                            "Connected since {}",
+                                            DateTime::<Local>::from(since).format("%H:%M")
+                                        )
+                                    },
+                                )),
+                                remote.clone(),
+                            ]
+                            .into_iter()
+                            .flatten()
+                            .collect::<Vec<_>>()
+                            .join(" · "),
+                        ),
+                        None if self.failed_vpn.as_ref() == Some(&vpn.name) => {
+                            Some("Failed to connect".to_string())
+                        }
+                        None => None,
+                    };
+                    let failed = active.is_none() && status.is_some();
 
                     row!(
-                        text(vpn.name.to_string()).width(Length::Fill),
-                        toggler(is_active)
+                        Column::new()
+                            .push(text(vpn.name.to_string()))
+                            .push_maybe(status.map(|status| {
+                                container(text(status).size(12)).style(move |theme: &Theme| {
+                                    container::Style {
+                                        text_color: if failed {
+                                            Some(theme.palette().danger)
+                                        } else {
+                                            None
+                                        },
+                                        ..Default::default()
+                                    }
+                                })
+                            }))
+                            .width(Length::Fill),
+                        toggler(active.is_some())
                             .on_toggle(|_| { NetworkMessage::ToggleVpn(vpn.clone()) })
                             .width(Length::Shrink),
                     )
+                    .align_y(Alignment::Center)
                     .into()
                 })
                 .collect::<Vec<Element<NetworkMessage>>>(),
         )
+        .push(horizontal_rule(1))
+        .push(match vpn_import {
+            Some(vpn_import) => Self::vpn_import_view(id, vpn_import),
+            None => button("Import from file...")
+                .on_press(NetworkMessage::ImportVpn(id))
+                .padding([8, 8])
+                .width(Length::Fill)
+                .style(GhostButtonStyle.into_style())
+                .into(),
+        })
         .spacing(8);
 
         if show_more_button {
@@ -342,6 +671,232 @@ impl NetworkData {
         }
     }
 
+    fn vpn_import_view(id: Id, vpn_import: &VpnImport) -> Element<NetworkMessage> {
+        let confirm = (!vpn_import.path.is_empty() && !vpn_import.importing)
+            .then_some(NetworkMessage::ConfirmVpnImport(id));
+
+        column!(
+            text_input("Path to a WireGuard or OpenVPN file", &vpn_import.path)
+                .size(14)
+                .padding([8, 8])
+                .style(TextInputStyle.into_style())
+                .on_input(NetworkMessage::VpnImportPathChanged)
+                .on_submit_maybe(confirm.clone()),
+            row!(
+                Column::new()
+                    .push_maybe(vpn_import.importing.then(|| text("Importing...").size(12)))
+                    .push_maybe(vpn_import.error.as_ref().map(|error| {
+                        container(text(error.clone()).size(12)).style(|theme: &Theme| {
+                            container::Style {
+                                text_color: Some(theme.palette().danger),
+                                ..Default::default()
+                            }
+                        })
+                    }))
+                    .width(Length::Fill),
+                button("Cancel")
+                    .padding([4, 12])
+                    .style(SettingsButtonStyle.into_style())
+                    .on_press(NetworkMessage::CancelVpnImport(id)),
+                button("Import")
+                    .padding([4, 12])
+                    .style(SettingsButtonStyle.into_style())
+                    .on_press_maybe(confirm),
+            )
+            .spacing(8)
+            .align_y(Alignment::Center),
+        )
+        .spacing(8)
+        .into()
+    }
+
+    pub fn get_ethernet_quick_setting_button(
+        &self,
+        sub_menu: Option<SubMenu>,
+    ) -> Option<(Element<Message>, Option<Element<Message>>)> {
+        if self.wired_devices.is_empty() {
+            return None;
+        }
+
+        let connected = self
+            .wired_devices
+            .iter()
+            .find(|d| d.active_profile.is_some());
+        let subtitle = connected
+            .and_then(|d| {
+                d.profiles
+                    .iter()
+                    .find(|p| d.active_profile.as_ref() == Some(&p.path))
+            })
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| "Disconnected".to_string());
+        let on_press = match connected {
+            Some(device) => NetworkMessage::DisconnectDevice(device.path.clone()),
+            None => {
+                let device = self
+                    .wired_devices
+                    .iter()
+                    .find(|d| d.carrier)
+                    .unwrap_or(&self.wired_devices[0]);
+
+                NetworkMessage::ActivateWiredProfile(device.path.clone(), None)
+            }
+        };
+
+        Some((
+            quick_setting_button(
+                Icons::Ethernet,
+                "Ethernet".to_string(),
+                Some(subtitle),
+                connected.is_some(),
+                Message::Network(on_press),
+                Some((
+                    SubMenu::Ethernet,
+                    sub_menu,
+                    Message::ToggleSubMenu(SubMenu::Ethernet),
+                )),
+            ),
+            sub_menu
+                .filter(|menu_type| *menu_type == SubMenu::Ethernet)
+                .map(|_| sub_menu_wrapper(self.ethernet_menu()).map(Message::Network)),
+        ))
+    }
+
+    pub fn ethernet_menu(&self) -> Element<NetworkMessage> {
+        Column::with_children(
+            self.wired_devices
+                .iter()
+                .enumerate()
+                .flat_map(|(index, device)| {
+                    let details: Element<NetworkMessage> =
+                        self.wired_device_view(device, self.wired_devices.len() > 1);
+
+                    if index > 0 {
+                        vec![horizontal_rule(1).into(), details]
+                    } else {
+                        vec![details]
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+        .spacing(8)
+        .into()
+    }
+
+    fn wired_device_view<'a>(
+        &'a self,
+        device: &'a WiredDevice,
+        show_interface: bool,
+    ) -> Element<'a, NetworkMessage> {
+        let state = if !device.carrier {
+            "Cable unplugged".to_string()
+        } else if device.active_profile.is_some() {
+            if device.speed > 0 {
+                format!("Connected · {} Mb/s", device.speed)
+            } else {
+                "Connected".to_string()
+            }
+        } else {
+            "Disconnected".to_string()
+        };
+
+        column!(
+            row!(
+                column!(
+                    text(if show_interface {
+                        device.interface.clone()
+                    } else {
+                        "Ethernet".to_string()
+                    }),
+                    text(state).size(12),
+                )
+                .width(Length::Fill),
+                button(if device.active_profile.is_some() {
+                    "Disconnect"
+                } else {
+                    "Connect"
+                })
+                .padding([4, 12])
+                .style(SettingsButtonStyle.into_style())
+                .on_press_maybe(if device.active_profile.is_some() {
+                    Some(NetworkMessage::DisconnectDevice(device.path.clone()))
+                } else {
+                    device
+                        .carrier
+                        .then(|| NetworkMessage::ActivateWiredProfile(device.path.clone(), None))
+                }),
+            )
+            .spacing(8)
+            .align_y(Alignment::Center),
+            Column::with_children(
+                device
+                    .profiles
+                    .iter()
+                    .map(|profile| {
+                        let is_active = device.active_profile.as_ref() == Some(&profile.path);
+
+                        button(
+                            container(text(profile.name.clone())).style(move |theme: &Theme| {
+                                container::Style {
+                                    text_color: if is_active {
+                                        Some(theme.palette().success)
+                                    } else {
+                                        None
+                                    },
+                                    ..Default::default()
+                                }
+                            }),
+                        )
+                        .style(GhostButtonStyle.into_style())
+                        .padding([8, 8])
+                        .on_press_maybe((!is_active && device.carrier).then(|| {
+                            NetworkMessage::ActivateWiredProfile(
+                                device.path.clone(),
+                                Some(profile.path.clone()),
+                            )
+                        }))
+                        .width(Length::Fill)
+                        .into()
+                    })
+                    .collect::<Vec<Element<NetworkMessage>>>(),
+            )
+            .spacing(4),
+        )
+        .spacing(8)
+        .into()
+    }
+
+    pub fn get_mobile_data_quick_setting_button(
+        &self,
+    ) -> Option<(Element<Message>, Option<Element<Message>>)> {
+        let modem = self.modems.first()?;
+
+        let subtitle = [
+            modem
+                .operator
+                .clone()
+                .unwrap_or_else(|| modem.model.clone()),
+            modem.access_technology.name().to_string(),
+            format!("{}%", modem.signal),
+        ]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+        Some((
+            quick_setting_button(
+                ActiveConnectionInfo::get_cellular_icon(modem.signal),
+                "Mobile Data".to_string(),
+                Some(subtitle),
+                self.wwan_enabled,
+                Message::Network(NetworkMessage::ToggleMobileData),
+                None,
+            ),
+            None,
+        ))
+    }
+
     pub fn get_airplane_mode_quick_setting_button(
         &self,
     ) -> (Element<Message>, Option<Element<Message>>) {
@@ -358,3 +913,12 @@ impl NetworkData {
         )
     }
 }
+
+fn detail_row<'a>(label: String, value: String) -> Element<'a, NetworkMessage> {
+    row!(
+        text(label).size(12).width(Length::Fill),
+        text(value).size(12),
+    )
+    .spacing(8)
+    .into()
+}
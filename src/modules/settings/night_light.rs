@@ -0,0 +1,84 @@
+use super::{quick_setting_button, sub_menu_wrapper, Message, SubMenu};
+use crate::{
+    components::icons::{icon, Icons},
+    config::NightLightConfig,
+    services::night_light::{NightLightManager, SunTimes, MIN_TEMPERATURE, NEUTRAL_TEMPERATURE},
+};
+use chrono::Local;
+use iced::{
+    widget::{container, row, slider, text, Column},
+    Alignment, Element, Length,
+};
+
+#[derive(Debug, Clone)]
+pub enum NightLightMessage {
+    Toggle,
+    Temperature(u32),
+    ScheduleTick,
+}
+
+/// Today's sunrise and sunset, `None` when the coordinates aren't configured
+pub fn schedule(config: &NightLightConfig) -> Option<SunTimes> {
+    config
+        .latitude
+        .zip(config.longitude)
+        .map(|(latitude, longitude)| SunTimes::new(Local::now().date_naive(), latitude, longitude))
+}
+
+impl NightLightManager {
+    pub fn get_quick_setting_button(
+        &self,
+        sub_menu: Option<SubMenu>,
+        temperature: u32,
+        config: &NightLightConfig,
+    ) -> (Element<Message>, Option<Element<Message>>) {
+        (
+            quick_setting_button(
+                Icons::NightLight,
+                "Night Light".to_string(),
+                self.is_enabled().then(|| format!("{}K", temperature)),
+                self.is_enabled(),
+                Message::NightLight(NightLightMessage::Toggle),
+                Some((
+                    SubMenu::NightLight,
+                    sub_menu,
+                    Message::ToggleSubMenu(SubMenu::NightLight),
+                )),
+            ),
+            sub_menu
+                .filter(|menu_type| *menu_type == SubMenu::NightLight)
+                .map(|_| sub_menu_wrapper(night_light_menu(temperature, config))),
+        )
+    }
+}
+
+fn night_light_menu<'a>(temperature: u32, config: &NightLightConfig) -> Element<'a, Message> {
+    let schedule = schedule(config).map(|sun_times| match sun_times {
+        SunTimes::Daylight { sunrise, sunset } => format!(
+            "Scheduled from {} to {}",
+            sunset.format("%H:%M"),
+            sunrise.format("%H:%M")
+        ),
+        SunTimes::PolarDay => "Scheduled, the sun doesn't set today".to_string(),
+        SunTimes::PolarNight => "Scheduled, the sun doesn't rise today".to_string(),
+    });
+
+    Column::new()
+        .push(
+            row!(
+                container(icon(Icons::NightLight)).padding([8, 11]),
+                slider(MIN_TEMPERATURE..=NEUTRAL_TEMPERATURE, temperature, |v| {
+                    Message::NightLight(NightLightMessage::Temperature(v))
+                })
+                .step(100_u32)
+                .width(Length::Fill),
+                text(format!("{}K", temperature)).size(12),
+            )
+            .align_y(Alignment::Center)
+            .spacing(8),
+        )
+        .push_maybe(schedule.map(|schedule| text(schedule).size(12)))
+        .spacing(8)
+        .padding([0, 8])
+        .into()
+}
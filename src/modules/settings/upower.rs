@@ -1,15 +1,27 @@
 use crate::{
     components::icons::{icon, Icons},
+    config::{BatteryConfig, CriticalBatteryAction},
     services::{
-        upower::{BatteryData, BatteryStatus, PowerProfile, UPowerService},
+        upower::{
+            BatteryData, BatteryDetails, BatteryStatus, PowerDevice, PowerProfile, UPowerService,
+            HELPER_CHARGE_LIMIT,
+        },
         ServiceEvent,
     },
-    utils::{format_duration, IndicatorState},
+    style::HeaderButtonStyle,
+    utils::{
+        format_duration, launcher,
+        notifications::{self, Urgency},
+        IndicatorState,
+    },
 };
 use iced::{
-    widget::{container, row, text, Container},
-    Alignment, Background, Border, Element, Theme,
+    widget::{button, column, container, row, text, toggler, Column, Container},
+    Alignment, Background, Border, Element, Length, Theme,
 };
+use log::info;
+use std::time::{Duration, Instant};
+use zbus::zvariant::OwnedObjectPath;
 
 use super::{quick_setting_button, Message};
 
@@ -17,6 +29,160 @@ use super::{quick_setting_button, Message};
 pub enum UPowerMessage {
     Event(ServiceEvent<UPowerService>),
     TogglePowerProfile,
+    ToggleChargeLimit(OwnedObjectPath, bool),
+    CriticalActionTick,
+    CancelCriticalAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BatteryLevel {
+    Warning,
+    Critical,
+}
+
+/// Low battery notifications and the countdown of the critical battery action
+#[derive(Debug, Default)]
+pub struct BatteryAlert {
+    level: Option<BatteryLevel>,
+    action_deadline: Option<Instant>,
+}
+
+impl BatteryAlert {
+    pub fn countdown_active(&self) -> bool {
+        self.action_deadline.is_some()
+    }
+
+    /// Notify every time a discharging battery goes below a threshold, at the critical
+    /// level the configured action is scheduled, plugging the charger resets everything
+    pub fn check(&mut self, battery: Option<BatteryData>, config: &BatteryConfig) {
+        let level = battery.and_then(|battery| match battery.status {
+            BatteryStatus::Discharging(_) if battery.capacity <= config.critical_threshold => {
+                Some((BatteryLevel::Critical, battery))
+            }
+            BatteryStatus::Discharging(_) if battery.capacity <= config.warning_threshold => {
+                Some((BatteryLevel::Warning, battery))
+            }
+            _ => None,
+        });
+
+        let Some((level, battery)) = level else {
+            self.level = None;
+            self.action_deadline = None;
+            return;
+        };
+
+        if self.level.is_some_and(|current| current >= level) {
+            return;
+        }
+        self.level = Some(level);
+
+        let remaining = match battery.status {
+            BatteryStatus::Discharging(remaining) if remaining.as_secs() > 0 => {
+                format!(
+                    "{}% remaining, empty in {}",
+                    battery.capacity,
+                    format_duration(&remaining)
+                )
+            }
+            _ => format!("{}% remaining", battery.capacity),
+        };
+
+        match level {
+            BatteryLevel::Warning => notifications::notify(
+                "Battery low".to_string(),
+                remaining,
+                "battery-low",
+                Urgency::Normal,
+            ),
+            BatteryLevel::Critical => {
+                let action = Self::action_name(config);
+                if action.is_some() {
+                    self.action_deadline =
+                        Some(Instant::now() + Duration::from_secs(config.critical_countdown));
+                }
+
+                notifications::notify(
+                    "Battery critically low".to_string(),
+                    match action {
+                        Some(action) => format!(
+                            "{}\n{} in {} seconds, cancel from the bar",
+                            remaining, action, config.critical_countdown
+                        ),
+                        None => remaining,
+                    },
+                    "battery-caution",
+                    Urgency::Critical,
+                );
+            }
+        }
+    }
+
+    fn action_name(config: &BatteryConfig) -> Option<&'static str> {
+        match config.critical_action? {
+            CriticalBatteryAction::Suspend => Some("Suspending"),
+            CriticalBatteryAction::Hibernate => Some("Hibernating"),
+            CriticalBatteryAction::Command if config.critical_cmd.is_some() => {
+                Some("Running the critical battery command")
+            }
+            CriticalBatteryAction::Command => None,
+        }
+    }
+
+    /// Run the critical action once the countdown is over
+    pub fn tick(&mut self, config: &BatteryConfig) {
+        if self
+            .action_deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            self.action_deadline = None;
+
+            info!(
+                "Battery critically low, running {:?}",
+                config.critical_action
+            );
+            match config.critical_action {
+                Some(CriticalBatteryAction::Suspend) => launcher::suspend(),
+                Some(CriticalBatteryAction::Hibernate) => launcher::hibernate(),
+                Some(CriticalBatteryAction::Command) => {
+                    if let Some(cmd) = config.critical_cmd.clone() {
+                        launcher::execute_command(cmd);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.action_deadline = None;
+    }
+
+    pub fn countdown_indicator<'a>(&self) -> Option<Element<'a, Message>> {
+        let remaining = self
+            .action_deadline?
+            .saturating_duration_since(Instant::now())
+            .as_secs();
+
+        Some(
+            button(
+                row!(
+                    icon(Icons::Battery0),
+                    text(format!("{}s", remaining)),
+                    icon(Icons::Close)
+                )
+                .spacing(4)
+                .align_y(Alignment::Center),
+            )
+            .padding([2, 8])
+            .on_press(Message::UPower(UPowerMessage::CancelCriticalAction))
+            .style(|theme: &Theme, status| {
+                let mut style = HeaderButtonStyle::Full.into_style()(theme, status);
+                style.text_color = theme.palette().danger;
+                style
+            })
+            .into(),
+        )
+    }
 }
 
 impl BatteryData {
@@ -78,6 +244,104 @@ impl BatteryData {
     }
 }
 
+impl PowerDevice {
+    fn remaining(&self) -> String {
+        match self.battery.status {
+            BatteryStatus::Charging(remaining) if remaining.as_secs() > 0 => {
+                format!("Full in {}", format_duration(&remaining))
+            }
+            BatteryStatus::Charging(_) => "Charging".to_string(),
+            BatteryStatus::Discharging(remaining) if remaining.as_secs() > 0 => {
+                format!("Empty in {}", format_duration(&remaining))
+            }
+            BatteryStatus::Discharging(_) => "Discharging".to_string(),
+            BatteryStatus::Full => "Fully charged".to_string(),
+        }
+    }
+
+    fn view<'a>(&self) -> Element<'a, Message> {
+        let state = self.battery.get_indicator_state();
+
+        row!(
+            container(icon(self.get_icon())).style(move |theme: &Theme| container::Style {
+                text_color: Some(match state {
+                    IndicatorState::Success => theme.palette().success,
+                    IndicatorState::Danger => theme.palette().danger,
+                    _ => theme.palette().text,
+                }),
+                ..Default::default()
+            }),
+            column!(text(self.name.clone()), text(self.remaining()).size(12)).width(Length::Fill),
+            text(format!("{}%", self.battery.capacity)),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+}
+
+impl BatteryDetails {
+    fn view<'a>(&self, has_charge_limit_helper: bool) -> Element<'a, Message> {
+        let mut info = Vec::new();
+        if let Some(health) = self.health {
+            info.push(format!("Health {:.0}%", health));
+        }
+        if let Some(cycles) = self.cycles {
+            info.push(format!("{} cycles", cycles));
+        }
+        if self.energy_rate > 0. {
+            info.push(format!("{:.1} W", self.energy_rate));
+        }
+        if let Some((start, end)) = self.charge_thresholds {
+            info.push(format!("Charge thresholds {}% - {}%", start, end));
+        }
+
+        // UPower applies its configured end threshold, the helper a fixed one
+        let limit = match self.charge_thresholds {
+            Some((_, end)) if self.charge_limit_supported && !has_charge_limit_helper => end,
+            _ => HELPER_CHARGE_LIMIT,
+        };
+
+        column!(text(info.join(" · ")).size(12))
+            .push_maybe(
+                (self.charge_limit_supported || has_charge_limit_helper).then(|| {
+                    toggler(self.charge_limit_enabled)
+                        .label(format!("Conservation mode ({}%)", limit))
+                        .on_toggle({
+                            let path = self.path.clone();
+                            move |enabled| {
+                                Message::UPower(UPowerMessage::ToggleChargeLimit(
+                                    path.clone(),
+                                    enabled,
+                                ))
+                            }
+                        })
+                }),
+            )
+            .spacing(4)
+            .padding([0, 24])
+            .into()
+    }
+}
+
+impl UPowerService {
+    pub fn devices_menu(&self, has_charge_limit_helper: bool) -> Element<Message> {
+        Column::with_children(self.devices.iter().map(|device| {
+            column!(device.view())
+                .push_maybe(
+                    device
+                        .details
+                        .as_ref()
+                        .map(|details| details.view(has_charge_limit_helper)),
+                )
+                .spacing(4)
+                .into()
+        }))
+        .spacing(8)
+        .into()
+    }
+}
+
 impl PowerProfile {
     pub fn indicator<Message: 'static>(&self) -> Option<Element<Message>> {
         match self {
@@ -0,0 +1,553 @@
+use crate::{
+    app::MenuType,
+    components::icons::{icon, Icons},
+    config::TimerModuleConfig,
+    outputs::Outputs,
+    style::{GhostButtonStyle, HeaderButtonStyle, OutlineButtonStyle},
+    utils::{
+        format_clock,
+        notifications::{self, Urgency},
+    },
+};
+use iced::{
+    alignment::Horizontal,
+    futures::{channel::mpsc::Sender, SinkExt},
+    stream::channel,
+    time::every,
+    widget::{button, column, container, horizontal_rule, row, text, Column, Row},
+    window::Id,
+    Alignment, Element, Length, Subscription, Task,
+};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::TypeId,
+    env, fs,
+    future::pending,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use zbus::interface;
+
+const TIMER_NAME: &str = "org.ashell.Timer";
+const TIMER_PATH: &str = "/org/ashell/Timer";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum TimerKind {
+    Countdown,
+    Stopwatch,
+    Pomodoro,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    fn label(self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "Work",
+            PomodoroPhase::ShortBreak => "Short break",
+            PomodoroPhase::LongBreak => "Long break",
+        }
+    }
+
+    fn duration(self, config: &TimerModuleConfig) -> Duration {
+        Duration::from_secs(
+            60 * match self {
+                PomodoroPhase::Work => config.pomodoro_work,
+                PomodoroPhase::ShortBreak => config.pomodoro_short_break,
+                PomodoroPhase::LongBreak => config.pomodoro_long_break,
+            },
+        )
+    }
+}
+
+/// The running timer, saved in the XDG state dir to survive restarts
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TimerState {
+    kind: TimerKind,
+    /// Length of the countdown or of the pomodoro phase, the stopwatch has none
+    duration: Option<Duration>,
+    /// Time counted before the last start or resume
+    elapsed: Duration,
+    /// Wall clock time of the last start or resume, `None` while paused
+    running_since: Option<SystemTime>,
+    phase: PomodoroPhase,
+    /// Completed work phases, counted to schedule the long breaks
+    pomodoros: u32,
+}
+
+impl TimerState {
+    fn new(kind: TimerKind, duration: Option<Duration>) -> Self {
+        Self {
+            kind,
+            duration,
+            elapsed: Duration::ZERO,
+            running_since: Some(SystemTime::now()),
+            phase: PomodoroPhase::Work,
+            pomodoros: 0,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+            + self
+                .running_since
+                .and_then(|since| SystemTime::now().duration_since(since).ok())
+                .unwrap_or_default()
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.duration
+            .map(|duration| duration.saturating_sub(self.elapsed()))
+    }
+
+    /// Time shown in the bar, the remaining one or the elapsed one of the stopwatch
+    fn display(&self) -> String {
+        format_clock(self.remaining().unwrap_or_else(|| self.elapsed()))
+    }
+
+    fn pause(&mut self) {
+        self.elapsed = self.elapsed();
+        self.running_since = None;
+    }
+
+    fn resume(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(SystemTime::now());
+        }
+    }
+
+    /// Move to the next pomodoro phase, notifying the end of the current one
+    fn next_phase(&mut self, config: &TimerModuleConfig) {
+        let next = match self.phase {
+            PomodoroPhase::Work => {
+                self.pomodoros += 1;
+                if self.pomodoros % config.pomodoro_long_break_interval.max(1) == 0 {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                }
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Work,
+        };
+
+        notifications::notify(
+            format!("{} is over", self.phase.label()),
+            format!(
+                "{} for {} minutes",
+                next.label(),
+                next.duration(config).as_secs() / 60
+            ),
+            "alarm-symbolic",
+            Urgency::Normal,
+        );
+
+        self.phase = next;
+        self.duration = Some(next.duration(config));
+        self.elapsed = Duration::ZERO;
+        self.running_since = Some(SystemTime::now());
+    }
+}
+
+fn state_path() -> PathBuf {
+    let state_home = env::var("XDG_STATE_HOME").unwrap_or_else(|_| {
+        format!(
+            "{}/.local/state",
+            env::var("HOME").expect("Could not get HOME environment variable")
+        )
+    });
+
+    PathBuf::from(state_home).join("ashell").join("timer.yaml")
+}
+
+fn load_state() -> Option<TimerState> {
+    let content = fs::read_to_string(state_path()).ok()?;
+
+    serde_yaml::from_str(&content)
+        .inspect_err(|e| warn!("Failed to read the timer state: {}", e))
+        .ok()
+}
+
+fn save_state(state: Option<&TimerState>) {
+    let path = state_path();
+
+    let result = match state {
+        Some(state) => serde_yaml::to_string(state)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&path, content)?;
+
+                Ok(())
+            }),
+        None if path.exists() => fs::remove_file(&path).map_err(anyhow::Error::from),
+        None => Ok(()),
+    };
+
+    if let Err(e) = result {
+        error!("Failed to save the timer state: {}", e);
+    }
+}
+
+/// Parse durations like `90s`, `25m` or `1h30m`, a bare number is in minutes
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(minutes) = value.parse::<u64>() {
+        return Some(Duration::from_secs(minutes * 60));
+    }
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' if !number.is_empty() => {
+                let value = number.parse::<u64>().ok()?;
+                seconds += value
+                    * match c {
+                        'h' => 3600,
+                        'm' => 60,
+                        _ => 1,
+                    };
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+
+    (number.is_empty() && seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+pub struct Timer {
+    state: Option<TimerState>,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self {
+            state: load_state(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ToggleMenu(Id),
+    StartCountdown(Duration),
+    StartStopwatch,
+    StartPomodoro,
+    Pause,
+    Resume,
+    TogglePause,
+    Skip,
+    Stop,
+    Tick,
+}
+
+impl Timer {
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &TimerModuleConfig,
+        outputs: &mut Outputs,
+    ) -> Task<crate::app::Message> {
+        match message {
+            Message::ToggleMenu(id) => return outputs.toggle_menu(id, MenuType::Timer),
+            Message::StartCountdown(duration) => {
+                self.state = Some(TimerState::new(TimerKind::Countdown, Some(duration)));
+            }
+            Message::StartStopwatch => {
+                self.state = Some(TimerState::new(TimerKind::Stopwatch, None));
+            }
+            Message::StartPomodoro => {
+                self.state = Some(TimerState::new(
+                    TimerKind::Pomodoro,
+                    Some(PomodoroPhase::Work.duration(config)),
+                ));
+            }
+            Message::Pause => {
+                if let Some(state) = self.state.as_mut() {
+                    state.pause();
+                }
+            }
+            Message::Resume => {
+                if let Some(state) = self.state.as_mut() {
+                    state.resume();
+                }
+            }
+            Message::TogglePause => {
+                if let Some(state) = self.state.as_mut() {
+                    if state.is_running() {
+                        state.pause();
+                    } else {
+                        state.resume();
+                    }
+                }
+            }
+            Message::Skip => {
+                if let Some(state) = self
+                    .state
+                    .as_mut()
+                    .filter(|state| state.kind == TimerKind::Pomodoro)
+                {
+                    state.next_phase(config);
+                }
+            }
+            Message::Stop => {
+                self.state = None;
+            }
+            Message::Tick => {
+                let Some(state) = self
+                    .state
+                    .as_mut()
+                    .filter(|state| state.remaining() == Some(Duration::ZERO))
+                else {
+                    return Task::none();
+                };
+
+                match state.kind {
+                    TimerKind::Pomodoro => state.next_phase(config),
+                    _ => {
+                        notifications::notify(
+                            "Time is up".to_owned(),
+                            "The countdown is over".to_owned(),
+                            "alarm-symbolic",
+                            Urgency::Normal,
+                        );
+                        self.state = None;
+                    }
+                }
+            }
+        }
+
+        save_state(self.state.as_ref());
+
+        Task::none()
+    }
+
+    pub fn view(&self, id: Id) -> Element<Message> {
+        let content: Element<Message> = match &self.state {
+            Some(state) => row!(
+                icon(match (state.kind, state.phase) {
+                    (TimerKind::Stopwatch, _) => Icons::Stopwatch,
+                    (TimerKind::Pomodoro, PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak) => {
+                        Icons::Coffee
+                    }
+                    _ => Icons::Timer,
+                }),
+                text(state.display()),
+            )
+            .push_maybe((!state.is_running()).then(|| icon(Icons::MediaPause)))
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into(),
+            None => icon(Icons::Timer).into(),
+        };
+
+        button(content)
+            .padding([2, 8])
+            .on_press(Message::ToggleMenu(id))
+            .style(HeaderButtonStyle::Full.into_style())
+            .into()
+    }
+
+    pub fn menu_view(&self, config: &TimerModuleConfig) -> Element<Message> {
+        let content: Element<Message> = match &self.state {
+            Some(state) => {
+                let title = match state.kind {
+                    TimerKind::Countdown => "Countdown".to_owned(),
+                    TimerKind::Stopwatch => "Stopwatch".to_owned(),
+                    TimerKind::Pomodoro => {
+                        format!("{} · {} done", state.phase.label(), state.pomodoros)
+                    }
+                };
+
+                let controls = row!(
+                    if state.is_running() {
+                        button(icon(Icons::MediaPause))
+                            .padding([8, 12])
+                            .on_press(Message::Pause)
+                            .style(OutlineButtonStyle.into_style())
+                    } else {
+                        button(icon(Icons::MediaPlay))
+                            .padding([8, 12])
+                            .on_press(Message::Resume)
+                            .style(OutlineButtonStyle.into_style())
+                    },
+                    button(icon(Icons::Stop))
+                        .padding([8, 12])
+                        .on_press(Message::Stop)
+                        .style(OutlineButtonStyle.into_style()),
+                )
+                .push_maybe((state.kind == TimerKind::Pomodoro).then(|| {
+                    button(icon(Icons::MediaNext))
+                        .padding([8, 12])
+                        .on_press(Message::Skip)
+                        .style(OutlineButtonStyle.into_style())
+                }))
+                .spacing(8);
+
+                column!(
+                    text(title).size(12),
+                    text(state.display()).size(32),
+                    controls,
+                )
+                .spacing(8)
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .into()
+            }
+            None => column!(
+                text("Countdown").size(12),
+                Row::with_children(config.presets.iter().map(|minutes| {
+                    button(text(format!("{}m", minutes)).size(12))
+                        .padding([4, 12])
+                        .on_press(Message::StartCountdown(Duration::from_secs(minutes * 60)))
+                        .style(OutlineButtonStyle.into_style())
+                        .into()
+                }))
+                .spacing(8),
+                horizontal_rule(1),
+                menu_button(
+                    Icons::Stopwatch,
+                    "Stopwatch".to_owned(),
+                    Message::StartStopwatch
+                ),
+                menu_button(
+                    Icons::Timer,
+                    format!(
+                        "Pomodoro ({}/{} minutes)",
+                        config.pomodoro_work, config.pomodoro_short_break
+                    ),
+                    Message::StartPomodoro
+                ),
+            )
+            .spacing(8)
+            .into(),
+        };
+
+        container(content)
+            .padding(16)
+            .width(Length::Fixed(250.))
+            .align_x(Horizontal::Center)
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let tick = if self.state.as_ref().is_some_and(|state| state.is_running()) {
+            every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![ipc_subscription(), tick])
+    }
+}
+
+fn menu_button<'a>(icon_type: Icons, label: String, message: Message) -> Element<'a, Message> {
+    button(
+        row!(icon(icon_type), text(label).size(12))
+            .spacing(8)
+            .align_y(Alignment::Center),
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .on_press(message)
+    .style(GhostButtonStyle.into_style())
+    .into()
+}
+
+/// Commands from other processes, e.g.
+/// `busctl --user call org.ashell.Timer /org/ashell/Timer org.ashell.Timer Countdown s 25m`
+struct TimerIpc {
+    output: Sender<Message>,
+}
+
+impl TimerIpc {
+    async fn send(&mut self, message: Message) -> zbus::fdo::Result<()> {
+        self.output
+            .send(message)
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[interface(name = "org.ashell.Timer")]
+impl TimerIpc {
+    async fn countdown(&mut self, duration: &str) -> zbus::fdo::Result<()> {
+        let duration = parse_duration(duration).ok_or_else(|| {
+            zbus::fdo::Error::InvalidArgs(format!("invalid duration: {}", duration))
+        })?;
+
+        self.send(Message::StartCountdown(duration)).await
+    }
+
+    async fn stopwatch(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::StartStopwatch).await
+    }
+
+    async fn pomodoro(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::StartPomodoro).await
+    }
+
+    async fn pause(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::Pause).await
+    }
+
+    async fn resume(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::Resume).await
+    }
+
+    async fn toggle(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::TogglePause).await
+    }
+
+    async fn skip(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::Skip).await
+    }
+
+    async fn stop(&mut self) -> zbus::fdo::Result<()> {
+        self.send(Message::Stop).await
+    }
+}
+
+fn ipc_subscription() -> Subscription<Message> {
+    Subscription::run_with_id(
+        TypeId::of::<TimerIpc>(),
+        channel(10, |output| async move {
+            let conn = async {
+                zbus::connection::Builder::session()?
+                    .name(TIMER_NAME)?
+                    .serve_at(TIMER_PATH, TimerIpc { output })?
+                    .build()
+                    .await
+            }
+            .await;
+
+            // the connection serves the commands until the subscription is dropped
+            let _conn = match conn {
+                Ok(conn) => conn,
+                Err(e) => {
+                    error!("Failed to serve the timer commands: {}", e);
+                    return pending().await;
+                }
+            };
+
+            pending::<()>().await
+        }),
+    )
+}
@@ -0,0 +1,281 @@
+use crate::{
+    app::MenuType,
+    components::icons::{icon, Icons},
+    outputs::Outputs,
+    services::{
+        tray::{TrayCommand, TrayIcon, TrayItem, TrayItemStatus, TrayMenuItem, TrayService},
+        ReadOnlyService, Service, ServiceEvent,
+    },
+    style::{header_pills, GhostButtonStyle},
+};
+use iced::{
+    widget::{
+        button, container, horizontal_rule, image, mouse_area, row, svg, text, Column, Row, Space,
+    },
+    window::Id,
+    Alignment, Element, Length, Padding, Subscription, Task, Theme,
+};
+
+const ICON_SIZE: f32 = 16.;
+
+#[derive(Default)]
+pub struct Tray {
+    service: Option<TrayService>,
+    selected: Option<String>,
+    expanded: Vec<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Event(ServiceEvent<TrayService>),
+    Activate(String),
+    ToggleMenu(Id, String),
+    ToggleSubmenu(i32),
+    MenuItemClicked(Id, i32),
+}
+
+impl Tray {
+    fn item(&self, address: &str) -> Option<&TrayItem> {
+        self.service
+            .as_ref()?
+            .items
+            .iter()
+            .find(|item| item.address == address)
+    }
+
+    fn command(&mut self, command: TrayCommand) -> Task<crate::app::Message> {
+        match self.service.as_mut() {
+            Some(service) => service
+                .command(command)
+                .map(|event| crate::app::Message::Tray(Message::Event(event))),
+            None => Task::none(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message, outputs: &mut Outputs) -> Task<crate::app::Message> {
+        match message {
+            Message::Event(event) => {
+                match event {
+                    ServiceEvent::Init(service) => {
+                        self.service = Some(service);
+                    }
+                    ServiceEvent::Update(data) => {
+                        if let Some(service) = self.service.as_mut() {
+                            service.update(data);
+                        }
+                    }
+                    ServiceEvent::Error(_) => {}
+                }
+                Task::none()
+            }
+            Message::Activate(address) => self.command(TrayCommand::Activate(address)),
+            Message::ToggleMenu(id, address) => {
+                let about_to_show = self
+                    .item(&address)
+                    .and_then(|item| item.menu.as_ref())
+                    .map(|menu| TrayCommand::AboutToShow(address.clone(), menu.path.clone()));
+
+                // switching to another item keeps the menu open
+                let menu_task = if self.selected.as_deref() == Some(address.as_str()) {
+                    outputs.toggle_menu(id, MenuType::Tray)
+                } else {
+                    self.selected = Some(address);
+                    self.expanded.clear();
+                    outputs.open_menu(id, MenuType::Tray)
+                };
+
+                match about_to_show {
+                    Some(command) => Task::batch(vec![menu_task, self.command(command)]),
+                    None => menu_task,
+                }
+            }
+            Message::ToggleSubmenu(id) => {
+                if let Some(index) = self.expanded.iter().position(|expanded| *expanded == id) {
+                    self.expanded.truncate(index);
+                } else {
+                    self.expanded.push(id);
+                }
+                Task::none()
+            }
+            Message::MenuItemClicked(id, item_id) => {
+                let Some((address, path)) = self.selected.as_deref().and_then(|address| {
+                    self.item(address)
+                        .and_then(|item| item.menu.as_ref())
+                        .map(|menu| (address.to_owned(), menu.path.clone()))
+                }) else {
+                    return Task::none();
+                };
+
+                Task::batch(vec![
+                    outputs.close_menu(id),
+                    self.command(TrayCommand::MenuItemClicked(address, path, item_id)),
+                ])
+            }
+        }
+    }
+
+    pub fn view(&self, id: Id) -> Option<Element<Message>> {
+        let items = self
+            .service
+            .as_ref()?
+            .items
+            .iter()
+            .filter(|item| item.status != TrayItemStatus::Passive)
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            return None;
+        }
+
+        Some(
+            container(
+                Row::with_children(items.into_iter().map(|item| {
+                    let toggle_menu = item
+                        .menu
+                        .as_ref()
+                        .map(|_| Message::ToggleMenu(id, item.address.clone()));
+                    // menu only items open the menu with both buttons
+                    let activate = if item.item_is_menu {
+                        toggle_menu.clone()
+                    } else {
+                        Some(Message::Activate(item.address.clone()))
+                    };
+
+                    let mut area = mouse_area(container(item_icon(item)).padding([4, 2]).style(
+                        move |theme: &Theme| {
+                            container::Style {
+                                text_color: (item.status == TrayItemStatus::NeedsAttention)
+                                    .then(|| theme.palette().danger),
+                                ..Default::default()
+                            }
+                        },
+                    ));
+                    if let Some(message) = activate {
+                        area = area.on_press(message);
+                    }
+                    if let Some(message) = toggle_menu {
+                        area = area.on_right_press(message);
+                    }
+
+                    area.into()
+                }))
+                .spacing(8)
+                .align_y(Alignment::Center),
+            )
+            .padding([2, 8])
+            .style(header_pills)
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self, id: Id) -> Element<Message> {
+        let Some(item) = self
+            .selected
+            .as_deref()
+            .and_then(|address| self.item(address))
+        else {
+            return container(text("The application left the tray"))
+                .padding(16)
+                .into();
+        };
+
+        Column::new()
+            .push(text(item.name()).size(12))
+            .push(horizontal_rule(1))
+            .extend(
+                item.menu
+                    .as_ref()
+                    .map(|menu| menu_items(&menu.items, &self.expanded, id, 0))
+                    .unwrap_or_default(),
+            )
+            .spacing(4)
+            .padding(16)
+            .width(Length::Fixed(250.))
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        TrayService::subscribe().map(Message::Event)
+    }
+}
+
+fn item_icon(item: &TrayItem) -> Element<Message> {
+    match &item.icon {
+        Some(TrayIcon::File(path)) if path.extension().is_some_and(|ext| ext == "svg") => {
+            svg(svg::Handle::from_path(path))
+                .width(Length::Fixed(ICON_SIZE))
+                .height(Length::Fixed(ICON_SIZE))
+                .into()
+        }
+        Some(TrayIcon::File(path)) => image(image::Handle::from_path(path))
+            .width(Length::Fixed(ICON_SIZE))
+            .height(Length::Fixed(ICON_SIZE))
+            .into(),
+        Some(TrayIcon::Pixmap { handle, .. }) => image(handle.clone())
+            .width(Length::Fixed(ICON_SIZE))
+            .height(Length::Fixed(ICON_SIZE))
+            .into(),
+        // without an icon show the initial of the application
+        None => text(item.name().chars().next().unwrap_or('?').to_string())
+            .size(12)
+            .into(),
+    }
+}
+
+/// Submenus expand inline, `expanded` is the path of the open ones
+fn menu_items<'a>(
+    items: &'a [TrayMenuItem],
+    expanded: &[i32],
+    id: Id,
+    depth: u16,
+) -> Vec<Element<'a, Message>> {
+    let mut elements = Vec::new();
+
+    for item in items.iter().filter(|item| item.visible) {
+        if item.separator {
+            elements.push(horizontal_rule(1).into());
+            continue;
+        }
+
+        let is_expanded = expanded.contains(&item.id);
+        let content = row!()
+            .push_maybe(item.toggle.map(|checked| -> Element<'a, Message> {
+                if checked {
+                    icon(Icons::Check).into()
+                } else {
+                    Space::with_width(Length::Fixed(ICON_SIZE)).into()
+                }
+            }))
+            .push(text(item.label.clone()).width(Length::Fill))
+            .push_maybe((!item.children.is_empty()).then(|| {
+                icon(if is_expanded {
+                    Icons::DownArrow
+                } else {
+                    Icons::RightArrow
+                })
+            }))
+            .spacing(8)
+            .align_y(Alignment::Center);
+
+        let message = if item.children.is_empty() {
+            Message::MenuItemClicked(id, item.id)
+        } else {
+            Message::ToggleSubmenu(item.id)
+        };
+
+        elements.push(
+            button(content)
+                .padding(Padding::from([4, 8]).left(f32::from(8 + depth * 16)))
+                .width(Length::Fill)
+                .on_press_maybe(item.enabled.then_some(message))
+                .style(GhostButtonStyle.into_style())
+                .into(),
+        );
+
+        if is_expanded {
+            elements.extend(menu_items(&item.children, expanded, id, depth + 1));
+        }
+    }
+
+    elements
+}
@@ -0,0 +1,243 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, pick_list, row, text, text_input, Column},
+    window::Id,
+    Alignment, Element, Length,
+};
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::{
+    components::icons::{icon, Icons},
+    services::network::{EapMethod, NetworkSecurity, NewWifiConnection, Phase2Auth},
+    style::{ConfirmButtonStyle, OutlineButtonStyle, TextInputStyle},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityType {
+    Open,
+    #[default]
+    WpaPersonal,
+    Wpa3Personal,
+    Enterprise,
+}
+
+impl SecurityType {
+    const ALL: [SecurityType; 4] = [
+        SecurityType::Open,
+        SecurityType::WpaPersonal,
+        SecurityType::Wpa3Personal,
+        SecurityType::Enterprise,
+    ];
+}
+
+impl std::fmt::Display for SecurityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SecurityType::Open => "None",
+            SecurityType::WpaPersonal => "WPA/WPA2 Personal",
+            SecurityType::Wpa3Personal => "WPA3 Personal",
+            SecurityType::Enterprise => "WPA/WPA2/WPA3 Enterprise",
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct NetworkDialog {
+    ssid: String,
+    hidden: bool,
+    security: SecurityType,
+    eap: EapMethod,
+    phase2: Phase2Auth,
+    identity: String,
+    anonymous_identity: String,
+    ca_cert: String,
+    password: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SsidChanged(String),
+    SecurityChanged(SecurityType),
+    EapMethodChanged(EapMethod),
+    Phase2AuthChanged(Phase2Auth),
+    IdentityChanged(String),
+    AnonymousIdentityChanged(String),
+    CaCertChanged(String),
+    PasswordChanged(String),
+    DialogConfirmed(Id),
+    DialogCancelled(Id),
+}
+
+impl NetworkDialog {
+    pub fn hidden() -> Self {
+        Self {
+            hidden: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn enterprise(ssid: String) -> Self {
+        Self {
+            ssid,
+            security: SecurityType::Enterprise,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SsidChanged(ssid) => self.ssid = ssid,
+            Message::SecurityChanged(security) => self.security = security,
+            Message::EapMethodChanged(eap) => self.eap = eap,
+            Message::Phase2AuthChanged(phase2) => self.phase2 = phase2,
+            Message::IdentityChanged(identity) => self.identity = identity,
+            Message::AnonymousIdentityChanged(identity) => self.anonymous_identity = identity,
+            Message::CaCertChanged(ca_cert) => self.ca_cert = ca_cert,
+            Message::PasswordChanged(password) => self.password = password,
+            Message::DialogConfirmed(_) | Message::DialogCancelled(_) => {}
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.ssid.is_empty()
+            && match self.security {
+                SecurityType::Open => true,
+                SecurityType::WpaPersonal => self.password.len() >= 8,
+                SecurityType::Wpa3Personal => !self.password.is_empty(),
+                SecurityType::Enterprise => !self.identity.is_empty(),
+            }
+    }
+
+    pub fn connection(self, device_path: Option<OwnedObjectPath>) -> NewWifiConnection {
+        let security = match self.security {
+            SecurityType::Open => NetworkSecurity::Open,
+            SecurityType::WpaPersonal => NetworkSecurity::WpaPersonal {
+                password: self.password,
+            },
+            SecurityType::Wpa3Personal => NetworkSecurity::Wpa3Personal {
+                password: self.password,
+            },
+            SecurityType::Enterprise => NetworkSecurity::Enterprise {
+                eap: self.eap,
+                phase2: self.phase2,
+                identity: self.identity,
+                anonymous_identity: Some(self.anonymous_identity).filter(|i| !i.is_empty()),
+                ca_cert: Some(self.ca_cert).filter(|c| !c.is_empty()),
+                password: self.password,
+            },
+        };
+
+        NewWifiConnection {
+            ssid: self.ssid,
+            hidden: self.hidden,
+            security,
+            device_path,
+        }
+    }
+
+    pub fn view(&self, id: Id) -> Element<Message> {
+        let input = |placeholder: &str, value: &str| {
+            text_input(placeholder, value)
+                .size(16)
+                .padding([8, 16])
+                .style(TextInputStyle.into_style())
+        };
+        let confirm = self.is_valid().then_some(Message::DialogConfirmed(id));
+
+        let mut fields = Column::new().spacing(8);
+
+        if self.hidden {
+            fields = fields.push(
+                input("Network name", &self.ssid)
+                    .on_input(Message::SsidChanged)
+                    .on_submit_maybe(confirm.clone()),
+            );
+        }
+
+        fields = fields.push(
+            pick_list(
+                SecurityType::ALL,
+                Some(self.security),
+                Message::SecurityChanged,
+            )
+            .width(Length::Fill)
+            .padding([8, 16]),
+        );
+
+        if self.security == SecurityType::Enterprise {
+            fields = fields
+                .push(
+                    row!(
+                        pick_list(EapMethod::ALL, Some(self.eap), Message::EapMethodChanged)
+                            .width(Length::Fill)
+                            .padding([8, 16]),
+                        pick_list(
+                            Phase2Auth::ALL,
+                            Some(self.phase2),
+                            Message::Phase2AuthChanged
+                        )
+                        .width(Length::Fill)
+                        .padding([8, 16]),
+                    )
+                    .spacing(8),
+                )
+                .push(input("Identity", &self.identity).on_input(Message::IdentityChanged))
+                .push(
+                    input("Anonymous identity (optional)", &self.anonymous_identity)
+                        .on_input(Message::AnonymousIdentityChanged),
+                )
+                .push(
+                    input("CA certificate path (optional)", &self.ca_cert)
+                        .on_input(Message::CaCertChanged),
+                );
+        }
+
+        if self.security != SecurityType::Open {
+            fields = fields.push(
+                input("Password", &self.password)
+                    .secure(true)
+                    .on_input(Message::PasswordChanged)
+                    .on_submit_maybe(confirm.clone()),
+            );
+        }
+
+        column!(
+            row!(
+                icon(Icons::WifiLock4).size(32),
+                text(if self.hidden {
+                    "Connect to other network"
+                } else {
+                    "Authentication required"
+                })
+                .size(22),
+            )
+            .spacing(16)
+            .align_y(Alignment::Center),
+            text(if self.hidden {
+                "Insert the name and security of the hidden network".to_string()
+            } else {
+                format!("Insert credentials to connect to: {}", self.ssid)
+            }),
+            fields,
+            row!(
+                horizontal_space(),
+                button(text("Cancel").align_y(Vertical::Center))
+                    .padding([4, 32])
+                    .style(OutlineButtonStyle.into_style())
+                    .height(Length::Fixed(50.))
+                    .on_press(Message::DialogCancelled(id)),
+                button(text("Connect").align_y(Vertical::Center))
+                    .padding([4, 32])
+                    .height(Length::Fixed(50.))
+                    .style(ConfirmButtonStyle.into_style())
+                    .on_press_maybe(confirm)
+            )
+            .spacing(8)
+            .width(Length::Fill)
+        )
+        .spacing(16)
+        .padding(16)
+        .max_width(350.)
+        .into()
+    }
+}
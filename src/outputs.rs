@@ -102,6 +102,15 @@ impl Outputs {
         })
     }
 
+    /// Output of the first bar, surfaces like the notification popups follow the
+    /// configured outputs, the active one is used only by the fallback bar
+    pub fn first_output(&self) -> IcedOutput {
+        self.0
+            .iter()
+            .find_map(|(_, shell_info, wl_output)| shell_info.as_ref().and(wl_output.clone()))
+            .map_or(IcedOutput::Active, IcedOutput::Output)
+    }
+
     pub fn add<Message: 'static>(
         &mut self,
         request_outputs: &[String],
@@ -326,6 +335,24 @@ impl Outputs {
         }
     }
 
+    /// Open a menu on the output of the given surface, closing the menus of the other outputs
+    pub fn open_menu<Message: 'static>(&mut self, id: Id, menu_type: MenuType) -> Task<Message> {
+        let tasks = self
+            .0
+            .iter_mut()
+            .filter_map(|(_, shell_info, _)| shell_info.as_mut())
+            .map(|shell_info| {
+                if shell_info.id == id || shell_info.menu.id == id {
+                    shell_info.menu.open(menu_type)
+                } else {
+                    shell_info.menu.close()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Task::batch(tasks)
+    }
+
     pub fn close_menu<Message: 'static>(&mut self, id: Id) -> Task<Message> {
         if let Some((_, Some(shell_info), _)) = self.0.iter_mut().find(|(_, shell_info, _)| {
             shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
@@ -352,6 +379,44 @@ impl Outputs {
         }
     }
 
+    /// Open a menu without user interaction, reusing the output where it's already shown or
+    /// falling back to the first one, and give it the keyboard focus
+    pub fn request_menu<Message: 'static>(&mut self, menu_type: MenuType) -> Task<Message> {
+        let target = self
+            .0
+            .iter()
+            .filter_map(|(_, shell_info, _)| shell_info.as_ref())
+            .find(|shell_info| shell_info.menu.menu_type == Some(menu_type))
+            .or_else(|| {
+                self.0
+                    .iter()
+                    .find_map(|(_, shell_info, _)| shell_info.as_ref())
+            })
+            .map(|shell_info| shell_info.id);
+
+        if let Some(id) = target {
+            let tasks = self
+                .0
+                .iter_mut()
+                .filter_map(|(_, shell_info, _)| shell_info.as_mut())
+                .map(|shell_info| {
+                    if shell_info.id == id {
+                        Task::batch(vec![
+                            shell_info.menu.open(menu_type),
+                            shell_info.menu.request_keyboard(),
+                        ])
+                    } else {
+                        shell_info.menu.close()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            Task::batch(tasks)
+        } else {
+            Task::none()
+        }
+    }
+
     pub fn request_keyboard<Message: 'static>(&self, id: Id) -> Task<Message> {
         if let Some((_, Some(shell_info), _)) = self.0.iter().find(|(_, shell_info, _)| {
             shell_info.as_ref().map(|shell_info| shell_info.id) == Some(id)
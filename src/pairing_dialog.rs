@@ -0,0 +1,94 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, row, text, text_input, Row},
+    window::Id,
+    Alignment, Element, Length,
+};
+
+use crate::{
+    components::icons::{icon, Icons},
+    services::bluetooth::{PairingKind, PairingRequest},
+    style::{ConfirmButtonStyle, OutlineButtonStyle, TextInputStyle},
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    InputChanged(String),
+    DialogConfirmed(Id),
+    DialogCancelled(Id),
+}
+
+fn is_valid(kind: &PairingKind, input: &str) -> bool {
+    match kind {
+        PairingKind::PinCode => (1..=16).contains(&input.len()),
+        PairingKind::Passkey => input.parse::<u32>().is_ok_and(|passkey| passkey <= 999999),
+        _ => true,
+    }
+}
+
+pub fn view<'a>(id: Id, request: &'a PairingRequest, input: &'a str) -> Element<'a, Message> {
+    let description = match &request.kind {
+        PairingKind::Confirmation(passkey) => format!(
+            "Confirm that {:06} is shown on {}",
+            passkey, request.device_name
+        ),
+        PairingKind::Authorization => format!("Allow {} to pair?", request.device_name),
+        PairingKind::DisplayPasskey(passkey) => format!(
+            "Type {:06} on {} and press enter",
+            passkey, request.device_name
+        ),
+        PairingKind::DisplayPinCode(pincode) => {
+            format!(
+                "Type {} on {} and press enter",
+                pincode, request.device_name
+            )
+        }
+        PairingKind::PinCode => format!("Insert the PIN code of {}", request.device_name),
+        PairingKind::Passkey => format!("Insert the passkey shown on {}", request.device_name),
+    };
+    let confirm = is_valid(&request.kind, input).then_some(Message::DialogConfirmed(id));
+
+    column!(
+        row!(
+            icon(Icons::Bluetooth).size(32),
+            text("Bluetooth pairing").size(22),
+        )
+        .spacing(16)
+        .align_y(Alignment::Center),
+        text(description),
+    )
+    .push_maybe(
+        matches!(request.kind, PairingKind::PinCode | PairingKind::Passkey).then(|| {
+            text_input("", input)
+                .size(16)
+                .padding([8, 16])
+                .style(TextInputStyle.into_style())
+                .on_input(Message::InputChanged)
+                .on_submit_maybe(confirm.clone())
+        }),
+    )
+    .push(
+        Row::new()
+            .push(horizontal_space())
+            .push(
+                button(text("Cancel").align_y(Vertical::Center))
+                    .padding([4, 32])
+                    .style(OutlineButtonStyle.into_style())
+                    .height(Length::Fixed(50.))
+                    .on_press(Message::DialogCancelled(id)),
+            )
+            .push_maybe(request.needs_reply().then(|| {
+                button(text("Confirm").align_y(Vertical::Center))
+                    .padding([4, 32])
+                    .height(Length::Fixed(50.))
+                    .style(ConfirmButtonStyle.into_style())
+                    .on_press_maybe(confirm)
+            }))
+            .spacing(8)
+            .width(Length::Fill),
+    )
+    .spacing(16)
+    .padding(16)
+    .max_width(350.)
+    .into()
+}
@@ -0,0 +1,275 @@
+use super::{
+    dbus::{AgentManagerProxy, DeviceProxy},
+    BluetoothEvent, PairingKind, PairingRequest,
+};
+use iced::futures::{
+    channel::{mpsc, oneshot},
+    Stream,
+};
+use log::{debug, info, warn};
+use std::{collections::HashMap, sync::Mutex};
+use zbus::{interface, zvariant::OwnedObjectPath, DBusError};
+
+const AGENT_PATH: &str = "/org/ashell/bluetooth/agent";
+// The agent can both show and insert codes, this allows every pairing method
+const AGENT_CAPABILITY: &str = "KeyboardDisplay";
+
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "org.bluez.Error")]
+pub enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Canceled(String),
+}
+
+type PendingRequests = HashMap<OwnedObjectPath, oneshot::Sender<Option<String>>>;
+
+pub struct BluetoothAgent {
+    events: Mutex<mpsc::UnboundedSender<BluetoothEvent>>,
+    pending: Mutex<PendingRequests>,
+}
+
+impl BluetoothAgent {
+    /// Serve the agent on the connection and register it as the default BlueZ agent
+    /// the first time, returns the stream of pairing requests for the current listener
+    pub async fn listen(
+        conn: &zbus::Connection,
+    ) -> anyhow::Result<impl Stream<Item = BluetoothEvent>> {
+        let (tx, rx) = mpsc::unbounded();
+        let object_server = conn.object_server();
+
+        if let Ok(agent) = object_server
+            .interface::<_, BluetoothAgent>(AGENT_PATH)
+            .await
+        {
+            *agent.get().await.events.lock().unwrap() = tx;
+        } else {
+            object_server
+                .at(
+                    AGENT_PATH,
+                    BluetoothAgent {
+                        events: Mutex::new(tx),
+                        pending: Mutex::new(HashMap::new()),
+                    },
+                )
+                .await?;
+
+            let agent_manager = AgentManagerProxy::new(conn).await?;
+            let path = OwnedObjectPath::try_from(AGENT_PATH)?;
+            agent_manager
+                .register_agent(&path, AGENT_CAPABILITY)
+                .await?;
+            agent_manager.request_default_agent(&path).await?;
+
+            info!("Bluetooth pairing agent registered");
+        }
+
+        Ok(rx)
+    }
+
+    pub async fn reply(
+        conn: &zbus::Connection,
+        device: &OwnedObjectPath,
+        reply: Option<String>,
+    ) -> anyhow::Result<()> {
+        let agent = conn
+            .object_server()
+            .interface::<_, BluetoothAgent>(AGENT_PATH)
+            .await?;
+
+        let sender = agent.get().await.pending.lock().unwrap().remove(device);
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(reply);
+            }
+            None => {
+                warn!("No pending pairing request for {}", device.as_str());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn notify(&self, request: PairingRequest) {
+        let _ = self
+            .events
+            .lock()
+            .unwrap()
+            .unbounded_send(BluetoothEvent::PairingRequested(request));
+    }
+
+    /// Ask the user and wait for the answer
+    async fn ask(
+        &self,
+        conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        kind: PairingKind,
+    ) -> Result<String, AgentError> {
+        let device_name = device_name(conn, &device).await;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(device.clone(), tx);
+
+        if self
+            .events
+            .lock()
+            .unwrap()
+            .unbounded_send(BluetoothEvent::PairingRequested(PairingRequest {
+                device: device.clone(),
+                device_name,
+                kind,
+            }))
+            .is_err()
+        {
+            self.pending.lock().unwrap().remove(&device);
+
+            return Err(AgentError::Rejected(
+                "No listener for pairing requests".to_string(),
+            ));
+        }
+
+        match rx.await {
+            Ok(Some(reply)) => Ok(reply),
+            Ok(None) => Err(AgentError::Rejected("Pairing rejected".to_string())),
+            Err(_) => Err(AgentError::Canceled("Pairing canceled".to_string())),
+        }
+    }
+}
+
+async fn device_name(conn: &zbus::Connection, device: &OwnedObjectPath) -> String {
+    let alias = match DeviceProxy::builder(conn).path(device.clone()) {
+        Ok(builder) => match builder.build().await {
+            Ok(proxy) => proxy.alias().await.ok(),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    };
+
+    alias.unwrap_or_else(|| device.to_string())
+}
+
+#[interface(name = "org.bluez.Agent1")]
+impl BluetoothAgent {
+    async fn release(&self) {
+        debug!("Bluetooth agent released");
+    }
+
+    async fn request_pin_code(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> Result<String, AgentError> {
+        debug!("Pin code requested for {}", device.as_str());
+
+        self.ask(conn, device, PairingKind::PinCode).await
+    }
+
+    async fn display_pin_code(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        pincode: String,
+    ) {
+        debug!("Display pin code for {}", device.as_str());
+
+        self.notify(PairingRequest {
+            device_name: device_name(conn, &device).await,
+            device,
+            kind: PairingKind::DisplayPinCode(pincode),
+        });
+    }
+
+    async fn request_passkey(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> Result<u32, AgentError> {
+        debug!("Passkey requested for {}", device.as_str());
+
+        let passkey = self.ask(conn, device, PairingKind::Passkey).await?;
+
+        passkey
+            .trim()
+            .parse()
+            .map_err(|_| AgentError::Rejected("Invalid passkey".to_string()))
+    }
+
+    async fn display_passkey(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        passkey: u32,
+        _entered: u16,
+    ) {
+        debug!("Display passkey for {}", device.as_str());
+
+        self.notify(PairingRequest {
+            device_name: device_name(conn, &device).await,
+            device,
+            kind: PairingKind::DisplayPasskey(passkey),
+        });
+    }
+
+    async fn request_confirmation(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        passkey: u32,
+    ) -> Result<(), AgentError> {
+        debug!("Confirmation requested for {}", device.as_str());
+
+        self.ask(conn, device, PairingKind::Confirmation(passkey))
+            .await
+            .map(|_| ())
+    }
+
+    async fn request_authorization(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> Result<(), AgentError> {
+        debug!("Authorization requested for {}", device.as_str());
+
+        self.ask(conn, device, PairingKind::Authorization)
+            .await
+            .map(|_| ())
+    }
+
+    // Services of paired devices are always allowed, the user already accepted the device
+    async fn authorize_service(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        uuid: String,
+    ) -> Result<(), AgentError> {
+        debug!("Service {} authorization for {}", uuid, device.as_str());
+
+        let paired = DeviceProxy::builder(conn)
+            .path(device)?
+            .build()
+            .await?
+            .paired()
+            .await?;
+
+        if paired {
+            Ok(())
+        } else {
+            Err(AgentError::Rejected("Device not paired".to_string()))
+        }
+    }
+
+    async fn cancel(&self) {
+        debug!("Pairing request canceled");
+
+        for (_, sender) in self.pending.lock().unwrap().drain() {
+            let _ = sender.send(None);
+        }
+
+        let _ = self
+            .events
+            .lock()
+            .unwrap()
+            .unbounded_send(BluetoothEvent::PairingCanceled);
+    }
+}
@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Deref};
 
+use iced::futures::{stream::select_all, Stream, StreamExt};
 use zbus::{
     proxy,
-    zvariant::{OwnedObjectPath, OwnedValue},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+    MatchRule, MessageStream,
 };
 
 use super::{BluetoothDevice, BluetoothState};
@@ -59,48 +61,141 @@ impl<'a> BluetoothDbus<'a> {
         }
     }
 
+    pub async fn discovering(&self) -> zbus::Result<bool> {
+        if let Some(adapter) = &self.adapter {
+            adapter.discovering().await
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub async fn set_discovering(&self, value: bool) -> zbus::Result<()> {
+        if let Some(adapter) = &self.adapter {
+            if value {
+                adapter.start_discovery().await?;
+            } else {
+                adapter.stop_discovery().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn remove_device(&self, device: &ObjectPath<'_>) -> zbus::Result<()> {
+        if let Some(adapter) = &self.adapter {
+            adapter.remove_device(device).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn device(&self, device: OwnedObjectPath) -> zbus::Result<DeviceProxy<'a>> {
+        DeviceProxy::builder(self.bluez.inner().connection())
+            .path(device)?
+            .build()
+            .await
+    }
+
+    /// Paired, connected and discovered devices of the adapter, discovered devices
+    /// without a name are skipped
     pub async fn devices(&self) -> anyhow::Result<Vec<BluetoothDevice>> {
-        let devices_proxy = self
+        let Some(adapter) = &self.adapter else {
+            return Ok(Vec::new());
+        };
+        let adapter_path = adapter.inner().path().as_str();
+
+        let mut devices = self
             .bluez
             .get_managed_objects()
             .await?
             .into_iter()
-            .filter_map(|(key, item)| {
-                if item.contains_key("org.bluez.Device1") {
-                    Some(key)
-                } else {
-                    None
+            .filter_map(|(path, interfaces)| {
+                let device = interfaces.get("org.bluez.Device1")?;
+                if get_path(device, "Adapter").as_deref() != Some(adapter_path) {
+                    return None;
                 }
+
+                let paired = get_bool(device, "Paired");
+                let connected = get_bool(device, "Connected");
+                let name = get_str(device, "Name");
+                if !paired && !connected && name.is_none() {
+                    return None;
+                }
+
+                Some(BluetoothDevice {
+                    name: get_str(device, "Alias").or(name).unwrap_or_default(),
+                    address: get_str(device, "Address").unwrap_or_default(),
+                    icon: get_str(device, "Icon"),
+                    battery: interfaces
+                        .get("org.bluez.Battery1")
+                        .and_then(|battery| battery.get("Percentage"))
+                        .and_then(|percentage| u8::try_from(percentage).ok()),
+                    paired,
+                    trusted: get_bool(device, "Trusted"),
+                    connected,
+                    path,
+                })
             })
             .collect::<Vec<_>>();
 
-        let mut devices = Vec::new();
-        for device_path in devices_proxy {
-            let device = DeviceProxy::builder(self.bluez.inner().connection())
-                .path(device_path.clone())?
-                .build()
-                .await?;
-
-            let name = device.name().await?;
-            let connected = device.connected().await?;
-
-            if connected {
-                let battery = BatteryProxy::builder(self.bluez.inner().connection())
-                    .path(&device_path)?
-                    .build()
-                    .await?;
-                let battery = battery.percentage().await?;
-
-                devices.push(BluetoothDevice {
-                    name,
-                    battery: Some(battery),
-                    path: device_path,
-                });
-            }
-        }
+        devices.sort_by(|a, b| {
+            b.connected
+                .cmp(&a.connected)
+                .then(b.paired.cmp(&a.paired))
+                .then(a.name.cmp(&b.name))
+        });
 
         Ok(devices)
     }
+
+    /// Emits every time a property of the adapter, of a device or of a device battery changes
+    pub async fn properties_changed(&self) -> anyhow::Result<impl Stream<Item = ()>> {
+        let mut changes = Vec::new();
+        for interface in [
+            "org.bluez.Adapter1",
+            "org.bluez.Device1",
+            "org.bluez.Battery1",
+        ] {
+            let rule = MatchRule::builder()
+                .msg_type(zbus::message::Type::Signal)
+                .sender("org.bluez")?
+                .interface("org.freedesktop.DBus.Properties")?
+                .member("PropertiesChanged")?
+                .arg(0, interface)?
+                .build();
+
+            changes.push(
+                MessageStream::for_match_rule(rule, self.bluez.inner().connection(), None)
+                    .await?
+                    .map(|_| ()),
+            );
+        }
+
+        Ok(select_all(changes))
+    }
+}
+
+fn get_str(data: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    data.get(key).and_then(|v| match v.deref() {
+        Value::Str(v) => Some(v.to_string()),
+        _ => None,
+    })
+}
+
+fn get_bool(data: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    data.get(key)
+        .and_then(|v| match v.deref() {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn get_path(data: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    data.get(key).and_then(|v| match v.deref() {
+        Value::ObjectPath(v) => Some(v.to_string()),
+        _ => None,
+    })
 }
 
 #[proxy(
@@ -124,24 +219,52 @@ pub trait BluezObjectManager {
     interface = "org.bluez.Adapter1"
 )]
 pub trait Adapter {
+    fn start_discovery(&self) -> zbus::Result<()>;
+
+    fn stop_discovery(&self) -> zbus::Result<()>;
+
+    fn remove_device(&self, device: &ObjectPath<'_>) -> zbus::Result<()>;
+
     #[zbus(property)]
     fn powered(&self) -> zbus::Result<bool>;
 
     #[zbus(property)]
     fn set_powered(&self, value: bool) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn discovering(&self) -> zbus::Result<bool>;
 }
 
 #[proxy(default_service = "org.bluez", interface = "org.bluez.Device1")]
-trait Device {
+pub trait Device {
+    fn connect(&self) -> zbus::Result<()>;
+
+    fn disconnect(&self) -> zbus::Result<()>;
+
+    fn pair(&self) -> zbus::Result<()>;
+
+    fn cancel_pairing(&self) -> zbus::Result<()>;
+
     #[zbus(property)]
-    fn name(&self) -> zbus::Result<String>;
+    fn alias(&self) -> zbus::Result<String>;
 
     #[zbus(property)]
-    fn connected(&self) -> zbus::Result<bool>;
-}
+    fn paired(&self) -> zbus::Result<bool>;
 
-#[proxy(default_service = "org.bluez", interface = "org.bluez.Battery1")]
-pub trait Battery {
     #[zbus(property)]
-    fn percentage(&self) -> zbus::Result<u8>;
+    fn trusted(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn set_trusted(&self, value: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    default_service = "org.bluez",
+    default_path = "/org/bluez",
+    interface = "org.bluez.AgentManager1"
+)]
+pub trait AgentManager {
+    fn register_agent(&self, agent: &ObjectPath<'_>, capability: &str) -> zbus::Result<()>;
+
+    fn request_default_agent(&self, agent: &ObjectPath<'_>) -> zbus::Result<()>;
 }
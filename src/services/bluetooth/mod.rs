@@ -1,20 +1,23 @@
-use super::{ReadOnlyService, Service, ServiceEvent};
-use dbus::{BatteryProxy, BluetoothDbus};
+use super::{
+    rfkill::{self, RfkillType},
+    ReadOnlyService, Service, ServiceEvent,
+};
+use agent::BluetoothAgent;
+use dbus::BluetoothDbus;
 use iced::{
     futures::{
         channel::mpsc::Sender,
-        stream::{pending, select_all},
+        stream::{pending, select},
         stream_select, SinkExt, Stream, StreamExt,
     },
     stream::channel,
     Subscription, Task,
 };
-use inotify::{Inotify, WatchMask};
 use log::{debug, error, info};
 use std::{any::TypeId, ops::Deref};
-use tokio::process::Command;
 use zbus::zvariant::OwnedObjectPath;
 
+mod agent;
 mod dbus;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -27,16 +30,56 @@ pub enum BluetoothState {
 #[derive(Debug, Clone)]
 pub struct BluetoothDevice {
     pub name: String,
+    pub address: String,
+    pub icon: Option<String>,
     pub battery: Option<u8>,
+    pub paired: bool,
+    pub trusted: bool,
+    pub connected: bool,
     pub path: OwnedObjectPath,
 }
 
 #[derive(Debug, Clone)]
 pub struct BluetoothData {
     pub state: BluetoothState,
+    pub discovering: bool,
     pub devices: Vec<BluetoothDevice>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairingKind {
+    Confirmation(u32),
+    Authorization,
+    DisplayPasskey(u32),
+    DisplayPinCode(String),
+    PinCode,
+    Passkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct PairingRequest {
+    pub device: OwnedObjectPath,
+    pub device_name: String,
+    pub kind: PairingKind,
+}
+
+impl PairingRequest {
+    /// Display requests only show a code, the pairing goes on without an answer
+    pub fn needs_reply(&self) -> bool {
+        !matches!(
+            self.kind,
+            PairingKind::DisplayPasskey(_) | PairingKind::DisplayPinCode(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BluetoothEvent {
+    Data(BluetoothData),
+    PairingRequested(PairingRequest),
+    PairingCanceled,
+}
+
 #[derive(Debug, Clone)]
 pub struct BluetoothService {
     conn: zbus::Connection,
@@ -54,6 +97,15 @@ impl Deref for BluetoothService {
 #[derive(Debug, Clone)]
 pub enum BluetoothCommand {
     Toggle,
+    StartDiscovery,
+    StopDiscovery,
+    Pair(OwnedObjectPath),
+    CancelPairing(OwnedObjectPath),
+    Connect(OwnedObjectPath),
+    Disconnect(OwnedObjectPath),
+    SetTrusted(OwnedObjectPath, bool),
+    Remove(OwnedObjectPath),
+    ReplyPairing(OwnedObjectPath, Option<String>),
 }
 
 enum State {
@@ -67,16 +119,23 @@ impl BluetoothService {
         let bluetooth = BluetoothDbus::new(conn).await?;
 
         let state = bluetooth.state().await?;
-        let rfkill_soft_block = BluetoothService::check_rfkill_soft_block().await?;
+        let rfkill_blocked = rfkill::state()
+            .map(|rfkill| rfkill.blocked(RfkillType::Bluetooth))
+            .unwrap_or_default();
 
         let state = match state {
             BluetoothState::Unavailable => BluetoothState::Unavailable,
-            BluetoothState::Active if rfkill_soft_block => BluetoothState::Inactive,
+            BluetoothState::Active if rfkill_blocked => BluetoothState::Inactive,
             state => state,
         };
+        let discovering = bluetooth.discovering().await.unwrap_or_default();
         let devices = bluetooth.devices().await?;
 
-        Ok(BluetoothData { state, devices })
+        Ok(BluetoothData {
+            state,
+            discovering,
+            devices,
+        })
     }
 
     async fn events(conn: &zbus::Connection) -> anyhow::Result<impl Stream<Item = ()>> {
@@ -96,21 +155,18 @@ impl BluetoothService {
         )
         .boxed();
 
-        let combined = if let Some(adapter) = bluetooth.adapter.as_ref() {
-            let powered = adapter.receive_powered_changed().await.map(|_| {});
-            let rfkill = BluetoothService::listen_rfkill_soft_block_changes().await?;
-            let devices = bluetooth.devices().await?;
-
-            let mut batteries = Vec::with_capacity(devices.len());
-            for device in devices {
-                let battery = BatteryProxy::builder(bluetooth.bluez.inner().connection())
-                    .path(device.path)?
-                    .build()
-                    .await?;
-                batteries.push(battery.receive_percentage_changed().await.map(|_| {}));
-            }
+        let combined = if bluetooth.adapter.is_some() {
+            let properties = bluetooth.properties_changed().await?;
+            let rfkill = match rfkill::listen() {
+                Ok(rfkill) => rfkill.map(|_| {}).boxed(),
+                Err(err) => {
+                    error!("Failed to listen for rfkill events: {}", err);
 
-            stream_select!(interface_changed, powered, rfkill, select_all(batteries)).boxed()
+                    pending().boxed()
+                }
+            };
+
+            stream_select!(interface_changed, properties, rfkill).boxed()
         } else {
             interface_changed
         };
@@ -153,12 +209,35 @@ impl BluetoothService {
             State::Active(conn) => {
                 info!("Listening for bluetooth events");
 
+                let pairing_requests = match BluetoothAgent::listen(&conn).await {
+                    Ok(requests) => requests.boxed(),
+                    Err(err) => {
+                        error!("Failed to register bluetooth pairing agent: {}", err);
+
+                        pending().boxed()
+                    }
+                };
+
                 match BluetoothService::events(&conn).await {
-                    Ok(mut events) => {
-                        while events.next().await.is_some() {
-                            if let Ok(data) = BluetoothService::initialize_data(&conn).await {
-                                let _ = output.send(ServiceEvent::Update(data)).await;
-                            }
+                    Ok(events) => {
+                        let changes = events
+                            .filter_map({
+                                let conn = conn.clone();
+                                move |_| {
+                                    let conn = conn.clone();
+                                    async move {
+                                        BluetoothService::initialize_data(&conn)
+                                            .await
+                                            .ok()
+                                            .map(BluetoothEvent::Data)
+                                    }
+                                }
+                            })
+                            .boxed();
+
+                        let mut events = select(changes, pairing_requests);
+                        while let Some(event) = events.next().await {
+                            let _ = output.send(ServiceEvent::Update(event)).await;
                         }
 
                         State::Active(conn)
@@ -178,42 +257,64 @@ impl BluetoothService {
         }
     }
 
-    pub async fn check_rfkill_soft_block() -> anyhow::Result<bool> {
-        let output = Command::new("/usr/sbin/rfkill")
-            .arg("list")
-            .arg("bluetooth")
-            .output()
-            .await?;
-
-        let output = String::from_utf8(output.stdout)?;
-
-        Ok(output.contains("Soft blocked: yes"))
-    }
+    async fn toggle_power(conn: &zbus::Connection, power: bool) -> anyhow::Result<()> {
+        let bluetooth = BluetoothDbus::new(conn).await?;
 
-    pub async fn listen_rfkill_soft_block_changes() -> anyhow::Result<impl Stream<Item = ()>> {
-        let inotify = Inotify::init()?;
+        // A soft blocked adapter can't be powered on
+        if power && rfkill::state()?.soft_blocked(RfkillType::Bluetooth) {
+            rfkill::set_soft_block(RfkillType::Bluetooth, false)?;
+        }
 
-        inotify.watches().add("/dev/rfkill", WatchMask::MODIFY)?;
+        bluetooth.set_powered(power).await?;
 
-        let buffer = [0; 512];
-        Ok(inotify.into_event_stream(buffer)?.map(|_| {}))
+        Ok(())
     }
 
-    async fn toggle_power(conn: &zbus::Connection, power: bool) -> anyhow::Result<()> {
+    async fn device_command(
+        conn: &zbus::Connection,
+        command: BluetoothCommand,
+    ) -> anyhow::Result<()> {
         let bluetooth = BluetoothDbus::new(conn).await?;
 
-        bluetooth.set_powered(power).await?;
+        match command {
+            BluetoothCommand::StartDiscovery => bluetooth.set_discovering(true).await?,
+            BluetoothCommand::StopDiscovery => bluetooth.set_discovering(false).await?,
+            BluetoothCommand::Pair(device) => {
+                let device = bluetooth.device(device).await?;
+
+                device.pair().await?;
+                device.set_trusted(true).await?;
+                device.connect().await?;
+            }
+            BluetoothCommand::CancelPairing(device) => {
+                bluetooth.device(device).await?.cancel_pairing().await?
+            }
+            BluetoothCommand::Connect(device) => bluetooth.device(device).await?.connect().await?,
+            BluetoothCommand::Disconnect(device) => {
+                bluetooth.device(device).await?.disconnect().await?
+            }
+            BluetoothCommand::SetTrusted(device, trusted) => {
+                bluetooth.device(device).await?.set_trusted(trusted).await?
+            }
+            BluetoothCommand::Remove(device) => bluetooth.remove_device(&device).await?,
+            BluetoothCommand::ReplyPairing(device, reply) => {
+                BluetoothAgent::reply(conn, &device, reply).await?
+            }
+            BluetoothCommand::Toggle => {}
+        }
 
         Ok(())
     }
 }
 
 impl ReadOnlyService for BluetoothService {
-    type UpdateEvent = BluetoothData;
+    type UpdateEvent = BluetoothEvent;
     type Error = ();
 
     fn update(&mut self, event: Self::UpdateEvent) {
-        self.data = event;
+        if let BluetoothEvent::Data(data) = event {
+            self.data = data;
+        }
     }
 
     fn subscribe() -> Subscription<ServiceEvent<Self>> {
@@ -259,12 +360,30 @@ impl Service for BluetoothService {
                                 }
                             }
 
-                            data
+                            BluetoothEvent::Data(data)
                         },
                         ServiceEvent::Update,
                     )
                 }
             }
+            command => {
+                let conn = self.conn.clone();
+                let data = self.data.clone();
+
+                Task::perform(
+                    async move {
+                        debug!("Bluetooth command: {:?}", command);
+                        if let Err(err) = BluetoothService::device_command(&conn, command).await {
+                            error!("Bluetooth command failed: {}", err);
+                        }
+
+                        BluetoothService::initialize_data(&conn)
+                            .await
+                            .unwrap_or(data)
+                    },
+                    |data| ServiceEvent::Update(BluetoothEvent::Data(data)),
+                )
+            }
         }
     }
 }
@@ -0,0 +1,147 @@
+use log::debug;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::Duration,
+};
+
+const DRM_FOLDER: &str = "/sys/class/drm";
+const I2C_SLAVE: libc::Ioctl = 0x0703;
+const DDC_ADDRESS: libc::c_ulong = 0x37;
+// The host writes from 0x51, checksums include the destination address 0x6E
+// for requests and the virtual host address 0x50 for replies
+const HOST_ADDRESS: u8 = 0x51;
+const DISPLAY_ADDRESS: u8 = 0x6E;
+const REPLY_ADDRESS: u8 = 0x50;
+const GET_VCP: u8 = 0x01;
+const GET_VCP_REPLY: u8 = 0x02;
+const SET_VCP: u8 = 0x03;
+const BRIGHTNESS_VCP: u8 = 0x10;
+// Minimum delay the display needs to process a request
+const DDC_DELAY: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone)]
+pub struct DdcMonitor {
+    pub name: String,
+    pub bus: PathBuf,
+}
+
+fn checksum(initial: u8, data: &[u8]) -> u8 {
+    data.iter().fold(initial, |acc, b| acc ^ b)
+}
+
+fn open(bus: &Path) -> anyhow::Result<File> {
+    let file = OpenOptions::new().read(true).write(true).open(bus)?;
+
+    if unsafe { libc::ioctl(file.as_raw_fd(), I2C_SLAVE, DDC_ADDRESS) } < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(file)
+}
+
+fn write_request(file: &mut File, payload: &[u8]) -> anyhow::Result<()> {
+    let mut request = vec![HOST_ADDRESS, 0x80 | payload.len() as u8];
+    request.extend_from_slice(payload);
+    request.push(checksum(DISPLAY_ADDRESS, &request));
+
+    file.write_all(&request)?;
+    sleep(DDC_DELAY);
+
+    Ok(())
+}
+
+/// Current and max brightness of the monitor, it blocks while waiting for the display
+pub fn get_brightness(bus: &Path) -> anyhow::Result<(u32, u32)> {
+    let mut file = open(bus)?;
+    write_request(&mut file, &[GET_VCP, BRIGHTNESS_VCP])?;
+
+    let mut reply = [0; 11];
+    file.read_exact(&mut reply)?;
+
+    if reply[0] != DISPLAY_ADDRESS
+        || reply[2] != GET_VCP_REPLY
+        || reply[4] != BRIGHTNESS_VCP
+        || checksum(REPLY_ADDRESS, &reply[..10]) != reply[10]
+    {
+        anyhow::bail!("Invalid DDC reply from {}", bus.display());
+    }
+    if reply[3] != 0 {
+        anyhow::bail!("Brightness not supported by {}", bus.display());
+    }
+
+    let max = u16::from_be_bytes([reply[6], reply[7]]) as u32;
+    let current = u16::from_be_bytes([reply[8], reply[9]]) as u32;
+
+    Ok((current, max))
+}
+
+/// Set the monitor brightness, it blocks while waiting for the display
+pub fn set_brightness(bus: &Path, value: u32) -> anyhow::Result<()> {
+    let mut file = open(bus)?;
+    let [high, low] = (value.min(u16::MAX as u32) as u16).to_be_bytes();
+
+    write_request(&mut file, &[SET_VCP, BRIGHTNESS_VCP, high, low])
+}
+
+/// I2C buses of the connected external monitors, only the buses linked to a DRM connector
+/// are used so other devices on the I2C buses are never touched
+pub fn monitors() -> Vec<DdcMonitor> {
+    let Ok(connectors) = fs::read_dir(DRM_FOLDER) else {
+        return Vec::new();
+    };
+
+    let mut monitors = connectors
+        .flatten()
+        .filter_map(|connector| {
+            let path = connector.path();
+            // card1-DP-1 -> DP-1
+            let name = connector
+                .file_name()
+                .to_string_lossy()
+                .split_once('-')?
+                .1
+                .to_string();
+
+            // internal panels use the backlight interface
+            if ["eDP", "LVDS", "DSI"]
+                .iter()
+                .any(|internal| name.starts_with(internal))
+            {
+                return None;
+            }
+
+            let status = fs::read_to_string(path.join("status")).ok()?;
+            if status.trim() != "connected" {
+                return None;
+            }
+
+            // HDMI and DVI connectors link the DDC bus, DP connectors own their AUX bus
+            let bus = fs::read_link(path.join("ddc"))
+                .ok()
+                .and_then(|ddc| ddc.file_name().map(|bus| bus.to_os_string()))
+                .or_else(|| {
+                    fs::read_dir(&path).ok()?.flatten().find_map(|entry| {
+                        let file_name = entry.file_name();
+                        file_name
+                            .to_string_lossy()
+                            .starts_with("i2c-")
+                            .then_some(file_name)
+                    })
+                })?;
+
+            Some(DdcMonitor {
+                name,
+                bus: Path::new("/dev").join(bus),
+            })
+        })
+        .collect::<Vec<_>>();
+    monitors.sort_by(|a, b| a.name.cmp(&b.name));
+
+    debug!("DDC monitors: {:?}", monitors);
+
+    monitors
+}
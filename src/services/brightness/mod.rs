@@ -0,0 +1,384 @@
+use super::{ReadOnlyService, Service, ServiceEvent};
+use ddc::DdcMonitor;
+use iced::{
+    futures::{
+        channel::mpsc::Sender,
+        stream::{pending, select_all},
+        SinkExt, Stream, StreamExt,
+    },
+    stream::channel,
+    Subscription, Task,
+};
+use inotify::{Inotify, WatchMask};
+use log::{debug, error, info, warn};
+use std::{
+    any::TypeId,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use zbus::proxy;
+
+mod ddc;
+
+const DEVICES_FOLDER: &str = "/sys/class/backlight";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrightnessDeviceKind {
+    Backlight,
+    /// External monitor controlled through DDC/CI on the given I2C bus
+    Ddc(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct BrightnessDevice {
+    pub name: String,
+    pub kind: BrightnessDeviceKind,
+    pub current: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BrightnessData {
+    pub devices: Vec<BrightnessDevice>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BrightnessService {
+    data: BrightnessData,
+    conn: zbus::Connection,
+}
+
+impl Deref for BrightnessService {
+    type Target = BrightnessData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl BrightnessService {
+    async fn get_max_brightness(device_path: &Path) -> anyhow::Result<u32> {
+        let max_brightness = fs::read_to_string(device_path.join("max_brightness"))?;
+        let max_brightness = max_brightness.trim().parse::<u32>()?;
+
+        Ok(max_brightness)
+    }
+
+    async fn get_actual_brightness(device_path: &Path) -> anyhow::Result<u32> {
+        let actual_brightness = fs::read_to_string(device_path.join("actual_brightness"))?;
+        let actual_brightness = actual_brightness.trim().parse::<u32>()?;
+
+        Ok(actual_brightness)
+    }
+
+    async fn backlight_device(device_path: &Path) -> anyhow::Result<BrightnessDevice> {
+        let name = device_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let max_brightness = Self::get_max_brightness(device_path).await?;
+        let actual_brightness = Self::get_actual_brightness(device_path).await?;
+
+        debug!(
+            "{} max brightness: {}, current brightness: {}",
+            name, max_brightness, actual_brightness
+        );
+
+        Ok(BrightnessDevice {
+            name,
+            kind: BrightnessDeviceKind::Backlight,
+            current: actual_brightness,
+            max: max_brightness,
+        })
+    }
+
+    async fn ddc_device(monitor: DdcMonitor) -> anyhow::Result<BrightnessDevice> {
+        let bus = monitor.bus.clone();
+        let (current, max) =
+            tokio::task::spawn_blocking(move || ddc::get_brightness(&bus)).await??;
+
+        debug!(
+            "{} max brightness: {}, current brightness: {}",
+            monitor.name, max, current
+        );
+
+        Ok(BrightnessDevice {
+            name: monitor.name,
+            kind: BrightnessDeviceKind::Ddc(monitor.bus),
+            current,
+            max,
+        })
+    }
+
+    fn backlight_paths() -> Vec<PathBuf> {
+        let mut paths = fs::read_dir(DEVICES_FOLDER)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        paths.sort();
+
+        paths
+    }
+
+    /// Only the monitors listed in `ddc_names` are probed, all of them when it is empty
+    async fn initialize_data(
+        backlight_paths: &[PathBuf],
+        ddc_names: &[String],
+    ) -> anyhow::Result<BrightnessData> {
+        let mut devices = Vec::new();
+
+        for device_path in backlight_paths {
+            match Self::backlight_device(device_path).await {
+                Ok(device) => devices.push(device),
+                Err(err) => warn!(
+                    "Failed to read backlight {}: {}",
+                    device_path.display(),
+                    err
+                ),
+            }
+        }
+
+        let monitors = tokio::task::spawn_blocking(ddc::monitors).await?;
+        for monitor in monitors {
+            if !ddc_names.is_empty() && !ddc_names.contains(&monitor.name) {
+                debug!("Skipping DDC/CI monitor {}", monitor.name);
+                continue;
+            }
+
+            let name = monitor.name.clone();
+            match Self::ddc_device(monitor).await {
+                Ok(device) => devices.push(device),
+                Err(err) => debug!("No DDC/CI brightness for {}: {}", name, err),
+            }
+        }
+
+        if devices.is_empty() {
+            warn!("No backlight devices found");
+            Err(anyhow::anyhow!("No backlight devices found"))
+        } else {
+            Ok(BrightnessData { devices })
+        }
+    }
+
+    async fn events(
+        backlight_paths: &[PathBuf],
+    ) -> anyhow::Result<impl Stream<Item = BrightnessEvent>> {
+        let mut events = Vec::with_capacity(backlight_paths.len());
+
+        for device_path in backlight_paths {
+            let actual_brightness_file = device_path.join("actual_brightness");
+            let inotify = Inotify::init()?;
+
+            inotify
+                .watches()
+                .add(&actual_brightness_file, WatchMask::MODIFY)?;
+
+            let buffer = [0; 512];
+            let name = device_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            events.push(
+                inotify
+                    .into_event_stream(buffer)?
+                    .filter_map({
+                        let device_path = device_path.to_owned();
+                        move |_| {
+                            let device_path = device_path.clone();
+                            let name = name.clone();
+                            async move {
+                                Self::get_actual_brightness(&device_path)
+                                    .await
+                                    .ok()
+                                    .map(|value| BrightnessEvent(name, value))
+                            }
+                        }
+                    })
+                    .boxed(),
+            );
+        }
+
+        Ok(select_all(events))
+    }
+
+    async fn start_listening(
+        state: State,
+        ddc_names: &[String],
+        output: &mut Sender<ServiceEvent<Self>>,
+    ) -> State {
+        match state {
+            State::Init => match zbus::Connection::system().await {
+                Ok(conn) => {
+                    let backlight_paths = Self::backlight_paths();
+                    let data =
+                        BrightnessService::initialize_data(&backlight_paths, ddc_names).await;
+
+                    match data {
+                        Ok(data) => {
+                            let _ = output
+                                .send(ServiceEvent::Init(BrightnessService { data, conn }))
+                                .await;
+
+                            State::Active(backlight_paths)
+                        }
+                        Err(err) => {
+                            error!("Failed to initialize brightness data: {}", err);
+
+                            State::Error
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!("Failed to connect to system bus: {}", err);
+
+                    State::Error
+                }
+            },
+            State::Active(backlight_paths) => {
+                info!("Listening for brightness events");
+
+                match BrightnessService::events(&backlight_paths).await {
+                    Ok(mut events) => {
+                        while let Some(event) = events.next().await {
+                            let _ = output.send(ServiceEvent::Update(event)).await;
+                        }
+
+                        // DDC monitors have no change notifications
+                        let _ = pending::<u8>().next().await;
+                        State::Active(backlight_paths)
+                    }
+                    Err(err) => {
+                        error!("Failed to listen for brightness events: {}", err);
+
+                        State::Error
+                    }
+                }
+            }
+            State::Error => {
+                error!("Brightness service error");
+
+                let _ = pending::<u8>().next().await;
+                State::Error
+            }
+        }
+    }
+
+    /// Like `subscribe`, but DDC/CI monitors not listed in `devices` are never probed
+    pub fn subscribe_with_devices(devices: Vec<String>) -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            (id, devices.clone()),
+            channel(100, |mut output| async move {
+                let mut state = State::Init;
+
+                loop {
+                    state = BrightnessService::start_listening(state, &devices, &mut output).await;
+                }
+            }),
+        )
+    }
+
+    async fn set_brightness(
+        conn: &zbus::Connection,
+        device: &BrightnessDevice,
+        value: u32,
+    ) -> anyhow::Result<()> {
+        match &device.kind {
+            BrightnessDeviceKind::Backlight => {
+                let brightness_ctrl = BrightnessCtrlProxy::new(conn).await?;
+
+                brightness_ctrl
+                    .set_brightness("backlight", &device.name, value)
+                    .await?;
+            }
+            BrightnessDeviceKind::Ddc(bus) => {
+                let bus = bus.clone();
+                tokio::task::spawn_blocking(move || ddc::set_brightness(&bus, value)).await??;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    Init,
+    Active(Vec<PathBuf>),
+    Error,
+}
+
+/// New brightness value of a device
+#[derive(Debug, Clone)]
+pub struct BrightnessEvent(String, u32);
+
+impl ReadOnlyService for BrightnessService {
+    type UpdateEvent = BrightnessEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        if let Some(device) = self.data.devices.iter_mut().find(|d| d.name == event.0) {
+            device.current = event.1;
+        }
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        Self::subscribe_with_devices(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BrightnessCommand {
+    Set(String, u32),
+    /// Only update the shown value, used while dragging a DDC slider
+    Preview(String, u32),
+}
+
+impl Service for BrightnessService {
+    type Command = BrightnessCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        match command {
+            BrightnessCommand::Set(name, v) => {
+                let conn = self.conn.clone();
+                let device = self.data.devices.iter().find(|d| d.name == name).cloned();
+
+                Task::perform(
+                    async move {
+                        if let Some(device) = device {
+                            debug!("Setting {} brightness to {}", name, v);
+                            if let Err(err) =
+                                BrightnessService::set_brightness(&conn, &device, v).await
+                            {
+                                error!("Failed to set {} brightness: {}", name, err);
+                            }
+                        }
+
+                        BrightnessEvent(name, v)
+                    },
+                    ServiceEvent::Update,
+                )
+            }
+            BrightnessCommand::Preview(name, v) => {
+                self.update(BrightnessEvent(name, v));
+
+                Task::none()
+            }
+        }
+    }
+}
+
+#[proxy(
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/session/auto",
+    interface = "org.freedesktop.login1.Session"
+)]
+pub(crate) trait BrightnessCtrl {
+    fn set_brightness(&self, subsystem: &str, name: &str, value: u32) -> zbus::Result<()>;
+}
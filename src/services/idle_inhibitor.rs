@@ -1,4 +1,5 @@
 use log::{debug, info, warn};
+use std::time::{Duration, Instant};
 use wayland_client::{
     protocol::{
         wl_compositor::WlCompositor,
@@ -12,6 +13,13 @@ use wayland_protocols::wp::idle_inhibit::zv1::client::{
     zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1, zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1,
 };
 
+/// What is keeping the system awake when the inhibitor wasn't enabled by hand
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AutoInhibitReason {
+    Media,
+    Fullscreen,
+}
+
 pub struct IdleInhibitorManager {
     _connection: Connection,
     _display: WlDisplay,
@@ -19,6 +27,9 @@ pub struct IdleInhibitorManager {
     event_queue: EventQueue<IdleInhibitorManagerData>,
     handle: QueueHandle<IdleInhibitorManagerData>,
     data: IdleInhibitorManagerData,
+    manual: bool,
+    deadline: Option<Instant>,
+    auto_reason: Option<AutoInhibitReason>,
 }
 
 impl IdleInhibitorManager {
@@ -37,6 +48,9 @@ impl IdleInhibitorManager {
                 event_queue,
                 handle,
                 data: IdleInhibitorManagerData::default(),
+                manual: false,
+                deadline: None,
+                auto_reason: None,
             };
 
             obj.roundtrip()?;
@@ -61,14 +75,63 @@ impl IdleInhibitorManager {
         self.data.idle_inhibitor_state.is_some()
     }
 
+    /// Whether the inhibitor was enabled by hand, as opposed to by the auto mode
+    pub fn is_manual(&self) -> bool {
+        self.manual
+    }
+
+    /// Time left before a timed inhibit ends
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn auto_reason(&self) -> Option<AutoInhibitReason> {
+        self.auto_reason
+    }
+
     pub fn toggle(&mut self) {
-        let res = if self.is_inhibited() {
-            self.set_inhibit_idle(false)
+        if self.manual {
+            self.stop();
         } else {
-            self.set_inhibit_idle(true)
-        };
+            self.inhibit_for(None);
+        }
+    }
+
+    /// Keep the system awake for the given time, or until disabled without a duration
+    pub fn inhibit_for(&mut self, duration: Option<Duration>) {
+        self.manual = true;
+        self.deadline = duration.map(|duration| Instant::now() + duration);
+        self.sync();
+    }
+
+    pub fn stop(&mut self) {
+        self.manual = false;
+        self.deadline = None;
+        self.sync();
+    }
+
+    /// Ends the timed inhibit once its deadline has passed
+    pub fn tick(&mut self) {
+        if self
+            .deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            info!(target: "IdleInhibitor::tick", "Idle Inhibitor timeout expired");
+            self.stop();
+        }
+    }
+
+    pub fn set_auto_reason(&mut self, reason: Option<AutoInhibitReason>) {
+        if self.auto_reason != reason {
+            debug!(target: "IdleInhibitor::set_auto_reason", "Auto inhibit reason changed to {:?}", reason);
+            self.auto_reason = reason;
+            self.sync();
+        }
+    }
 
-        if let Err(err) = res {
+    fn sync(&mut self) {
+        if let Err(err) = self.set_inhibit_idle(self.manual || self.auto_reason.is_some()) {
             warn!("Failed to toggle idle inhibitor: {}", err);
         }
     }
@@ -0,0 +1,304 @@
+use super::{brightness::BrightnessCtrlProxy, ReadOnlyService, Service, ServiceEvent};
+use iced::{
+    futures::{channel::mpsc::Sender, stream::pending, SinkExt, Stream, StreamExt},
+    stream::channel,
+    Subscription, Task,
+};
+use inotify::{Inotify, WatchMask};
+use log::{debug, error, info, warn};
+use std::{
+    any::TypeId,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use zbus::proxy;
+
+const LEDS_FOLDER: &str = "/sys/class/leds";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    UPower,
+    /// Led device name in the leds folder
+    Leds(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardBacklightData {
+    pub current: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyboardBacklightService {
+    data: KeyboardBacklightData,
+    source: Source,
+    conn: zbus::Connection,
+}
+
+impl Deref for KeyboardBacklightService {
+    type Target = KeyboardBacklightData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl KeyboardBacklightService {
+    fn leds_device() -> Option<PathBuf> {
+        let mut devices = fs::read_dir(LEDS_FOLDER)
+            .ok()?
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .contains("kbd_backlight")
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        devices.sort();
+
+        devices.into_iter().next()
+    }
+
+    fn read_value(path: &Path) -> anyhow::Result<u32> {
+        Ok(fs::read_to_string(path)?.trim().parse::<u32>()?)
+    }
+
+    /// UPower is preferred, the leds class is the fallback when UPower doesn't
+    /// know any keyboard backlight
+    async fn initialize_data(
+        conn: &zbus::Connection,
+    ) -> anyhow::Result<(Source, KeyboardBacklightData)> {
+        if let Ok(kbd_backlight) = KbdBacklightProxy::new(conn).await {
+            if let (Ok(max), Ok(current)) = (
+                kbd_backlight.get_max_brightness().await,
+                kbd_backlight.get_brightness().await,
+            ) {
+                if max > 0 {
+                    debug!("UPower keyboard backlight: {}/{}", current, max);
+
+                    return Ok((
+                        Source::UPower,
+                        KeyboardBacklightData {
+                            current: current.max(0) as u32,
+                            max: max as u32,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let device =
+            Self::leds_device().ok_or_else(|| anyhow::anyhow!("No keyboard backlight found"))?;
+        let name = device
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let max = Self::read_value(&device.join("max_brightness"))?;
+        let current = Self::read_value(&device.join("brightness"))?;
+        debug!("{} keyboard backlight: {}/{}", name, current, max);
+
+        Ok((Source::Leds(name), KeyboardBacklightData { current, max }))
+    }
+
+    async fn events(
+        conn: &zbus::Connection,
+        source: &Source,
+    ) -> anyhow::Result<impl Stream<Item = KeyboardBacklightEvent>> {
+        match source {
+            Source::UPower => {
+                let kbd_backlight = KbdBacklightProxy::new(conn).await?;
+
+                Ok(kbd_backlight
+                    .receive_brightness_changed()
+                    .await?
+                    .filter_map(|signal| async move {
+                        signal
+                            .args()
+                            .ok()
+                            .map(|args| KeyboardBacklightEvent(args.value.max(0) as u32))
+                    })
+                    .boxed())
+            }
+            Source::Leds(name) => {
+                let device = Path::new(LEDS_FOLDER).join(name);
+                // hardware hotkeys changes are notified only on brightness_hw_changed
+                let watched = ["brightness_hw_changed", "brightness"]
+                    .iter()
+                    .map(|file| device.join(file))
+                    .find(|path| path.exists())
+                    .ok_or_else(|| anyhow::anyhow!("No brightness file for {}", name))?;
+
+                let inotify = Inotify::init()?;
+                inotify.watches().add(&watched, WatchMask::MODIFY)?;
+
+                let buffer = [0; 512];
+                Ok(inotify
+                    .into_event_stream(buffer)?
+                    .filter_map(move |_| {
+                        let brightness = device.join("brightness");
+                        async move {
+                            Self::read_value(&brightness)
+                                .ok()
+                                .map(KeyboardBacklightEvent)
+                        }
+                    })
+                    .boxed())
+            }
+        }
+    }
+
+    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+        match state {
+            State::Init => match zbus::Connection::system().await {
+                Ok(conn) => match Self::initialize_data(&conn).await {
+                    Ok((source, data)) => {
+                        let _ = output
+                            .send(ServiceEvent::Init(KeyboardBacklightService {
+                                data,
+                                source: source.clone(),
+                                conn: conn.clone(),
+                            }))
+                            .await;
+
+                        State::Active(conn, source)
+                    }
+                    Err(err) => {
+                        warn!("Failed to initialize keyboard backlight: {}", err);
+
+                        State::Error
+                    }
+                },
+                Err(err) => {
+                    error!("Failed to connect to system bus: {}", err);
+
+                    State::Error
+                }
+            },
+            State::Active(conn, source) => {
+                info!("Listening for keyboard backlight events");
+
+                match Self::events(&conn, &source).await {
+                    Ok(mut events) => {
+                        while let Some(event) = events.next().await {
+                            let _ = output.send(ServiceEvent::Update(event)).await;
+                        }
+
+                        State::Active(conn, source)
+                    }
+                    Err(err) => {
+                        error!("Failed to listen for keyboard backlight events: {}", err);
+
+                        State::Error
+                    }
+                }
+            }
+            State::Error => {
+                let _ = pending::<u8>().next().await;
+
+                State::Error
+            }
+        }
+    }
+
+    async fn set_brightness(
+        conn: &zbus::Connection,
+        source: &Source,
+        value: u32,
+    ) -> anyhow::Result<()> {
+        match source {
+            Source::UPower => {
+                let kbd_backlight = KbdBacklightProxy::new(conn).await?;
+
+                kbd_backlight.set_brightness(value as i32).await?;
+            }
+            Source::Leds(name) => {
+                let brightness_ctrl = BrightnessCtrlProxy::new(conn).await?;
+
+                brightness_ctrl.set_brightness("leds", name, value).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    Init,
+    Active(zbus::Connection, Source),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyboardBacklightEvent(u32);
+
+impl ReadOnlyService for KeyboardBacklightService {
+    type UpdateEvent = KeyboardBacklightEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        self.data.current = event.0;
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(100, |mut output| async move {
+                let mut state = State::Init;
+
+                loop {
+                    state = KeyboardBacklightService::start_listening(state, &mut output).await;
+                }
+            }),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum KeyboardBacklightCommand {
+    Set(u32),
+}
+
+impl Service for KeyboardBacklightService {
+    type Command = KeyboardBacklightCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        let conn = self.conn.clone();
+        let source = self.source.clone();
+
+        match command {
+            KeyboardBacklightCommand::Set(value) => Task::perform(
+                async move {
+                    debug!("Setting keyboard backlight to {}", value);
+                    if let Err(err) = Self::set_brightness(&conn, &source, value).await {
+                        error!("Failed to set keyboard backlight: {}", err);
+                    }
+
+                    value
+                },
+                |value| ServiceEvent::Update(KeyboardBacklightEvent(value)),
+            ),
+        }
+    }
+}
+
+#[proxy(
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower/KbdBacklight",
+    interface = "org.freedesktop.UPower.KbdBacklight"
+)]
+trait KbdBacklight {
+    fn get_brightness(&self) -> zbus::Result<i32>;
+
+    fn get_max_brightness(&self) -> zbus::Result<i32>;
+
+    fn set_brightness(&self, value: i32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn brightness_changed(&self, value: i32) -> zbus::Result<()>;
+}
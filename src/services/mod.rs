@@ -4,8 +4,14 @@ pub mod audio;
 pub mod bluetooth;
 pub mod brightness;
 pub mod idle_inhibitor;
+pub mod keyboard_backlight;
+pub mod mpris;
 pub mod network;
+pub mod night_light;
+pub mod notifications;
 pub mod privacy;
+pub mod rfkill;
+pub mod tray;
 pub mod upower;
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,80 @@
+use std::{
+    env, fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Bigger art is truncated, the image won't decode
+const MAX_SIZE: u64 = 10 * 1024 * 1024;
+/// Number of cached files, the oldest ones are removed to make room for new art
+const MAX_CACHED: usize = 100;
+
+pub fn is_remote(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+fn cache_dir() -> PathBuf {
+    let cache_home = env::var("XDG_CACHE_HOME").unwrap_or_else(|_| {
+        format!(
+            "{}/.cache",
+            env::var("HOME").expect("Could not get HOME environment variable")
+        )
+    });
+
+    PathBuf::from(cache_home).join("ashell").join("art")
+}
+
+/// Cache file of the art url, named after its hash
+pub fn cache_path(url: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+
+    cache_dir().join(format!("{:016x}", hasher.finish()))
+}
+
+/// Downloads the art to the cache dir, returns the path of the cached file
+pub async fn download(url: String) -> anyhow::Result<PathBuf> {
+    tokio::task::spawn_blocking(move || {
+        let response = ureq::get(&url).timeout(Duration::from_secs(10)).call()?;
+
+        let mut content = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_SIZE)
+            .read_to_end(&mut content)?;
+
+        let dir = cache_dir();
+        fs::create_dir_all(&dir)?;
+        prune(&dir)?;
+
+        // written aside and renamed, a partial file is never shown
+        let path = cache_path(&url);
+        let partial = path.with_extension("part");
+        fs::write(&partial, content)?;
+        fs::rename(&partial, &path)?;
+
+        Ok(path)
+    })
+    .await?
+}
+
+fn prune(dir: &Path) -> anyhow::Result<()> {
+    let mut files = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+
+            Some((entry.metadata().ok()?.modified().ok()?, entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    if files.len() >= MAX_CACHED {
+        files.sort();
+        for (_, path) in &files[..=files.len() - MAX_CACHED] {
+            fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use iced::futures::{stream_select, Stream, StreamExt};
+use std::{collections::HashMap, path::PathBuf};
+use zbus::{
+    fdo::DBusProxy,
+    proxy,
+    zvariant::{ObjectPath, OwnedValue},
+    MatchRule, MessageStream,
+};
+
+pub const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// Bus names of every running MPRIS player
+pub async fn player_names(conn: &zbus::Connection) -> anyhow::Result<Vec<String>> {
+    let dbus = DBusProxy::new(conn).await?;
+
+    let mut names = dbus
+        .list_names()
+        .await?
+        .into_iter()
+        .map(|name| name.to_string())
+        .filter(|name| name.starts_with(MPRIS_PREFIX))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    Ok(names)
+}
+
+pub async fn player(conn: &zbus::Connection, name: String) -> anyhow::Result<PlayerProxy<'static>> {
+    let player = PlayerProxy::builder(conn)
+        .destination(name)?
+        .build()
+        .await?;
+
+    Ok(player)
+}
+
+pub async fn media_player(
+    conn: &zbus::Connection,
+    name: String,
+) -> anyhow::Result<MediaPlayer2Proxy<'static>> {
+    let media_player = MediaPlayer2Proxy::builder(conn)
+        .destination(name)?
+        .build()
+        .await?;
+
+    Ok(media_player)
+}
+
+pub enum PlayerChange {
+    /// A player bus name got a new owner
+    Appeared(String),
+    /// A player bus name left the bus
+    Vanished(String),
+    /// The players of the sender unique name changed some of their properties
+    Properties(String),
+    /// The players of the sender unique name jumped to the position, in microseconds
+    Seeked(String, i64),
+    /// The remote art url has been downloaded to the cached file, it's not a D-Bus signal
+    ArtCached(String, PathBuf),
+}
+
+fn signal_sender(message: &zbus::Message) -> Option<String> {
+    message.header().sender().map(|sender| sender.to_string())
+}
+
+/// Emits every time a player appears, disappears, changes one of its properties or seeks
+pub async fn players_changed(
+    conn: &zbus::Connection,
+) -> anyhow::Result<impl Stream<Item = PlayerChange>> {
+    let dbus = DBusProxy::new(conn).await?;
+    let owners = dbus
+        .receive_name_owner_changed()
+        .await?
+        .filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            if !args.name.starts_with(MPRIS_PREFIX) {
+                return None;
+            }
+
+            let name = args.name.to_string();
+            Some(match args.new_owner.as_ref() {
+                Some(_) => PlayerChange::Appeared(name),
+                None => PlayerChange::Vanished(name),
+            })
+        })
+        .boxed();
+
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .path("/org/mpris/MediaPlayer2")?
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .arg(0, "org.mpris.MediaPlayer2.Player")?
+        .build();
+
+    let properties = MessageStream::for_match_rule(rule, conn, None)
+        .await?
+        .filter_map(
+            |message| async move { signal_sender(&message.ok()?).map(PlayerChange::Properties) },
+        )
+        .boxed();
+
+    // the position isn't notified with PropertiesChanged, only jumps are signaled
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .path("/org/mpris/MediaPlayer2")?
+        .interface("org.mpris.MediaPlayer2.Player")?
+        .member("Seeked")?
+        .build();
+
+    let seeked = MessageStream::for_match_rule(rule, conn, None)
+        .await?
+        .filter_map(|message| async move {
+            let message = message.ok()?;
+            let position = message.body().deserialize::<i64>().ok()?;
+
+            signal_sender(&message).map(|sender| PlayerChange::Seeked(sender, position))
+        })
+        .boxed();
+
+    Ok(stream_select!(owners, properties, seeked))
+}
+
+#[proxy(
+    interface = "org.mpris.MediaPlayer2",
+    default_path = "/org/mpris/MediaPlayer2"
+)]
+pub trait MediaPlayer2 {
+    #[zbus(property)]
+    fn identity(&self) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "org.mpris.MediaPlayer2.Player",
+    default_path = "/org/mpris/MediaPlayer2"
+)]
+pub trait Player {
+    fn next(&self) -> zbus::Result<()>;
+
+    fn previous(&self) -> zbus::Result<()>;
+
+    fn play_pause(&self) -> zbus::Result<()>;
+
+    fn set_position(&self, track_id: &ObjectPath<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn metadata(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> zbus::Result<i64>;
+
+    #[zbus(property)]
+    fn volume(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn set_volume(&self, value: f64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn can_seek(&self) -> zbus::Result<bool>;
+}
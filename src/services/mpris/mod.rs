@@ -0,0 +1,531 @@
+use super::{ReadOnlyService, Service, ServiceEvent};
+use dbus::PlayerChange;
+use iced::{
+    futures::{
+        channel::mpsc::{self, Sender},
+        stream::{once, pending, select},
+        SinkExt, StreamExt,
+    },
+    stream::channel,
+    Subscription, Task,
+};
+use log::{debug, error, info, warn};
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use zbus::{
+    fdo::DBusProxy,
+    zvariant::{OwnedObjectPath, OwnedValue},
+};
+
+mod art;
+mod dbus;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl From<String> for PlaybackStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Playing" => PlaybackStatus::Playing,
+            "Paused" => PlaybackStatus::Paused,
+            _ => PlaybackStatus::Stopped,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MprisMetadata {
+    pub track_id: Option<OwnedObjectPath>,
+    pub title: Option<String>,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub art_url: Option<String>,
+    pub length: Option<Duration>,
+    /// Downloaded copy of the remote art
+    cached_art: Option<PathBuf>,
+}
+
+impl From<HashMap<String, OwnedValue>> for MprisMetadata {
+    fn from(metadata: HashMap<String, OwnedValue>) -> Self {
+        let get = |key: &str| metadata.get(key).and_then(|value| value.try_clone().ok());
+
+        // players don't agree on the signedness of the length
+        let length = get("mpris:length").and_then(|value| {
+            i64::try_from(value.try_clone().ok()?)
+                .ok()
+                .or_else(|| u64::try_from(value).ok().map(|length| length as i64))
+        });
+
+        let art_url = get("mpris:artUrl").and_then(|value| String::try_from(value).ok());
+        let cached_art = art_url
+            .as_deref()
+            .filter(|url| art::is_remote(url))
+            .map(art::cache_path)
+            .filter(|path| path.exists());
+
+        MprisMetadata {
+            track_id: get("mpris:trackid").and_then(|value| OwnedObjectPath::try_from(value).ok()),
+            title: get("xesam:title")
+                .and_then(|value| String::try_from(value).ok())
+                .filter(|title| !title.is_empty()),
+            artists: get("xesam:artist")
+                .and_then(|value| Vec::<String>::try_from(value).ok())
+                .unwrap_or_default(),
+            album: get("xesam:album")
+                .and_then(|value| String::try_from(value).ok())
+                .filter(|album| !album.is_empty()),
+            art_url,
+            length: length
+                .filter(|length| *length > 0)
+                .map(|length| Duration::from_micros(length as u64)),
+            cached_art,
+        }
+    }
+}
+
+impl MprisMetadata {
+    /// "artist – title", or just the title when the artist is unknown
+    pub fn label(&self) -> Option<String> {
+        let title = self.title.as_ref()?;
+
+        Some(if self.artists.is_empty() {
+            title.clone()
+        } else {
+            format!("{} – {}", self.artists.join(", "), title)
+        })
+    }
+
+    /// Local path of the album art, remote art is shown once it's downloaded
+    pub fn art_path(&self) -> Option<PathBuf> {
+        let url = self.art_url.as_ref()?;
+        if art::is_remote(url) {
+            return self.cached_art.clone();
+        }
+
+        let path = url.strip_prefix("file://")?;
+
+        let bytes = path.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let escaped = (bytes[i] == b'%')
+                .then(|| bytes.get(i + 1..i + 3))
+                .flatten()
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+            match escaped {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                None => {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        Some(PathBuf::from(String::from_utf8_lossy(&decoded).to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MprisPlayer {
+    pub service: String,
+    pub identity: String,
+    pub status: PlaybackStatus,
+    pub metadata: MprisMetadata,
+    pub volume: Option<f64>,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    pub can_seek: bool,
+    position: Option<Duration>,
+    position_read: Instant,
+    /// Unique name of the bus name owner, the signals are sent from it
+    owner: String,
+}
+
+impl MprisPlayer {
+    /// Players don't notify the position while playing, it's extrapolated
+    /// from the last read
+    pub fn position(&self) -> Option<Duration> {
+        let position = self.position?;
+
+        let position = if self.status == PlaybackStatus::Playing {
+            position + self.position_read.elapsed()
+        } else {
+            position
+        };
+
+        Some(match self.metadata.length {
+            Some(length) => position.min(length),
+            None => position,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MprisData {
+    pub players: Vec<MprisPlayer>,
+}
+
+impl MprisData {
+    pub fn is_playing(&self) -> bool {
+        self.players
+            .iter()
+            .any(|player| player.status == PlaybackStatus::Playing)
+    }
+
+    /// The selected player if it's still running, otherwise the first playing one
+    pub fn player(&self, selected: Option<&str>) -> Option<&MprisPlayer> {
+        selected
+            .and_then(|selected| self.players.iter().find(|p| p.service == selected))
+            .or_else(|| {
+                self.players
+                    .iter()
+                    .find(|p| p.status == PlaybackStatus::Playing)
+            })
+            .or_else(|| self.players.first())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MprisService {
+    data: MprisData,
+    conn: zbus::Connection,
+}
+
+impl Deref for MprisService {
+    type Target = MprisData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl MprisService {
+    async fn initialize_data(conn: &zbus::Connection) -> anyhow::Result<MprisData> {
+        let mut players = Vec::new();
+
+        for name in dbus::player_names(conn).await? {
+            // players can quit between the listing and the property read
+            match Self::player_data(conn, name.clone()).await {
+                Ok(player) => players.push(player),
+                Err(err) => debug!("Failed to read MPRIS player {}: {}", name, err),
+            }
+        }
+
+        Ok(MprisData { players })
+    }
+
+    async fn player_data(conn: &zbus::Connection, name: String) -> anyhow::Result<MprisPlayer> {
+        let owner = DBusProxy::new(conn)
+            .await?
+            .get_name_owner(name.as_str().try_into()?)
+            .await?
+            .to_string();
+        let player = dbus::player(conn, name.clone()).await?;
+        let identity = match dbus::media_player(conn, name.clone()).await {
+            Ok(media_player) => media_player.identity().await.ok(),
+            Err(_) => None,
+        }
+        .unwrap_or_else(|| name.trim_start_matches(dbus::MPRIS_PREFIX).to_string());
+
+        Ok(MprisPlayer {
+            status: player.playback_status().await?.into(),
+            metadata: player
+                .metadata()
+                .await
+                .map(MprisMetadata::from)
+                .unwrap_or_default(),
+            volume: player.volume().await.ok(),
+            can_go_next: player.can_go_next().await.unwrap_or_default(),
+            can_go_previous: player.can_go_previous().await.unwrap_or_default(),
+            can_seek: player.can_seek().await.unwrap_or_default(),
+            position: player
+                .position()
+                .await
+                .ok()
+                .filter(|position| *position >= 0)
+                .map(|position| Duration::from_micros(position as u64)),
+            position_read: Instant::now(),
+            service: name,
+            identity,
+            owner,
+        })
+    }
+
+    /// Downloads in the background the remote art that isn't cached yet,
+    /// each url is tried once
+    fn download_art(
+        data: &MprisData,
+        downloads: &mut HashSet<String>,
+        sender: &Sender<PlayerChange>,
+    ) {
+        let urls = data
+            .players
+            .iter()
+            .filter(|p| p.metadata.cached_art.is_none())
+            .filter_map(|p| p.metadata.art_url.as_ref())
+            .filter(|url| art::is_remote(url));
+
+        for url in urls {
+            if !downloads.insert(url.clone()) {
+                continue;
+            }
+
+            let url = url.clone();
+            let mut sender = sender.clone();
+            tokio::spawn(async move {
+                match art::download(url.clone()).await {
+                    Ok(path) => {
+                        let _ = sender.send(PlayerChange::ArtCached(url, path)).await;
+                    }
+                    Err(err) => warn!("Failed to download album art {}: {}", url, err),
+                }
+            });
+        }
+    }
+
+    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+        match state {
+            State::Init => match zbus::Connection::session().await {
+                Ok(conn) => match Self::initialize_data(&conn).await {
+                    Ok(data) => {
+                        let _ = output
+                            .send(ServiceEvent::Init(MprisService {
+                                data: data.clone(),
+                                conn: conn.clone(),
+                            }))
+                            .await;
+
+                        State::Active(conn, data)
+                    }
+                    Err(err) => {
+                        warn!("Failed to initialize MPRIS players: {}", err);
+
+                        State::Error
+                    }
+                },
+                Err(err) => {
+                    error!("Failed to connect to session bus: {}", err);
+
+                    State::Error
+                }
+            },
+            State::Active(conn, mut data) => {
+                info!("Listening for MPRIS events");
+
+                match dbus::players_changed(&conn).await {
+                    Ok(events) => {
+                        let (art_sender, art_receiver) = mpsc::channel(10);
+                        let mut downloads = HashSet::new();
+                        Self::download_art(&data, &mut downloads, &art_sender);
+
+                        // the downloads never end, a None marks the end of the D-Bus signals
+                        let mut events = select(
+                            events.map(Some).chain(once(async { None })).boxed(),
+                            art_receiver.map(Some),
+                        );
+                        while let Some(Some(change)) = events.next().await {
+                            let changed = match change {
+                                PlayerChange::Appeared(name) => {
+                                    match Self::player_data(&conn, name.clone()).await {
+                                        Ok(player) => {
+                                            data.players.retain(|p| p.service != name);
+                                            let index =
+                                                data.players.partition_point(|p| p.service < name);
+                                            data.players.insert(index, player);
+                                            true
+                                        }
+                                        Err(err) => {
+                                            debug!("Failed to read MPRIS player {}: {}", name, err);
+                                            false
+                                        }
+                                    }
+                                }
+                                PlayerChange::Vanished(name) => {
+                                    let count = data.players.len();
+                                    data.players.retain(|p| p.service != name);
+                                    data.players.len() != count
+                                }
+                                PlayerChange::Properties(sender) => {
+                                    let mut changed = false;
+                                    for player in
+                                        data.players.iter_mut().filter(|p| p.owner == sender)
+                                    {
+                                        match Self::player_data(&conn, player.service.clone()).await
+                                        {
+                                            Ok(new_player) => {
+                                                *player = new_player;
+                                                changed = true;
+                                            }
+                                            Err(err) => debug!(
+                                                "Failed to refresh MPRIS player {}: {}",
+                                                player.service, err
+                                            ),
+                                        }
+                                    }
+                                    changed
+                                }
+                                PlayerChange::Seeked(sender, position) => {
+                                    let mut changed = false;
+                                    for player in
+                                        data.players.iter_mut().filter(|p| p.owner == sender)
+                                    {
+                                        player.position =
+                                            Some(Duration::from_micros(position.max(0) as u64));
+                                        player.position_read = Instant::now();
+                                        changed = true;
+                                    }
+                                    changed
+                                }
+                                PlayerChange::ArtCached(url, path) => {
+                                    let mut changed = false;
+                                    for player in data
+                                        .players
+                                        .iter_mut()
+                                        .filter(|p| p.metadata.art_url.as_ref() == Some(&url))
+                                    {
+                                        player.metadata.cached_art = Some(path.clone());
+                                        changed = true;
+                                    }
+                                    changed
+                                }
+                            };
+
+                            if changed {
+                                Self::download_art(&data, &mut downloads, &art_sender);
+                                let _ = output
+                                    .send(ServiceEvent::Update(MprisEvent(data.clone())))
+                                    .await;
+                            }
+                        }
+
+                        State::Active(conn, data)
+                    }
+                    Err(err) => {
+                        error!("Failed to listen for MPRIS events: {}", err);
+
+                        State::Error
+                    }
+                }
+            }
+            State::Error => {
+                let _ = pending::<u8>().next().await;
+
+                State::Error
+            }
+        }
+    }
+
+    async fn player_command(
+        conn: &zbus::Connection,
+        command: MprisCommand,
+        track_id: Option<OwnedObjectPath>,
+    ) -> anyhow::Result<()> {
+        match command {
+            MprisCommand::PlayPause(name) => dbus::player(conn, name).await?.play_pause().await?,
+            MprisCommand::Next(name) => dbus::player(conn, name).await?.next().await?,
+            MprisCommand::Previous(name) => dbus::player(conn, name).await?.previous().await?,
+            MprisCommand::SetPosition(name, position) => {
+                let track_id =
+                    track_id.ok_or_else(|| anyhow::anyhow!("{} has no track id", name))?;
+
+                dbus::player(conn, name)
+                    .await?
+                    .set_position(&track_id, position.as_micros() as i64)
+                    .await?
+            }
+            MprisCommand::SetVolume(name, volume) => {
+                dbus::player(conn, name)
+                    .await?
+                    .set_volume(volume.clamp(0., 1.))
+                    .await?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    Init,
+    /// The listener keeps its own copy of the players to refresh them one by one
+    Active(zbus::Connection, MprisData),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct MprisEvent(MprisData);
+
+impl ReadOnlyService for MprisService {
+    type UpdateEvent = MprisEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        self.data = event.0;
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(100, |mut output| async move {
+                let mut state = State::Init;
+
+                loop {
+                    state = MprisService::start_listening(state, &mut output).await;
+                }
+            }),
+        )
+    }
+}
+
+/// Player commands, each one targets the player with the given bus name
+#[derive(Debug, Clone)]
+pub enum MprisCommand {
+    PlayPause(String),
+    Next(String),
+    Previous(String),
+    SetPosition(String, Duration),
+    SetVolume(String, f64),
+}
+
+impl Service for MprisService {
+    type Command = MprisCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        let conn = self.conn.clone();
+        let track_id = match &command {
+            MprisCommand::SetPosition(name, _) => self
+                .players
+                .iter()
+                .find(|p| &p.service == name)
+                .and_then(|p| p.metadata.track_id.clone()),
+            _ => None,
+        };
+
+        Task::perform(
+            async move {
+                debug!("MPRIS command: {:?}", command);
+                if let Err(err) = Self::player_command(&conn, command, track_id).await {
+                    error!("Failed to send MPRIS command: {}", err);
+                }
+            },
+            |_| (),
+        )
+        // the players signal their changes, the listener refreshes them
+        .discard()
+    }
+}
@@ -1,12 +1,16 @@
-use super::{AccessPoint, ActiveConnectionInfo, KnownConnection, Vpn};
-use iced::futures::StreamExt;
+use super::{
+    modem_manager, vpn_import, AccessPoint, AccessTechnology, ActiveConnectionInfo,
+    ConnectionDetails, IpConfig, KnownConnection, NetworkSecurity, NewWifiConnection, Vpn,
+    VpnState, WifiSecurity, WiredDevice, WiredProfile, WirelessAdapter,
+};
+use iced::futures::{Stream, StreamExt};
 use itertools::Itertools;
-use log::debug;
-use std::{collections::HashMap, ops::Deref};
+use log::{debug, warn};
+use std::{collections::HashMap, net::Ipv6Addr, ops::Deref, path::Path};
 use zbus::{
     proxy,
     zvariant::{self, ObjectPath, OwnedObjectPath, OwnedValue, Value},
-    Result,
+    MatchRule, MessageStream, Result,
 };
 
 pub struct NetworkDbus<'a>(NetworkManagerProxy<'a>);
@@ -69,10 +73,21 @@ impl<'a> NetworkDbus<'a> {
 
         let mut info = Vec::<ActiveConnectionInfo>::with_capacity(active_connections.len());
         for connection in ac_proxies {
-            if connection.vpn().await.unwrap_or_default() {
+            if connection.vpn().await.unwrap_or_default()
+                || connection.connection_type().await.unwrap_or_default() == "wireguard"
+            {
+                let state = VpnState::from(connection.state().await.unwrap_or_default());
+                let remote = match connection.connection().await {
+                    Ok(settings_path) => self.vpn_remote(settings_path).await.unwrap_or_default(),
+                    Err(_) => None,
+                };
+
                 info.push(ActiveConnectionInfo::Vpn {
                     name: connection.id().await?,
                     object_path: connection.inner().path().to_owned().into(),
+                    state,
+                    remote,
+                    since: None,
                 });
                 continue;
             }
@@ -116,10 +131,39 @@ impl<'a> NetworkDbus<'a> {
                             });
                         }
                     }
-                    Some(DeviceType::WireGuard) => {
-                        info.push(ActiveConnectionInfo::Vpn {
+                    Some(DeviceType::Modem) => {
+                        // a slow or missing ModemManager must not hide the other connections
+                        let modem_path = match device
+                            .udi()
+                            .await
+                            .map_err(anyhow::Error::from)
+                            .and_then(|udi| Ok(OwnedObjectPath::try_from(udi)?))
+                        {
+                            Ok(modem_path) => modem_path,
+                            Err(e) => {
+                                warn!("Failed to get the modem of {}: {}", device.0.path(), e);
+                                continue;
+                            }
+                        };
+
+                        let (strength, access_technology) = match modem_manager::modem_info(
+                            self.0.inner().connection(),
+                            modem_path.clone(),
+                        )
+                        .await
+                        {
+                            Ok(modem) => (modem.signal, modem.access_technology),
+                            Err(e) => {
+                                warn!("Failed to read modem {}: {}", modem_path.as_str(), e);
+                                (0, AccessTechnology::default())
+                            }
+                        };
+
+                        info.push(ActiveConnectionInfo::Wwan {
                             name: connection.id().await?,
-                            object_path: connection.inner().path().to_owned().into(),
+                            modem_path,
+                            strength,
+                            access_technology,
                         });
                     }
                     _ => {}
@@ -132,6 +176,7 @@ impl<'a> NetworkDbus<'a> {
                 ActiveConnectionInfo::Vpn { name, .. } => format!("0{name}"),
                 ActiveConnectionInfo::Wired { name, .. } => format!("1{name}"),
                 ActiveConnectionInfo::WiFi { name, .. } => format!("2{name}"),
+                ActiveConnectionInfo::Wwan { name, .. } => format!("3{name}"),
             };
             helper(a).cmp(&helper(b))
         });
@@ -139,6 +184,47 @@ impl<'a> NetworkDbus<'a> {
         Ok(info)
     }
 
+    /// Emits every time an active connection changes state, e.g. a VPN going from
+    /// activating to activated
+    pub async fn active_connection_state_changed(&self) -> anyhow::Result<impl Stream<Item = ()>> {
+        let rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.NetworkManager")?
+            .interface("org.freedesktop.NetworkManager.Connection.Active")?
+            .member("StateChanged")?
+            .build();
+        let changes =
+            MessageStream::for_match_rule(rule, self.0.inner().connection(), None).await?;
+
+        Ok(changes.map(|_| ()))
+    }
+
+    /// The remote endpoint of a VPN connection: the first WireGuard peer endpoint or
+    /// the remote/gateway of a VPN plugin connection
+    async fn vpn_remote(&self, settings_path: OwnedObjectPath) -> anyhow::Result<Option<String>> {
+        let settings = ConnectionSettingsProxy::builder(self.0.inner().connection())
+            .path(settings_path)?
+            .build()
+            .await?
+            .get_settings()
+            .await?;
+
+        if let Some(peers) = settings.get("wireguard").and_then(|w| w.get("peers")) {
+            let peers = Vec::<HashMap<String, OwnedValue>>::try_from(peers.try_clone()?)?;
+
+            return Ok(peers.iter().find_map(|peer| get_str(peer, "endpoint")));
+        }
+
+        let data = match settings.get("vpn").and_then(|vpn| vpn.get("data")) {
+            Some(data) => HashMap::<String, String>::try_from(data.try_clone()?)?,
+            None => HashMap::new(),
+        };
+
+        Ok(["remote", "gateway"]
+            .iter()
+            .find_map(|key| data.get(*key).cloned()))
+    }
+
     pub async fn known_connections(
         &self,
         wireless_access_points: &[AccessPoint],
@@ -169,7 +255,7 @@ impl<'a> NetworkDbus<'a> {
                 if let Some(cur_ssid) = ssid {
                     known_ssid.push(cur_ssid);
                 }
-            } else if s.contains_key("vpn") {
+            } else if s.contains_key("vpn") || s.contains_key("wireguard") {
                 let id = s
                     .get("connection")
                     .and_then(|c| c.get("id"))
@@ -218,6 +304,117 @@ impl<'a> NetworkDbus<'a> {
         Ok(wireless_devices)
     }
 
+    pub async fn wireless_adapters(&self) -> anyhow::Result<Vec<WirelessAdapter>> {
+        let mut adapters = Vec::new();
+        for path in self.wireless_devices().await? {
+            let device = DeviceProxy::builder(self.0.inner().connection())
+                .path(&path)?
+                .build()
+                .await?;
+
+            adapters.push(WirelessAdapter {
+                interface: device.interface().await.unwrap_or_default(),
+                path,
+            });
+        }
+
+        Ok(adapters)
+    }
+
+    pub async fn wired_devices(&self) -> anyhow::Result<Vec<WiredDevice>> {
+        let mut wired_devices = Vec::new();
+        for path in self.devices().await? {
+            let device = DeviceProxy::builder(self.0.inner().connection())
+                .path(&path)?
+                .build()
+                .await?;
+
+            if !matches!(
+                device.device_type().await.map(DeviceType::from),
+                Ok(DeviceType::Ethernet)
+            ) {
+                continue;
+            }
+
+            let wired_device = WiredDeviceProxy::builder(self.0.inner().connection())
+                .path(&path)?
+                .build()
+                .await?;
+
+            let mut profiles = Vec::new();
+            for connection in device.available_connections().await.unwrap_or_default() {
+                let settings = ConnectionSettingsProxy::builder(self.0.inner().connection())
+                    .path(&connection)?
+                    .build()
+                    .await?;
+                let name = settings
+                    .get_settings()
+                    .await?
+                    .get("connection")
+                    .and_then(|c| get_str(c, "id"))
+                    .unwrap_or_default();
+
+                profiles.push(WiredProfile {
+                    name,
+                    path: connection,
+                });
+            }
+            profiles.sort_by(|a, b| a.name.cmp(&b.name));
+
+            // The active connection path is "/" when the device is disconnected
+            let active_profile = match device.active_connection().await {
+                Ok(active_connection) if active_connection.as_str() != "/" => {
+                    let active_connection =
+                        ActiveConnectionProxy::builder(self.0.inner().connection())
+                            .path(active_connection)?
+                            .build()
+                            .await?;
+
+                    active_connection.connection().await.ok()
+                }
+                _ => None,
+            };
+
+            wired_devices.push(WiredDevice {
+                interface: device.interface().await.unwrap_or_default(),
+                carrier: wired_device.carrier().await.unwrap_or_default(),
+                speed: wired_device.speed().await.unwrap_or_default(),
+                active_profile,
+                profiles,
+                path,
+            });
+        }
+
+        Ok(wired_devices)
+    }
+
+    pub async fn activate_wired_profile(
+        &self,
+        device: OwnedObjectPath,
+        profile: Option<OwnedObjectPath>,
+    ) -> anyhow::Result<()> {
+        // Without a profile NetworkManager picks the best available connection for the device
+        let profile = match profile {
+            Some(profile) => profile,
+            None => OwnedObjectPath::try_from("/")?,
+        };
+
+        self.activate_connection(profile, device, OwnedObjectPath::try_from("/")?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn disconnect_device(&self, device: OwnedObjectPath) -> anyhow::Result<()> {
+        let device = DeviceProxy::builder(self.0.inner().connection())
+            .path(device)?
+            .build()
+            .await?;
+        device.disconnect().await?;
+
+        Ok(())
+    }
+
     pub async fn wireless_access_points(&self) -> anyhow::Result<Vec<AccessPoint>> {
         let wireless_devices = self.wireless_devices().await?;
         let wireless_access_point_futures: Vec<_> = wireless_devices
@@ -255,7 +452,13 @@ impl<'a> NetworkDbus<'a> {
                         .await?;
 
                     let ssid = String::from_utf8_lossy(&ap.ssid().await?.clone()).into_owned();
-                    let public = ap.flags().await.unwrap_or_default() == 0;
+                    let flags = ap.flags().await.unwrap_or_default();
+                    let public = flags == 0;
+                    let security = WifiSecurity::from_flags(
+                        flags,
+                        ap.wpa_flags().await.unwrap_or_default(),
+                        ap.rsn_flags().await.unwrap_or_default(),
+                    );
                     let strength = ap.strength().await?;
                     if let Some(access_point) = aps.get(&ssid) {
                         if access_point.strength > strength {
@@ -270,6 +473,7 @@ impl<'a> NetworkDbus<'a> {
                             strength,
                             state,
                             public,
+                            security,
                             working: false,
                             path: ap.inner().path().to_owned(),
                             device_path: device.0.path().to_owned(),
@@ -367,6 +571,244 @@ impl<'a> NetworkDbus<'a> {
 
         Ok(())
     }
+
+    pub async fn add_wifi_connection(&self, connection: &NewWifiConnection) -> anyhow::Result<()> {
+        let device = match &connection.device_path {
+            Some(device) => device.clone(),
+            None => match self.wireless_devices().await?.into_iter().next() {
+                Some(device) => device,
+                None => anyhow::bail!("No wireless device available"),
+            },
+        };
+
+        debug!("Create new wifi connection: {}", connection.ssid);
+
+        let mut wireless =
+            HashMap::from([("ssid", Value::Array(connection.ssid.as_bytes().into()))]);
+        if connection.hidden {
+            wireless.insert("hidden", Value::Bool(true));
+        }
+
+        let mut conn_settings: HashMap<&str, HashMap<&str, zvariant::Value>> = HashMap::from([
+            ("802-11-wireless", wireless),
+            (
+                "connection",
+                HashMap::from([
+                    ("id", Value::Str(connection.ssid.as_str().into())),
+                    ("type", Value::Str("802-11-wireless".into())),
+                ]),
+            ),
+        ]);
+
+        match &connection.security {
+            NetworkSecurity::Open => {}
+            NetworkSecurity::WpaPersonal { password } => {
+                conn_settings.insert(
+                    "802-11-wireless-security",
+                    HashMap::from([
+                        ("psk", Value::Str(password.as_str().into())),
+                        ("key-mgmt", Value::Str("wpa-psk".into())),
+                    ]),
+                );
+            }
+            NetworkSecurity::Wpa3Personal { password } => {
+                conn_settings.insert(
+                    "802-11-wireless-security",
+                    HashMap::from([
+                        ("psk", Value::Str(password.as_str().into())),
+                        ("key-mgmt", Value::Str("sae".into())),
+                    ]),
+                );
+            }
+            NetworkSecurity::Enterprise {
+                eap,
+                phase2,
+                identity,
+                anonymous_identity,
+                ca_cert,
+                password,
+            } => {
+                conn_settings.insert(
+                    "802-11-wireless-security",
+                    HashMap::from([("key-mgmt", Value::Str("wpa-eap".into()))]),
+                );
+
+                let mut security = HashMap::from([
+                    ("eap", Value::from(vec![eap.value()])),
+                    ("phase2-auth", Value::Str(phase2.value().into())),
+                    ("identity", Value::Str(identity.as_str().into())),
+                    ("password", Value::Str(password.as_str().into())),
+                ]);
+                if let Some(anonymous_identity) = anonymous_identity {
+                    security.insert(
+                        "anonymous-identity",
+                        Value::Str(anonymous_identity.as_str().into()),
+                    );
+                }
+                if let Some(ca_cert) = ca_cert {
+                    // NetworkManager expects a nul terminated file:// uri for path based certificates
+                    let mut path = format!("file://{}", ca_cert).into_bytes();
+                    path.push(0);
+                    security.insert("ca-cert", Value::from(path));
+                }
+                conn_settings.insert("802-1x", security);
+            }
+        }
+
+        self.add_and_activate_connection(conn_settings, &device, &ObjectPath::try_from("/")?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn connection_details(
+        &self,
+        access_point: &AccessPoint,
+    ) -> anyhow::Result<ConnectionDetails> {
+        let wireless_device = WirelessDeviceProxy::builder(self.0.inner().connection())
+            .path(&access_point.device_path)?
+            .build()
+            .await?;
+
+        // The access point list is deduplicated by ssid, so if we are connected
+        // to this network the active access point is the one with the real details
+        let mut ap_path = access_point.path.to_owned();
+        let mut is_active = false;
+        if let Ok(active_access_point) = wireless_device.active_access_point().await {
+            if let Ok(active_access_point) = AccessPointProxy::builder(self.0.inner().connection())
+                .path(active_access_point)?
+                .build()
+                .await
+            {
+                let active_ssid = active_access_point.ssid().await.unwrap_or_default();
+                if String::from_utf8_lossy(&active_ssid) == access_point.ssid {
+                    ap_path = active_access_point.inner().path().to_owned();
+                    is_active = true;
+                }
+            }
+        }
+
+        let ap = AccessPointProxy::builder(self.0.inner().connection())
+            .path(ap_path)?
+            .build()
+            .await?;
+
+        let frequency = ap.frequency().await.unwrap_or_default();
+        let security = WifiSecurity::from_flags(
+            ap.flags().await.unwrap_or_default(),
+            ap.wpa_flags().await.unwrap_or_default(),
+            ap.rsn_flags().await.unwrap_or_default(),
+        );
+
+        let (ip4, ip6) = if is_active {
+            let device = DeviceProxy::builder(self.0.inner().connection())
+                .path(&access_point.device_path)?
+                .build()
+                .await?;
+
+            let ip4 = match device.ip4_config().await {
+                Ok(path) => self.ip4_config(path).await.ok(),
+                Err(_) => None,
+            };
+            let ip6 = match device.ip6_config().await {
+                Ok(path) => self.ip6_config(path).await.ok(),
+                Err(_) => None,
+            };
+
+            (ip4, ip6)
+        } else {
+            (None, None)
+        };
+
+        Ok(ConnectionDetails {
+            ssid: access_point.ssid.clone(),
+            frequency,
+            security,
+            ip4,
+            ip6,
+        })
+    }
+
+    async fn ip4_config(&self, path: OwnedObjectPath) -> anyhow::Result<IpConfig> {
+        let config = Ip4ConfigProxy::builder(self.0.inner().connection())
+            .path(path)?
+            .build()
+            .await?;
+
+        let addresses = config
+            .address_data()
+            .await?
+            .iter()
+            .filter_map(|data| {
+                let address = get_str(data, "address")?;
+                let prefix = get_u32(data, "prefix")?;
+
+                Some(format!("{}/{}", address, prefix))
+            })
+            .collect();
+        let gateway = config.gateway().await.ok().filter(|g| !g.is_empty());
+        let dns = config
+            .nameserver_data()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|data| get_str(data, "address"))
+            .collect();
+
+        Ok(IpConfig {
+            addresses,
+            gateway,
+            dns,
+        })
+    }
+
+    async fn ip6_config(&self, path: OwnedObjectPath) -> anyhow::Result<IpConfig> {
+        let config = Ip6ConfigProxy::builder(self.0.inner().connection())
+            .path(path)?
+            .build()
+            .await?;
+
+        let addresses = config
+            .address_data()
+            .await?
+            .iter()
+            .filter_map(|data| {
+                let address = get_str(data, "address")?;
+                let prefix = get_u32(data, "prefix")?;
+
+                Some(format!("{}/{}", address, prefix))
+            })
+            .collect();
+        let gateway = config.gateway().await.ok().filter(|g| !g.is_empty());
+        let dns = config
+            .nameservers()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|address| <[u8; 16]>::try_from(address).ok())
+            .map(|address| Ipv6Addr::from(address).to_string())
+            .collect();
+
+        Ok(IpConfig {
+            addresses,
+            gateway,
+            dns,
+        })
+    }
+}
+
+pub(super) fn get_str(data: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    data.get(key).and_then(|v| match v.deref() {
+        Value::Str(v) => Some(v.to_string()),
+        _ => None,
+    })
+}
+
+fn get_u32(data: &HashMap<String, OwnedValue>, key: &str) -> Option<u32> {
+    data.get(key).and_then(|v| match v.deref() {
+        Value::U32(v) => Some(*v),
+        _ => None,
+    })
 }
 
 pub struct NetworkSettingsDbus<'a>(SettingsProxy<'a>);
@@ -390,6 +832,22 @@ impl<'a> NetworkSettingsDbus<'a> {
         Ok(self.list_connections().await?)
     }
 
+    pub async fn import_vpn(&self, path: &Path) -> anyhow::Result<OwnedObjectPath> {
+        let mut import = vpn_import::connection_settings(path)?;
+        debug!("Import VPN connection from {}", path.display());
+
+        match self
+            .add_connection(std::mem::take(&mut import.settings))
+            .await
+        {
+            Ok(connection) => Ok(connection),
+            Err(e) => {
+                import.remove_files();
+                Err(e.into())
+            }
+        }
+    }
+
     pub async fn find_connection(&self, name: &str) -> anyhow::Result<Option<OwnedObjectPath>> {
         let connections = self.list_connections().await?;
 
@@ -416,6 +874,20 @@ impl<'a> NetworkSettingsDbus<'a> {
 
         Ok(None)
     }
+
+    pub async fn delete_connection(&self, name: &str) -> anyhow::Result<()> {
+        if let Some(connection) = self.find_connection(name).await? {
+            let connection = ConnectionSettingsProxy::builder(self.inner().connection())
+                .path(connection)?
+                .build()
+                .await?;
+
+            debug!("Delete connection: {}", name);
+            connection.delete().await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -423,6 +895,7 @@ pub enum DeviceType {
     Ethernet,
     Wifi,
     Bluetooth,
+    Modem,
     TunTap,
     WireGuard,
     Generic,
@@ -437,6 +910,7 @@ impl From<u32> for DeviceType {
             1 => DeviceType::Ethernet,
             2 => DeviceType::Wifi,
             5 => DeviceType::Bluetooth,
+            8 => DeviceType::Modem,
             14 => DeviceType::Generic,
             16 => DeviceType::TunTap,
             29 => DeviceType::WireGuard,
@@ -471,18 +945,25 @@ impl From<u32> for ActiveConnectionState {
 pub enum ConnectivityState {
     None,
     Portal,
-    Loss,
+    Limited,
     Full,
     #[default]
     Unknown,
 }
 
+impl ConnectivityState {
+    /// A captive portal or a limited connectivity usually means a login page has to be visited
+    pub fn needs_login(&self) -> bool {
+        matches!(self, ConnectivityState::Portal | ConnectivityState::Limited)
+    }
+}
+
 impl From<u32> for ConnectivityState {
     fn from(state: u32) -> ConnectivityState {
         match state {
             1 => ConnectivityState::None,
             2 => ConnectivityState::Portal,
-            3 => ConnectivityState::Loss,
+            3 => ConnectivityState::Limited,
             4 => ConnectivityState::Full,
             _ => ConnectivityState::Unknown,
         }
@@ -560,8 +1041,17 @@ pub trait NetworkManager {
     #[zbus(property)]
     fn set_wireless_enabled(&self, value: bool) -> Result<()>;
 
+    #[zbus(property)]
+    fn wwan_enabled(&self) -> Result<bool>;
+
+    #[zbus(property)]
+    fn set_wwan_enabled(&self, value: bool) -> Result<()>;
+
     #[zbus(property)]
     fn connectivity(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn connectivity_check_uri(&self) -> Result<String>;
 }
 
 #[proxy(
@@ -587,6 +1077,9 @@ trait ActiveConnection {
 
     #[zbus(property)]
     fn devices(&self) -> Result<Vec<OwnedObjectPath>>;
+
+    #[zbus(property)]
+    fn connection(&self) -> Result<OwnedObjectPath>;
 }
 
 #[proxy(
@@ -595,9 +1088,17 @@ trait ActiveConnection {
     interface = "org.freedesktop.NetworkManager.Device"
 )]
 pub trait Device {
+    fn disconnect(&self) -> Result<()>;
+
     #[zbus(property)]
     fn device_type(&self) -> Result<u32>;
 
+    #[zbus(property)]
+    fn interface(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn udi(&self) -> Result<String>;
+
     #[zbus(property)]
     fn available_connections(&self) -> Result<Vec<OwnedObjectPath>>;
 
@@ -606,13 +1107,19 @@ pub trait Device {
 
     #[zbus(property)]
     fn state(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn ip4_config(&self) -> Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn ip6_config(&self) -> Result<OwnedObjectPath>;
 }
 
 #[proxy(
     interface = "org.freedesktop.NetworkManager.Device.Wired",
     default_service = "org.freedesktop.NetworkManager"
 )]
-trait WiredDevice {
+pub trait WiredDevice {
     /// Carrier property
     #[zbus(property)]
     fn carrier(&self) -> zbus::Result<bool>;
@@ -669,6 +1176,45 @@ pub trait AccessPoint {
 
     #[zbus(property)]
     fn flags(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn wpa_flags(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn rsn_flags(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn frequency(&self) -> Result<u32>;
+}
+
+#[proxy(
+    default_service = "org.freedesktop.NetworkManager",
+    interface = "org.freedesktop.NetworkManager.IP4Config"
+)]
+trait Ip4Config {
+    #[zbus(property)]
+    fn address_data(&self) -> Result<Vec<HashMap<String, OwnedValue>>>;
+
+    #[zbus(property)]
+    fn gateway(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn nameserver_data(&self) -> Result<Vec<HashMap<String, OwnedValue>>>;
+}
+
+#[proxy(
+    default_service = "org.freedesktop.NetworkManager",
+    interface = "org.freedesktop.NetworkManager.IP6Config"
+)]
+trait Ip6Config {
+    #[zbus(property)]
+    fn address_data(&self) -> Result<Vec<HashMap<String, OwnedValue>>>;
+
+    #[zbus(property)]
+    fn gateway(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn nameservers(&self) -> Result<Vec<Vec<u8>>>;
 }
 
 #[proxy(
@@ -679,7 +1225,7 @@ pub trait AccessPoint {
 pub trait Settings {
     fn add_connection(
         &self,
-        connection: HashMap<String, HashMap<String, OwnedValue>>,
+        connection: HashMap<&str, HashMap<&str, Value<'_>>>,
     ) -> Result<OwnedObjectPath>;
 
     #[zbus(property)]
@@ -696,7 +1242,20 @@ pub trait Settings {
     interface = "org.freedesktop.NetworkManager.Settings.Connection"
 )]
 trait ConnectionSettings {
+    fn delete(&self) -> Result<()>;
+
     fn update(&self, settings: HashMap<String, HashMap<String, OwnedValue>>) -> Result<()>;
 
     fn get_settings(&self) -> Result<HashMap<String, HashMap<String, OwnedValue>>>;
 }
+
+#[proxy(
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/AgentManager",
+    interface = "org.freedesktop.NetworkManager.AgentManager"
+)]
+pub trait AgentManager {
+    fn register_with_capabilities(&self, identifier: &str, capabilities: u32) -> Result<()>;
+
+    fn unregister(&self) -> Result<()>;
+}
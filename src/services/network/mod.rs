@@ -1,8 +1,11 @@
 use super::{Service, ServiceEvent};
-use crate::services::{bluetooth::BluetoothService, ReadOnlyService};
+use crate::services::{
+    rfkill::{self, RfkillType},
+    ReadOnlyService,
+};
 use dbus::{
-    AccessPointProxy, ConnectivityState, DeviceProxy, DeviceState, NetworkDbus,
-    NetworkSettingsDbus, WirelessDeviceProxy,
+    AccessPointProxy, ConnectivityState, DeviceState, NetworkDbus, NetworkSettingsDbus,
+    WiredDeviceProxy, WirelessDeviceProxy,
 };
 use iced::{
     futures::{
@@ -13,37 +16,68 @@ use iced::{
     stream::channel,
     Subscription, Task,
 };
-use log::{debug, error, info};
-use std::{any::TypeId, collections::HashMap, ops::Deref};
+use log::{debug, error, info, warn};
+use secret_agent::SecretAgent;
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 use tokio::process::Command;
 use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 
 pub mod dbus;
+mod modem_manager;
+mod secret_agent;
+pub mod vpn_import;
+
+// A plain http page, captive portals redirect it to their login page
+const FALLBACK_PORTAL_URL: &str = "http://neverssl.com";
 
 #[derive(Debug, Clone)]
 pub enum NetworkEvent {
     WiFiEnabled(bool),
+    WwanEnabled(bool),
     AirplaneMode(bool),
     Connectivity(ConnectivityState),
     WirelessDevice {
         wifi_present: bool,
+        wireless_adapters: Vec<WirelessAdapter>,
         wireless_access_points: Vec<AccessPoint>,
     },
+    WiredDevices(Vec<WiredDevice>),
+    /// Wired devices were added or removed, the carrier streams need to be rebuilt
+    WiredDeviceList(Vec<WiredDevice>),
     ActiveConnections(Vec<ActiveConnectionInfo>),
     KnownConnections(Vec<KnownConnection>),
     WirelessAccessPoint(Vec<AccessPoint>),
     Strength((String, u8)),
-    RequestPasswordForSSID(String),
+    Modems(Vec<ModemInfo>),
+    ModemDevices(Vec<ModemInfo>),
+    SecretsRequested(SecretsRequest),
+    SecretsRequestClosed(String, String),
     ScanningNearbyWifi,
+    ConnectionDetails(Option<ConnectionDetails>),
+    VpnImported(Result<Vec<KnownConnection>, String>),
 }
 
 #[derive(Debug, Clone)]
 pub enum NetworkCommand {
     ScanNearByWiFi,
     ToggleWiFi,
+    ToggleMobileData,
     ToggleAirplaneMode,
     SelectAccessPoint((AccessPoint, Option<String>)),
     ToggleVpn(Vpn),
+    ImportVpn(PathBuf),
+    ActivateWiredProfile(OwnedObjectPath, Option<OwnedObjectPath>),
+    DisconnectDevice(OwnedObjectPath),
+    GetConnectionDetails(AccessPoint),
+    ForgetConnection(String),
+    ConnectToNetwork(NewWifiConnection),
+    ProvideSecrets(SecretsRequest, Option<String>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -52,17 +86,250 @@ pub struct AccessPoint {
     pub strength: u8,
     pub state: DeviceState,
     pub public: bool,
+    pub security: WifiSecurity,
     pub working: bool,
     pub path: ObjectPath<'static>,
     pub device_path: ObjectPath<'static>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WirelessAdapter {
+    pub path: OwnedObjectPath,
+    pub interface: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiredProfile {
+    pub name: String,
+    pub path: OwnedObjectPath,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiredDevice {
+    pub path: OwnedObjectPath,
+    pub interface: String,
+    pub carrier: bool,
+    pub speed: u32,
+    pub active_profile: Option<OwnedObjectPath>,
+    pub profiles: Vec<WiredProfile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiSecurity {
+    Open,
+    Wep,
+    WpaPersonal,
+    Wpa2Personal,
+    Wpa3Personal,
+    WpaEnterprise,
+    Wpa2Enterprise,
+    Wpa3Enterprise,
+    EnhancedOpen,
+}
+
+impl WifiSecurity {
+    // See NM80211ApFlags and NM80211ApSecurityFlags in the NetworkManager D-Bus API
+    pub fn from_flags(flags: u32, wpa_flags: u32, rsn_flags: u32) -> Self {
+        const PRIVACY: u32 = 0x1;
+        const KEY_MGMT_PSK: u32 = 0x100;
+        const KEY_MGMT_802_1X: u32 = 0x200;
+        const KEY_MGMT_SAE: u32 = 0x400;
+        const KEY_MGMT_OWE: u32 = 0x800;
+        const KEY_MGMT_OWE_TM: u32 = 0x1000;
+        const KEY_MGMT_EAP_SUITE_B_192: u32 = 0x2000;
+
+        if rsn_flags & KEY_MGMT_EAP_SUITE_B_192 != 0 {
+            WifiSecurity::Wpa3Enterprise
+        } else if rsn_flags & KEY_MGMT_802_1X != 0 {
+            WifiSecurity::Wpa2Enterprise
+        } else if wpa_flags & KEY_MGMT_802_1X != 0 {
+            WifiSecurity::WpaEnterprise
+        } else if rsn_flags & KEY_MGMT_SAE != 0 {
+            WifiSecurity::Wpa3Personal
+        } else if rsn_flags & KEY_MGMT_PSK != 0 {
+            WifiSecurity::Wpa2Personal
+        } else if wpa_flags & KEY_MGMT_PSK != 0 {
+            WifiSecurity::WpaPersonal
+        } else if rsn_flags & (KEY_MGMT_OWE | KEY_MGMT_OWE_TM) != 0 {
+            WifiSecurity::EnhancedOpen
+        } else if flags & PRIVACY != 0 {
+            WifiSecurity::Wep
+        } else {
+            WifiSecurity::Open
+        }
+    }
+
+    pub fn is_enterprise(&self) -> bool {
+        matches!(
+            self,
+            WifiSecurity::WpaEnterprise
+                | WifiSecurity::Wpa2Enterprise
+                | WifiSecurity::Wpa3Enterprise
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WifiSecurity::Open => "Open",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::WpaPersonal => "WPA Personal",
+            WifiSecurity::Wpa2Personal => "WPA2 Personal",
+            WifiSecurity::Wpa3Personal => "WPA3 Personal",
+            WifiSecurity::WpaEnterprise => "WPA Enterprise",
+            WifiSecurity::Wpa2Enterprise => "WPA2 Enterprise",
+            WifiSecurity::Wpa3Enterprise => "WPA3 Enterprise",
+            WifiSecurity::EnhancedOpen => "Enhanced Open",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EapMethod {
+    #[default]
+    Peap,
+    Ttls,
+}
+
+impl EapMethod {
+    pub const ALL: [EapMethod; 2] = [EapMethod::Peap, EapMethod::Ttls];
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            EapMethod::Peap => "peap",
+            EapMethod::Ttls => "ttls",
+        }
+    }
+}
+
+impl std::fmt::Display for EapMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EapMethod::Peap => "PEAP",
+            EapMethod::Ttls => "TTLS",
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Phase2Auth {
+    #[default]
+    Mschapv2,
+    Pap,
+    Gtc,
+}
+
+impl Phase2Auth {
+    pub const ALL: [Phase2Auth; 3] = [Phase2Auth::Mschapv2, Phase2Auth::Pap, Phase2Auth::Gtc];
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            Phase2Auth::Mschapv2 => "mschapv2",
+            Phase2Auth::Pap => "pap",
+            Phase2Auth::Gtc => "gtc",
+        }
+    }
+}
+
+impl std::fmt::Display for Phase2Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Phase2Auth::Mschapv2 => "MSCHAPv2",
+            Phase2Auth::Pap => "PAP",
+            Phase2Auth::Gtc => "GTC",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NetworkSecurity {
+    Open,
+    WpaPersonal {
+        password: String,
+    },
+    Wpa3Personal {
+        password: String,
+    },
+    Enterprise {
+        eap: EapMethod,
+        phase2: Phase2Auth,
+        identity: String,
+        anonymous_identity: Option<String>,
+        ca_cert: Option<String>,
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWifiConnection {
+    pub ssid: String,
+    pub hidden: bool,
+    pub security: NetworkSecurity,
+    pub device_path: Option<OwnedObjectPath>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretsRequest {
+    pub connection_path: String,
+    pub setting_name: String,
+    pub name: String,
+}
+
+impl SecretsRequest {
+    pub fn key(&self) -> (String, String) {
+        (self.connection_path.clone(), self.setting_name.clone())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct IpConfig {
+    pub addresses: Vec<String>,
+    pub gateway: Option<String>,
+    pub dns: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionDetails {
+    pub ssid: String,
+    pub frequency: u32,
+    pub security: WifiSecurity,
+    pub ip4: Option<IpConfig>,
+    pub ip6: Option<IpConfig>,
+}
+
+impl ConnectionDetails {
+    pub fn band(&self) -> &'static str {
+        match self.frequency {
+            0..=2999 => "2.4 GHz",
+            3000..=5924 => "5 GHz",
+            _ => "6 GHz",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Vpn {
     pub name: String,
     pub path: OwnedObjectPath,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VpnState {
+    Connecting,
+    Connected,
+    Disconnecting,
+}
+
+impl From<u32> for VpnState {
+    // See NMActiveConnectionState in the NetworkManager D-Bus API
+    fn from(state: u32) -> Self {
+        match state {
+            2 => VpnState::Connected,
+            3 | 4 => VpnState::Disconnecting,
+            _ => VpnState::Connecting,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum KnownConnection {
     AccessPoint(AccessPoint),
@@ -83,6 +350,16 @@ pub enum ActiveConnectionInfo {
     Vpn {
         name: String,
         object_path: OwnedObjectPath,
+        state: VpnState,
+        remote: Option<String>,
+        /// Known only when the VPN has been seen connecting, NetworkManager doesn't expose it
+        since: Option<SystemTime>,
+    },
+    Wwan {
+        name: String,
+        modem_path: OwnedObjectPath,
+        strength: u8,
+        access_technology: AccessTechnology,
     },
 }
 
@@ -92,20 +369,89 @@ impl ActiveConnectionInfo {
             Self::Wired { name, .. } => name.clone(),
             Self::WiFi { name, .. } => name.clone(),
             Self::Vpn { name, .. } => name.clone(),
+            Self::Wwan { name, .. } => name.clone(),
         }
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AccessTechnology {
+    #[default]
+    Unknown,
+    Gsm,
+    Edge,
+    Umts,
+    Hspa,
+    Lte,
+    Nr5g,
+}
+
+impl AccessTechnology {
+    // See MMModemAccessTechnology in the ModemManager D-Bus API
+    pub fn from_flags(flags: u32) -> Self {
+        const GSM: u32 = 0x2 | 0x4 | 0x8;
+        const EDGE: u32 = 0x10;
+        const UMTS: u32 = 0x20 | 0x400 | 0x800 | 0x1000 | 0x2000;
+        const HSPA: u32 = 0x40 | 0x80 | 0x100 | 0x200;
+        const LTE: u32 = 0x4000 | 0x10000 | 0x20000;
+        const NR5G: u32 = 0x8000;
+
+        if flags & NR5G != 0 {
+            AccessTechnology::Nr5g
+        } else if flags & LTE != 0 {
+            AccessTechnology::Lte
+        } else if flags & HSPA != 0 {
+            AccessTechnology::Hspa
+        } else if flags & UMTS != 0 {
+            AccessTechnology::Umts
+        } else if flags & EDGE != 0 {
+            AccessTechnology::Edge
+        } else if flags & GSM != 0 {
+            AccessTechnology::Gsm
+        } else {
+            AccessTechnology::Unknown
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccessTechnology::Unknown => "",
+            AccessTechnology::Gsm => "2G",
+            AccessTechnology::Edge => "EDGE",
+            AccessTechnology::Umts => "3G",
+            AccessTechnology::Hspa => "H+",
+            AccessTechnology::Lte => "LTE",
+            AccessTechnology::Nr5g => "5G",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModemInfo {
+    pub path: OwnedObjectPath,
+    pub model: String,
+    pub operator: Option<String>,
+    pub signal: u8,
+    pub access_technology: AccessTechnology,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct NetworkData {
     pub wifi_present: bool,
+    pub wireless_adapters: Vec<WirelessAdapter>,
     pub wireless_access_points: Vec<AccessPoint>,
+    pub wired_devices: Vec<WiredDevice>,
     pub active_connections: Vec<ActiveConnectionInfo>,
     pub known_connections: Vec<KnownConnection>,
     pub wifi_enabled: bool,
+    pub modems: Vec<ModemInfo>,
+    pub wwan_enabled: bool,
     pub airplane_mode: bool,
     pub connectivity: ConnectivityState,
+    pub portal_url: String,
     pub scanning_nearby_wifi: bool,
+    pub connection_details: Option<Box<ConnectionDetails>>,
+    pub failed_vpn: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -141,23 +487,113 @@ impl ReadOnlyService for NetworkService {
                 debug!("WiFi enabled: {}", wifi_enabled);
                 self.data.wifi_enabled = wifi_enabled;
             }
+            NetworkEvent::WwanEnabled(wwan_enabled) => {
+                debug!("WWAN enabled: {}", wwan_enabled);
+                self.data.wwan_enabled = wwan_enabled;
+            }
+            NetworkEvent::Modems(modems) | NetworkEvent::ModemDevices(modems) => {
+                for active_connection in self.data.active_connections.iter_mut() {
+                    if let ActiveConnectionInfo::Wwan {
+                        modem_path,
+                        strength,
+                        access_technology,
+                        ..
+                    } = active_connection
+                    {
+                        if let Some(modem) = modems.iter().find(|m| &m.path == modem_path) {
+                            *strength = modem.signal;
+                            *access_technology = modem.access_technology;
+                        }
+                    }
+                }
+                self.data.modems = modems;
+            }
             NetworkEvent::ScanningNearbyWifi => {
                 self.data.scanning_nearby_wifi = true;
             }
             NetworkEvent::WirelessDevice {
                 wifi_present,
+                wireless_adapters,
                 wireless_access_points,
             } => {
                 self.data.wifi_present = wifi_present;
+                self.data.wireless_adapters = wireless_adapters;
                 self.data.scanning_nearby_wifi = false;
                 self.data.wireless_access_points = wireless_access_points;
             }
-            NetworkEvent::ActiveConnections(active_connections) => {
+            NetworkEvent::WiredDevices(wired_devices)
+            | NetworkEvent::WiredDeviceList(wired_devices) => {
+                self.data.wired_devices = wired_devices;
+            }
+            NetworkEvent::ActiveConnections(mut active_connections) => {
+                for current in active_connections.iter_mut() {
+                    let ActiveConnectionInfo::Vpn {
+                        object_path,
+                        state: VpnState::Connected,
+                        since,
+                        ..
+                    } = current
+                    else {
+                        continue;
+                    };
+
+                    let previous = self.data.active_connections.iter().find_map(|c| match c {
+                        ActiveConnectionInfo::Vpn {
+                            object_path: path,
+                            state,
+                            since,
+                            ..
+                        } if path == object_path => Some((*state, *since)),
+                        _ => None,
+                    });
+
+                    *since = match previous {
+                        Some((VpnState::Connected, previous_since)) => previous_since,
+                        _ => Some(SystemTime::now()),
+                    };
+                }
+
+                for previous in &self.data.active_connections {
+                    let ActiveConnectionInfo::Vpn {
+                        name,
+                        object_path,
+                        state: VpnState::Connecting,
+                        ..
+                    } = previous
+                    else {
+                        continue;
+                    };
+
+                    // A VPN that disappears while connecting failed to activate
+                    let still_active = active_connections.iter().any(|c| match c {
+                        ActiveConnectionInfo::Vpn {
+                            object_path: path, ..
+                        } => path == object_path,
+                        _ => false,
+                    });
+                    if !still_active {
+                        info!("VPN {} failed to connect", name);
+                        self.data.failed_vpn = Some(name.clone());
+                    }
+                }
+
+                let failed_vpn_connected = active_connections.iter().any(|c| match c {
+                    ActiveConnectionInfo::Vpn { name, state, .. } => {
+                        *state == VpnState::Connected && self.data.failed_vpn.as_ref() == Some(name)
+                    }
+                    _ => false,
+                });
+                if failed_vpn_connected {
+                    self.data.failed_vpn = None;
+                }
+
                 self.data.active_connections = active_connections;
             }
-            NetworkEvent::KnownConnections(known_connections) => {
+            NetworkEvent::KnownConnections(known_connections)
+            | NetworkEvent::VpnImported(Ok(known_connections)) => {
                 self.data.known_connections = known_connections;
             }
+            NetworkEvent::VpnImported(Err(_)) => {}
             NetworkEvent::Strength((ssid, new_strength)) => {
                 if let Some(ap) = self
                     .data
@@ -183,7 +619,10 @@ impl ReadOnlyService for NetworkService {
             NetworkEvent::WirelessAccessPoint(wireless_access_points) => {
                 self.data.wireless_access_points = wireless_access_points;
             }
-            NetworkEvent::RequestPasswordForSSID(_) => {}
+            NetworkEvent::SecretsRequested(_) | NetworkEvent::SecretsRequestClosed(..) => {}
+            NetworkEvent::ConnectionDetails(connection_details) => {
+                self.data.connection_details = connection_details.map(Box::new);
+            }
         }
     }
 
@@ -207,22 +646,39 @@ impl NetworkService {
     async fn initialize_data(conn: &zbus::Connection) -> anyhow::Result<NetworkData> {
         let nm = NetworkDbus::new(conn).await?;
 
-        // airplane mode
-        let bluetooth_soft_blocked = BluetoothService::check_rfkill_soft_block()
-            .await
-            .unwrap_or_default();
-
         let wifi_present = nm.wifi_device_present().await?;
 
+        let wireless_adapters = nm.wireless_adapters().await?;
+        debug!("Wireless adapters: {:?}", wireless_adapters);
+
+        let wired_devices = nm.wired_devices().await?;
+        debug!("Wired devices: {:?}", wired_devices);
+
         let wifi_enabled = nm.wireless_enabled().await.unwrap_or_default();
         debug!("Wifi enabled: {}", wifi_enabled);
 
-        let airplane_mode = bluetooth_soft_blocked && !wifi_enabled;
+        let modems = modem_manager::modems(conn).await.unwrap_or_default();
+        debug!("Modems: {:?}", modems);
+
+        let wwan_enabled = nm.wwan_enabled().await.unwrap_or_default();
+        debug!("WWAN enabled: {}", wwan_enabled);
+
+        let airplane_mode = rfkill::state()
+            .map(|rfkill| rfkill.airplane_mode())
+            .unwrap_or_default();
         debug!("Airplane mode: {}", airplane_mode);
 
         let active_connections = nm.active_connections_info().await?;
         debug!("Active connections: {:?}", active_connections);
 
+        let portal_url = nm
+            .connectivity_check_uri()
+            .await
+            .ok()
+            .filter(|uri| !uri.is_empty())
+            .unwrap_or_else(|| FALLBACK_PORTAL_URL.to_string());
+        debug!("Portal url: {}", portal_url);
+
         let wireless_access_points = nm.wireless_access_points().await?;
         debug!("Wireless access points: {:?}", wireless_access_points);
 
@@ -231,13 +687,20 @@ impl NetworkService {
 
         Ok(NetworkData {
             wifi_present,
+            wireless_adapters,
+            wired_devices,
             active_connections,
             wifi_enabled,
+            modems,
+            wwan_enabled,
             airplane_mode,
             connectivity: nm.connectivity().await?,
+            portal_url,
             wireless_access_points,
             known_connections,
             scanning_nearby_wifi: false,
+            connection_details: None,
+            failed_vpn: None,
         })
     }
 
@@ -280,7 +743,10 @@ impl NetworkService {
                     Ok(mut events) => {
                         while let Some(event) = events.next().await {
                             let mut exit_loop = false;
-                            if let NetworkEvent::WirelessDevice { .. } = event {
+                            if let NetworkEvent::WirelessDevice { .. }
+                            | NetworkEvent::ModemDevices(_)
+                            | NetworkEvent::WiredDeviceList(_) = event
+                            {
                                 exit_loop = true;
                             }
                             let _ = output.send(ServiceEvent::Update(event)).await;
@@ -355,6 +821,24 @@ impl NetworkService {
             })
             .boxed();
 
+        let active_connection_state_changes = nm
+            .active_connection_state_changed()
+            .await?
+            .then({
+                let conn = conn.clone();
+                move |_| {
+                    let conn = conn.clone();
+                    async move {
+                        let nm = NetworkDbus::new(&conn).await.unwrap();
+                        let value = nm.active_connections_info().await.unwrap_or_default();
+
+                        debug!("Active connection state changed: {:?}", value);
+                        NetworkEvent::ActiveConnections(value)
+                    }
+                }
+            })
+            .boxed();
+
         let devices = nm.wireless_devices().await.unwrap_or_default();
 
         let wireless_devices_changed = nm
@@ -372,15 +856,18 @@ impl NetworkService {
                         let current_devices = nm.wireless_devices().await.unwrap_or_default();
                         if current_devices != devices {
                             let wifi_present = nm.wifi_device_present().await.unwrap_or_default();
+                            let wireless_adapters =
+                                nm.wireless_adapters().await.unwrap_or_default();
                             let wireless_access_points =
                                 nm.wireless_access_points().await.unwrap_or_default();
 
                             debug!(
-                                "Wireless device changed: wifi present {:?}, wireless_access_points {:?}",
-                                wifi_present, wireless_access_points,
+                                "Wireless device changed: wifi present {:?}, wireless_adapters {:?}, wireless_access_points {:?}",
+                                wifi_present, wireless_adapters, wireless_access_points,
                             );
                             Some(NetworkEvent::WirelessDevice {
                                 wifi_present,
+                                wireless_adapters,
                                 wireless_access_points,
                             })
                         } else {
@@ -391,37 +878,67 @@ impl NetworkService {
             })
             .boxed();
 
-        // When devices list change I need to update the wireless device state changes
-        let wireless_ac = nm.wireless_access_points().await?;
-
-        let mut device_state_changes = Vec::with_capacity(wireless_ac.len());
-        for ac in wireless_ac.iter() {
-            let dp = DeviceProxy::builder(conn)
-                .path(ac.device_path.clone())?
+        // Wired devices change when a connection is (de)activated, when a device is
+        // added/removed or when the cable is plugged/unplugged
+        let mut wired_changes = vec![
+            nm.receive_active_connections_changed()
+                .await
+                .map(|_| ())
+                .boxed(),
+            nm.receive_devices_changed().await.map(|_| ()).boxed(),
+        ];
+        let wired_paths = nm
+            .wired_devices()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|wired_device| wired_device.path)
+            .collect::<Vec<_>>();
+        for path in wired_paths.iter() {
+            let dp = WiredDeviceProxy::builder(conn)
+                .path(path.clone())?
                 .build()
                 .await?;
 
-            device_state_changes.push(
-                dp.receive_state_changed()
-                    .await
-                    .filter_map(|val| async move {
-                        let val = val.get().await;
-                        let val = val.map(DeviceState::from).unwrap_or_default();
+            wired_changes.push(dp.receive_carrier_changed().await.map(|_| ()).boxed());
+        }
+        let wired_devices_changed = select_all(wired_changes)
+            .filter_map({
+                let conn = conn.clone();
+                move |_| {
+                    let conn = conn.clone();
+                    let wired_paths = wired_paths.clone();
+                    async move {
+                        let nm = NetworkDbus::new(&conn).await.unwrap();
+                        // an empty list would look like a device list change and
+                        // rebuild every stream, a failed read is skipped instead
+                        let wired_devices = match nm.wired_devices().await {
+                            Ok(wired_devices) => wired_devices,
+                            Err(err) => {
+                                warn!("Failed to read the wired devices: {}", err);
+
+                                return None;
+                            }
+                        };
 
-                        if val == DeviceState::NeedAuth {
-                            Some(val)
-                        } else {
-                            None
-                        }
-                    })
-                    .map(|_| {
-                        let ssid = ac.ssid.clone();
+                        debug!("Wired devices changed: {:?}", wired_devices);
+                        Some(
+                            if wired_devices
+                                .iter()
+                                .map(|wired_device| &wired_device.path)
+                                .eq(wired_paths.iter())
+                            {
+                                NetworkEvent::WiredDevices(wired_devices)
+                            } else {
+                                NetworkEvent::WiredDeviceList(wired_devices)
+                            },
+                        )
+                    }
+                }
+            })
+            .boxed();
 
-                        debug!("Request password for ssid {}", ssid);
-                        NetworkEvent::RequestPasswordForSSID(ssid)
-                    }),
-            );
-        }
+        let wireless_ac = nm.wireless_access_points().await?;
 
         // When devices list change I need to update the access points changes
         let mut ac_changes = Vec::with_capacity(wireless_ac.len());
@@ -503,25 +1020,103 @@ impl NetworkService {
             })
             .boxed();
 
+        let wwan_enabled = nm
+            .receive_wwan_enabled_changed()
+            .await
+            .then(|v| async move {
+                let value = v.get().await.unwrap_or_default();
+
+                debug!("WWAN enabled changed: {}", value);
+                NetworkEvent::WwanEnabled(value)
+            })
+            .boxed();
+
+        let modem_paths = modem_manager::modem_paths(conn).await.unwrap_or_default();
+
+        let modem_devices_changed = match modem_manager::modem_devices_changed(conn).await {
+            Ok(changes) => changes
+                .then({
+                    let conn = conn.clone();
+                    move |_| {
+                        let conn = conn.clone();
+                        async move {
+                            let modems = modem_manager::modems(&conn).await.unwrap_or_default();
+
+                            debug!("Modem devices changed: {:?}", modems);
+                            NetworkEvent::ModemDevices(modems)
+                        }
+                    }
+                })
+                .boxed(),
+            Err(_) => pending().boxed(),
+        };
+
+        let modem_changes = modem_manager::modem_properties_changed(conn, &modem_paths)
+            .await?
+            .then({
+                let conn = conn.clone();
+                move |_| {
+                    let conn = conn.clone();
+                    async move {
+                        let modems = modem_manager::modems(&conn).await.unwrap_or_default();
+
+                        debug!("Modems changed: {:?}", modems);
+                        NetworkEvent::Modems(modems)
+                    }
+                }
+            })
+            .boxed();
+
+        let secrets_requests = match SecretAgent::listen(conn).await {
+            Ok(requests) => requests.boxed(),
+            Err(err) => {
+                error!("Failed to register network secret agent: {}", err);
+
+                pending().boxed()
+            }
+        };
+
+        let rfkill_changes = match rfkill::listen() {
+            Ok(changes) => changes
+                .map(|rfkill| {
+                    let airplane_mode = rfkill.airplane_mode();
+
+                    debug!("Rfkill changed, airplane mode: {}", airplane_mode);
+                    NetworkEvent::AirplaneMode(airplane_mode)
+                })
+                .boxed(),
+            Err(err) => {
+                error!("Failed to listen for rfkill events: {}", err);
+
+                pending().boxed()
+            }
+        };
+
         let events = select_all(vec![
             wireless_enabled,
             wireless_devices_changed,
             connectivity_changed,
             active_connections_changes,
+            active_connection_state_changes,
+            wired_devices_changed,
             access_points,
             strength_changes,
             known_connections,
+            wwan_enabled,
+            modem_devices_changed,
+            modem_changes,
+            secrets_requests,
+            rfkill_changes,
         ]);
 
         Ok(events)
     }
 
     async fn set_airplane_mode(conn: &zbus::Connection, airplane_mode: bool) -> anyhow::Result<()> {
-        Command::new("/usr/sbin/rfkill")
-            .arg(if airplane_mode { "block" } else { "unblock" })
-            .arg("bluetooth")
-            .output()
-            .await?;
+        // NetworkManager disabling the wireless does not block the radio
+        rfkill::set_soft_block(RfkillType::Wlan, airplane_mode)?;
+        rfkill::set_soft_block(RfkillType::Bluetooth, airplane_mode)?;
+        rfkill::set_soft_block(RfkillType::Wwan, airplane_mode)?;
 
         let nm = NetworkDbus::new(conn).await?;
         nm.set_wireless_enabled(!airplane_mode).await?;
@@ -545,6 +1140,13 @@ impl NetworkService {
         Ok(())
     }
 
+    async fn set_wwan_enabled(conn: &zbus::Connection, enabled: bool) -> anyhow::Result<()> {
+        let nm = NetworkDbus::new(conn).await?;
+        nm.set_wwan_enabled(enabled).await?;
+
+        Ok(())
+    }
+
     async fn set_wifi_enabled(conn: &zbus::Connection, enabled: bool) -> anyhow::Result<()> {
         let nm = NetworkDbus::new(conn).await?;
         nm.set_wireless_enabled(enabled).await?;
@@ -565,6 +1167,74 @@ impl NetworkService {
         Ok(known_connections)
     }
 
+    async fn activate_wired_profile(
+        conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        profile: Option<OwnedObjectPath>,
+    ) -> anyhow::Result<Vec<WiredDevice>> {
+        let nm = NetworkDbus::new(conn).await?;
+        nm.activate_wired_profile(device, profile).await?;
+
+        nm.wired_devices().await
+    }
+
+    async fn disconnect_device(
+        conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> anyhow::Result<Vec<WiredDevice>> {
+        let nm = NetworkDbus::new(conn).await?;
+        nm.disconnect_device(device).await?;
+
+        nm.wired_devices().await
+    }
+
+    async fn connection_details(
+        conn: &zbus::Connection,
+        access_point: &AccessPoint,
+    ) -> anyhow::Result<ConnectionDetails> {
+        let nm = NetworkDbus::new(conn).await?;
+
+        nm.connection_details(access_point).await
+    }
+
+    async fn forget_connection(
+        conn: &zbus::Connection,
+        name: &str,
+    ) -> anyhow::Result<Vec<KnownConnection>> {
+        let settings = NetworkSettingsDbus::new(conn).await?;
+        settings.delete_connection(name).await?;
+
+        let nm = NetworkDbus::new(conn).await?;
+        let wireless_ac = nm.wireless_access_points().await?;
+        let known_connections = nm.known_connections(&wireless_ac).await?;
+        Ok(known_connections)
+    }
+
+    async fn connect_to_network(
+        conn: &zbus::Connection,
+        connection: &NewWifiConnection,
+    ) -> anyhow::Result<Vec<KnownConnection>> {
+        let nm = NetworkDbus::new(conn).await?;
+        nm.add_wifi_connection(connection).await?;
+
+        let wireless_ac = nm.wireless_access_points().await?;
+        let known_connections = nm.known_connections(&wireless_ac).await?;
+        Ok(known_connections)
+    }
+
+    async fn import_vpn(
+        conn: &zbus::Connection,
+        path: &Path,
+    ) -> anyhow::Result<Vec<KnownConnection>> {
+        let settings = NetworkSettingsDbus::new(conn).await?;
+        settings.import_vpn(path).await?;
+
+        let nm = NetworkDbus::new(conn).await?;
+        let wireless_ac = nm.wireless_access_points().await?;
+        let known_connections = nm.known_connections(&wireless_ac).await?;
+        Ok(known_connections)
+    }
+
     async fn set_vpn(
         conn: &zbus::Connection,
         connection: OwnedObjectPath,
@@ -647,6 +1317,23 @@ impl Service for NetworkService {
                     |wifi_enabled| ServiceEvent::Update(NetworkEvent::WiFiEnabled(wifi_enabled)),
                 )
             }
+            NetworkCommand::ToggleMobileData => {
+                let conn = self.conn.clone();
+                let wwan_enabled = self.wwan_enabled;
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::set_wwan_enabled(&conn, !wwan_enabled).await;
+
+                        if res.is_ok() {
+                            !wwan_enabled
+                        } else {
+                            wwan_enabled
+                        }
+                    },
+                    |wwan_enabled| ServiceEvent::Update(NetworkEvent::WwanEnabled(wwan_enabled)),
+                )
+            }
             NetworkCommand::SelectAccessPoint((access_point, password)) => {
                 let conn = self.conn.clone();
 
@@ -666,11 +1353,12 @@ impl Service for NetworkService {
             NetworkCommand::ToggleVpn(vpn) => {
                 let conn = self.conn.clone();
                 let mut active_vpn = self.active_connections.iter().find_map(|kc| match kc {
-                    ActiveConnectionInfo::Vpn { name, object_path } if name == &vpn.name => {
-                        Some(object_path.clone())
-                    }
+                    ActiveConnectionInfo::Vpn {
+                        name, object_path, ..
+                    } if name == &vpn.name => Some(object_path.clone()),
                     _ => None,
                 });
+                self.data.failed_vpn = None;
 
                 Task::perform(
                     async move {
@@ -690,6 +1378,124 @@ impl Service for NetworkService {
                     },
                 )
             }
+            NetworkCommand::ImportVpn(path) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::import_vpn(&conn, &path).await;
+
+                        res.map_err(|err| {
+                            error!("Failed to import VPN from {}: {}", path.display(), err);
+
+                            err.to_string()
+                        })
+                    },
+                    |res| ServiceEvent::Update(NetworkEvent::VpnImported(res)),
+                )
+            }
+            NetworkCommand::ActivateWiredProfile(device, profile) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res =
+                            NetworkService::activate_wired_profile(&conn, device, profile).await;
+
+                        if let Err(err) = &res {
+                            error!("Failed to activate wired connection: {}", err);
+                        }
+
+                        res.unwrap_or_default()
+                    },
+                    |wired_devices| ServiceEvent::Update(NetworkEvent::WiredDevices(wired_devices)),
+                )
+            }
+            NetworkCommand::DisconnectDevice(device) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::disconnect_device(&conn, device).await;
+
+                        if let Err(err) = &res {
+                            error!("Failed to disconnect device: {}", err);
+                        }
+
+                        res.unwrap_or_default()
+                    },
+                    |wired_devices| ServiceEvent::Update(NetworkEvent::WiredDevices(wired_devices)),
+                )
+            }
+            NetworkCommand::GetConnectionDetails(access_point) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::connection_details(&conn, &access_point).await;
+
+                        if let Err(err) = &res {
+                            error!("Failed to get connection details: {}", err);
+                        }
+
+                        res.ok()
+                    },
+                    |details| ServiceEvent::Update(NetworkEvent::ConnectionDetails(details)),
+                )
+            }
+            NetworkCommand::ConnectToNetwork(connection) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::connect_to_network(&conn, &connection).await;
+
+                        if let Err(err) = &res {
+                            error!("Failed to connect to {}: {}", connection.ssid, err);
+                        }
+
+                        res.unwrap_or_default()
+                    },
+                    |known_connections| {
+                        ServiceEvent::Update(NetworkEvent::KnownConnections(known_connections))
+                    },
+                )
+            }
+            NetworkCommand::ProvideSecrets(request, secret) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        if let Err(err) = SecretAgent::reply(&conn, &request, secret).await {
+                            error!("Failed to provide secrets for {}: {}", request.name, err);
+                        }
+
+                        request
+                    },
+                    |request| {
+                        ServiceEvent::Update(NetworkEvent::SecretsRequestClosed(
+                            request.connection_path,
+                            request.setting_name,
+                        ))
+                    },
+                )
+            }
+            NetworkCommand::ForgetConnection(name) => {
+                let conn = self.conn.clone();
+
+                Task::perform(
+                    async move {
+                        let res = NetworkService::forget_connection(&conn, &name).await;
+
+                        debug!("Connection forgotten: {:?}", res);
+
+                        res.unwrap_or_default()
+                    },
+                    |known_connections| {
+                        ServiceEvent::Update(NetworkEvent::KnownConnections(known_connections))
+                    },
+                )
+            }
         }
     }
 }
@@ -0,0 +1,154 @@
+use super::{AccessTechnology, ModemInfo};
+use iced::futures::{stream::select_all, Stream, StreamExt};
+use zbus::{fdo::ObjectManagerProxy, proxy, zvariant::OwnedObjectPath, Result};
+
+const MODEM_MANAGER_SERVICE: &str = "org.freedesktop.ModemManager1";
+const MODEM_MANAGER_PATH: &str = "/org/freedesktop/ModemManager1";
+const MODEM_INTERFACE: &str = "org.freedesktop.ModemManager1.Modem";
+
+async fn object_manager(conn: &zbus::Connection) -> anyhow::Result<ObjectManagerProxy<'static>> {
+    let object_manager = ObjectManagerProxy::builder(conn)
+        .destination(MODEM_MANAGER_SERVICE)?
+        .path(MODEM_MANAGER_PATH)?
+        .build()
+        .await?;
+
+    Ok(object_manager)
+}
+
+pub async fn modem_paths(conn: &zbus::Connection) -> anyhow::Result<Vec<OwnedObjectPath>> {
+    let objects = object_manager(conn).await?.get_managed_objects().await?;
+
+    let mut paths = objects
+        .into_iter()
+        .filter(|(_, interfaces)| {
+            interfaces
+                .keys()
+                .any(|interface| interface.as_str() == MODEM_INTERFACE)
+        })
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    paths.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+    Ok(paths)
+}
+
+pub async fn modem_info(
+    conn: &zbus::Connection,
+    path: OwnedObjectPath,
+) -> anyhow::Result<ModemInfo> {
+    let modem = ModemProxy::builder(conn)
+        .path(path.clone())?
+        .build()
+        .await?;
+    let modem_3gpp = Modem3gppProxy::builder(conn)
+        .path(path.clone())?
+        .build()
+        .await?;
+
+    let (signal, _) = modem.signal_quality().await.unwrap_or_default();
+
+    Ok(ModemInfo {
+        path,
+        model: modem.model().await.unwrap_or_default(),
+        operator: modem_3gpp
+            .operator_name()
+            .await
+            .ok()
+            .filter(|operator| !operator.is_empty()),
+        signal: signal.min(100) as u8,
+        access_technology: AccessTechnology::from_flags(
+            modem.access_technologies().await.unwrap_or_default(),
+        ),
+    })
+}
+
+pub async fn modems(conn: &zbus::Connection) -> anyhow::Result<Vec<ModemInfo>> {
+    let mut modems = Vec::new();
+    for path in modem_paths(conn).await? {
+        modems.push(modem_info(conn, path).await?);
+    }
+
+    Ok(modems)
+}
+
+/// Emits every time a modem is added or removed
+pub async fn modem_devices_changed(
+    conn: &zbus::Connection,
+) -> anyhow::Result<impl Stream<Item = ()>> {
+    let object_manager = object_manager(conn).await?;
+
+    let added = object_manager.receive_interfaces_added().await?.map(|_| ());
+    let removed = object_manager
+        .receive_interfaces_removed()
+        .await?
+        .map(|_| ());
+
+    Ok(select_all(vec![added.boxed(), removed.boxed()]))
+}
+
+/// Emits every time the signal, the access technology or the operator of a modem changes
+pub async fn modem_properties_changed(
+    conn: &zbus::Connection,
+    paths: &[OwnedObjectPath],
+) -> anyhow::Result<impl Stream<Item = ()>> {
+    let mut changes = Vec::with_capacity(paths.len() * 3);
+    for path in paths {
+        let modem = ModemProxy::builder(conn)
+            .path(path.clone())?
+            .build()
+            .await?;
+        let modem_3gpp = Modem3gppProxy::builder(conn)
+            .path(path.clone())?
+            .build()
+            .await?;
+
+        changes.push(
+            modem
+                .receive_signal_quality_changed()
+                .await
+                .map(|_| ())
+                .boxed(),
+        );
+        changes.push(
+            modem
+                .receive_access_technologies_changed()
+                .await
+                .map(|_| ())
+                .boxed(),
+        );
+        changes.push(
+            modem_3gpp
+                .receive_operator_name_changed()
+                .await
+                .map(|_| ())
+                .boxed(),
+        );
+    }
+
+    Ok(select_all(changes))
+}
+
+#[proxy(
+    default_service = "org.freedesktop.ModemManager1",
+    interface = "org.freedesktop.ModemManager1.Modem"
+)]
+trait Modem {
+    #[zbus(property)]
+    fn model(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn signal_quality(&self) -> Result<(u32, bool)>;
+
+    #[zbus(property)]
+    fn access_technologies(&self) -> Result<u32>;
+}
+
+#[proxy(
+    default_service = "org.freedesktop.ModemManager1",
+    interface = "org.freedesktop.ModemManager1.Modem.Modem3gpp"
+)]
+trait Modem3gpp {
+    #[zbus(property)]
+    fn operator_name(&self) -> Result<String>;
+}
@@ -0,0 +1,232 @@
+use super::{
+    dbus::{get_str, AgentManagerProxy},
+    NetworkEvent, SecretsRequest,
+};
+use iced::futures::{
+    channel::{mpsc, oneshot},
+    Stream,
+};
+use log::{debug, info, warn};
+use std::{collections::HashMap, sync::Mutex};
+use zbus::{
+    interface,
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+    DBusError,
+};
+
+const SECRET_AGENT_PATH: &str = "/org/freedesktop/NetworkManager/SecretAgent";
+const SECRET_AGENT_IDENTIFIER: &str = "org.ashell.SecretAgent";
+
+// NM_SECRET_AGENT_GET_SECRETS_FLAG_ALLOW_INTERACTION
+const ALLOW_INTERACTION: u32 = 0x1;
+// NM_SECRET_AGENT_CAPABILITY_VPN_HINTS
+const CAPABILITY_VPN_HINTS: u32 = 0x1;
+
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "org.freedesktop.NetworkManager.SecretAgent")]
+pub enum SecretAgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    UserCanceled(String),
+    NoSecrets(String),
+}
+
+type Secrets = HashMap<String, HashMap<String, OwnedValue>>;
+type PendingRequests = HashMap<(String, String), oneshot::Sender<Option<String>>>;
+
+pub struct SecretAgent {
+    events: Mutex<mpsc::UnboundedSender<NetworkEvent>>,
+    pending: Mutex<PendingRequests>,
+}
+
+impl SecretAgent {
+    /// Serve the agent on the connection and register it in NetworkManager the first time,
+    /// returns the stream of secret requests for the current listener
+    pub async fn listen(
+        conn: &zbus::Connection,
+    ) -> anyhow::Result<impl Stream<Item = NetworkEvent>> {
+        let (tx, rx) = mpsc::unbounded();
+        let object_server = conn.object_server();
+
+        if let Ok(agent) = object_server
+            .interface::<_, SecretAgent>(SECRET_AGENT_PATH)
+            .await
+        {
+            *agent.get().await.events.lock().unwrap() = tx;
+        } else {
+            object_server
+                .at(
+                    SECRET_AGENT_PATH,
+                    SecretAgent {
+                        events: Mutex::new(tx),
+                        pending: Mutex::new(HashMap::new()),
+                    },
+                )
+                .await?;
+
+            AgentManagerProxy::new(conn)
+                .await?
+                .register_with_capabilities(SECRET_AGENT_IDENTIFIER, CAPABILITY_VPN_HINTS)
+                .await?;
+
+            info!("Network secret agent registered");
+        }
+
+        Ok(rx)
+    }
+
+    pub async fn reply(
+        conn: &zbus::Connection,
+        request: &SecretsRequest,
+        secret: Option<String>,
+    ) -> anyhow::Result<()> {
+        let agent = conn
+            .object_server()
+            .interface::<_, SecretAgent>(SECRET_AGENT_PATH)
+            .await?;
+
+        let sender = agent
+            .get()
+            .await
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&request.key());
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(secret);
+            }
+            None => {
+                warn!("No pending secrets request for {}", request.name);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn secret_key(connection: &Secrets, setting_name: &str, hints: &[String]) -> String {
+        match setting_name {
+            "802-11-wireless-security" => {
+                let key_mgmt = connection
+                    .get(setting_name)
+                    .and_then(|s| get_str(s, "key-mgmt"));
+
+                if key_mgmt.as_deref() == Some("none") {
+                    "wep-key0".to_string()
+                } else {
+                    "psk".to_string()
+                }
+            }
+            _ => hints
+                .iter()
+                .find(|hint| !hint.starts_with("x-vpn-message:"))
+                .cloned()
+                .unwrap_or_else(|| "password".to_string()),
+        }
+    }
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.SecretAgent")]
+impl SecretAgent {
+    async fn get_secrets(
+        &self,
+        connection: Secrets,
+        connection_path: OwnedObjectPath,
+        setting_name: String,
+        hints: Vec<String>,
+        flags: u32,
+    ) -> Result<Secrets, SecretAgentError> {
+        debug!(
+            "Secrets requested for {} {} {:?} {}",
+            connection_path.as_str(),
+            setting_name,
+            hints,
+            flags
+        );
+
+        if flags & ALLOW_INTERACTION == 0 {
+            return Err(SecretAgentError::NoSecrets(
+                "User interaction not allowed".to_string(),
+            ));
+        }
+
+        let name = connection
+            .get("connection")
+            .and_then(|s| get_str(s, "id"))
+            .unwrap_or_else(|| connection_path.to_string());
+        let key = SecretAgent::secret_key(&connection, &setting_name, &hints);
+        let request = SecretsRequest {
+            connection_path: connection_path.to_string(),
+            setting_name,
+            name,
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request.key(), tx);
+
+        if self
+            .events
+            .lock()
+            .unwrap()
+            .unbounded_send(NetworkEvent::SecretsRequested(request.clone()))
+            .is_err()
+        {
+            self.pending.lock().unwrap().remove(&request.key());
+
+            return Err(SecretAgentError::NoSecrets(
+                "No listener for secrets requests".to_string(),
+            ));
+        }
+
+        let secret = match rx.await {
+            Ok(Some(secret)) => secret,
+            _ => {
+                return Err(SecretAgentError::UserCanceled(
+                    "Secrets request canceled".to_string(),
+                ))
+            }
+        };
+
+        let secrets = if request.setting_name == "vpn" {
+            HashMap::from([(
+                "secrets".to_string(),
+                Value::from(HashMap::from([(key, secret)]))
+                    .try_to_owned()
+                    .map_err(zbus::Error::from)?,
+            )])
+        } else {
+            HashMap::from([(
+                key,
+                Value::from(secret)
+                    .try_to_owned()
+                    .map_err(zbus::Error::from)?,
+            )])
+        };
+
+        Ok(HashMap::from([(request.setting_name, secrets)]))
+    }
+
+    async fn cancel_get_secrets(&self, connection_path: OwnedObjectPath, setting_name: String) {
+        debug!(
+            "Secrets request canceled for {} {}",
+            connection_path.as_str(),
+            setting_name
+        );
+
+        let key = (connection_path.to_string(), setting_name);
+        if let Some(sender) = self.pending.lock().unwrap().remove(&key) {
+            let _ = sender.send(None);
+        }
+
+        let _ = self
+            .events
+            .lock()
+            .unwrap()
+            .unbounded_send(NetworkEvent::SecretsRequestClosed(key.0, key.1));
+    }
+
+    // Secrets are stored by NetworkManager itself, the agent doesn't keep any of them
+    async fn save_secrets(&self, _connection: Secrets, _connection_path: OwnedObjectPath) {}
+
+    async fn delete_secrets(&self, _connection: Secrets, _connection_path: OwnedObjectPath) {}
+}
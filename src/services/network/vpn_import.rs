@@ -0,0 +1,488 @@
+use anyhow::{anyhow, bail, Context};
+use log::warn;
+use std::{
+    collections::HashMap,
+    fs::{self, DirBuilder, OpenOptions, Permissions},
+    io::{ErrorKind, Write},
+    net::IpAddr,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+use zbus::zvariant::Value;
+
+pub type ConnectionSettings = HashMap<&'static str, HashMap<&'static str, Value<'static>>>;
+
+// NM_SETTING_SECRET_FLAG_NOT_SAVED, the secret agent asks for the password on every activation
+const SECRET_FLAG_NOT_SAVED: &str = "2";
+
+/// The settings of an imported file and the inline OpenVPN files written for it
+pub struct VpnImport {
+    pub settings: ConnectionSettings,
+    files: Vec<PathBuf>,
+}
+
+impl VpnImport {
+    /// Delete the written files, used when NetworkManager rejects the connection
+    pub fn remove_files(&self) {
+        for file in self.files.iter() {
+            if let Err(e) = fs::remove_file(file) {
+                warn!("Failed to remove {}: {}", file.display(), e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VpnFormat {
+    WireGuard,
+    OpenVpn,
+}
+
+/// Both use the `.conf` extension, the format is told by the content
+fn detect_format(content: &str) -> Option<VpnFormat> {
+    content.lines().find_map(|line| {
+        let line = line.trim();
+        let keyword = line
+            .split(|c: char| c.is_whitespace() || c == '=')
+            .next()
+            .unwrap_or_default();
+
+        if line == "[Interface]" || keyword == "PrivateKey" {
+            Some(VpnFormat::WireGuard)
+        } else if keyword == "client" || keyword == "remote" {
+            Some(VpnFormat::OpenVpn)
+        } else {
+            None
+        }
+    })
+}
+
+/// Build the NetworkManager settings of a WireGuard or an OpenVPN configuration file
+pub fn connection_settings(path: &Path) -> anyhow::Result<VpnImport> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("Invalid file name {}", path.display()))?;
+
+    let mut import = VpnImport {
+        settings: HashMap::new(),
+        files: Vec::new(),
+    };
+    let settings = match detect_format(&content) {
+        Some(VpnFormat::WireGuard) => wireguard(name, &content),
+        Some(VpnFormat::OpenVpn) => openvpn(
+            name,
+            path.parent().unwrap_or(Path::new("/")),
+            &content,
+            &mut import.files,
+        ),
+        None => bail!("Unsupported VPN file, expected a WireGuard or OpenVPN configuration"),
+    };
+
+    match settings {
+        Ok(settings) => {
+            import.settings = settings;
+            Ok(import)
+        }
+        Err(e) => {
+            import.remove_files();
+            Err(e)
+        }
+    }
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn wireguard(name: &str, content: &str) -> anyhow::Result<ConnectionSettings> {
+    let mut section = "";
+    let mut interface = HashMap::new();
+    let mut peers: Vec<HashMap<&str, Value>> = Vec::new();
+    let mut addresses = Vec::new();
+    let mut dns = Vec::new();
+    let mut dns_search = Vec::new();
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            if section == "Peer" {
+                peers.push(HashMap::new());
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("Invalid line: {}", line);
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        match (section, key) {
+            ("Interface", "PrivateKey") => {
+                interface.insert("private-key", Value::from(value.to_string()));
+            }
+            ("Interface", "ListenPort") => {
+                interface.insert("listen-port", Value::U32(value.parse()?));
+            }
+            ("Interface", "FwMark") => {
+                interface.insert("fwmark", Value::U32(value.parse()?));
+            }
+            ("Interface", "MTU") => {
+                interface.insert("mtu", Value::U32(value.parse()?));
+            }
+            ("Interface", "Address") => {
+                addresses.extend(list(value));
+            }
+            ("Interface", "DNS") => {
+                for entry in list(value) {
+                    match entry.parse::<IpAddr>() {
+                        Ok(ip) => dns.push(ip),
+                        Err(_) => dns_search.push(entry),
+                    }
+                }
+            }
+            ("Peer", key) => {
+                let peer = peers
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("Peer without section"))?;
+                match key {
+                    "PublicKey" => {
+                        peer.insert("public-key", Value::from(value.to_string()));
+                    }
+                    "PresharedKey" => {
+                        peer.insert("preshared-key", Value::from(value.to_string()));
+                        peer.insert("preshared-key-flags", Value::U32(0));
+                    }
+                    "Endpoint" => {
+                        peer.insert("endpoint", Value::from(value.to_string()));
+                    }
+                    "AllowedIPs" => {
+                        peer.insert("allowed-ips", Value::from(list(value)));
+                    }
+                    "PersistentKeepalive" => {
+                        peer.insert("persistent-keepalive", Value::U32(value.parse()?));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !interface.contains_key("private-key") {
+        bail!("Missing PrivateKey in the [Interface] section");
+    }
+    interface.insert("peers", Value::from(peers));
+
+    let mut ipv4_addresses = Vec::new();
+    let mut ipv6_addresses = Vec::new();
+    for address in addresses {
+        let (ip, prefix) = match address.split_once('/') {
+            Some((ip, prefix)) => (ip.parse::<IpAddr>()?, Some(prefix.parse::<u32>()?)),
+            None => (address.parse::<IpAddr>()?, None),
+        };
+        let address_data = |default_prefix| {
+            HashMap::from([
+                ("address", Value::from(ip.to_string())),
+                ("prefix", Value::U32(prefix.unwrap_or(default_prefix))),
+            ])
+        };
+
+        if ip.is_ipv4() {
+            ipv4_addresses.push(address_data(32));
+        } else {
+            ipv6_addresses.push(address_data(128));
+        }
+    }
+
+    let mut ipv4 = ip_settings(ipv4_addresses);
+    let mut ipv6 = ip_settings(ipv6_addresses);
+
+    // ipv4.dns is a list of addresses in network byte order, ipv6.dns a list of byte arrays
+    let ipv4_dns = dns
+        .iter()
+        .filter_map(|ip| match ip {
+            IpAddr::V4(ip) => Some(u32::from_ne_bytes(ip.octets())),
+            IpAddr::V6(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let ipv6_dns = dns
+        .iter()
+        .filter_map(|ip| match ip {
+            IpAddr::V6(ip) => Some(ip.octets().to_vec()),
+            IpAddr::V4(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if !ipv4_dns.is_empty() {
+        ipv4.insert("dns", Value::from(ipv4_dns));
+    }
+    if !ipv6_dns.is_empty() {
+        ipv6.insert("dns", Value::from(ipv6_dns));
+    }
+    if !dns_search.is_empty() {
+        ipv4.insert("dns-search", Value::from(dns_search));
+    }
+
+    // Interface names are limited to 15 characters
+    let interface_name = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .take(15)
+        .collect::<String>();
+
+    Ok(HashMap::from([
+        (
+            "connection",
+            connection(name, "wireguard", Some(interface_name)),
+        ),
+        ("wireguard", interface),
+        ("ipv4", ipv4),
+        ("ipv6", ipv6),
+    ]))
+}
+
+fn openvpn(
+    name: &str,
+    dir: &Path,
+    content: &str,
+    files: &mut Vec<PathBuf>,
+) -> anyhow::Result<ConnectionSettings> {
+    const INLINE_FILES: [(&str, &str); 5] = [
+        ("ca", "ca"),
+        ("cert", "cert"),
+        ("key", "key"),
+        ("tls-auth", "ta"),
+        ("tls-crypt", "tls-crypt"),
+    ];
+
+    let mut data = HashMap::new();
+    let mut remotes = Vec::new();
+    let mut password_auth = false;
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        // Inline files are written next to the ones NetworkManager itself imports
+        if let Some(tag) = line.strip_prefix('<').and_then(|l| l.strip_suffix('>')) {
+            let end = format!("</{}>", tag);
+            let block = lines
+                .by_ref()
+                .take_while(|l| l.trim() != end)
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if let Some((_, key)) = INLINE_FILES.iter().find(|(t, _)| *t == tag) {
+                let path = write_inline_file(name, tag, &block)?;
+                files.push(path.clone());
+                data.insert(key.to_string(), path.to_string_lossy().into_owned());
+            }
+            continue;
+        }
+
+        let mut args = line.split_whitespace();
+        let directive = args.next().unwrap_or_default();
+        let args = args.collect::<Vec<_>>();
+
+        match (directive, args.as_slice()) {
+            ("remote", [host, rest @ ..]) => {
+                remotes.push(
+                    std::iter::once(*host)
+                        .chain(rest.iter().take(2).copied())
+                        .collect::<Vec<_>>()
+                        .join(":"),
+                );
+            }
+            ("port", [port]) => {
+                data.insert("port".to_string(), port.to_string());
+            }
+            ("proto", [proto]) if proto.starts_with("tcp") => {
+                data.insert("proto-tcp".to_string(), "yes".to_string());
+            }
+            ("dev", [dev]) | ("dev-type", [dev]) => {
+                let dev_type = if dev.starts_with("tap") { "tap" } else { "tun" };
+                data.insert("dev-type".to_string(), dev_type.to_string());
+            }
+            ("tls-auth", [file, direction @ ..]) => {
+                data.insert("ta".to_string(), resolve(dir, file));
+                if let Some(direction) = direction.first() {
+                    data.insert("ta-dir".to_string(), direction.to_string());
+                }
+            }
+            ("key-direction", [direction]) => {
+                data.insert("ta-dir".to_string(), direction.to_string());
+            }
+            (directive, [file]) if INLINE_FILES.iter().any(|(t, _)| *t == directive) => {
+                let (_, key) = INLINE_FILES.iter().find(|(t, _)| *t == directive).unwrap();
+                data.insert(key.to_string(), resolve(dir, file));
+            }
+            ("auth-user-pass", _) => password_auth = true,
+            ("cipher", [cipher]) => {
+                data.insert("cipher".to_string(), cipher.to_string());
+            }
+            ("auth", [auth]) => {
+                data.insert("auth".to_string(), auth.to_string());
+            }
+            ("comp-lzo", mode) => {
+                let mode = mode.first().copied().unwrap_or("adaptive");
+                data.insert("comp-lzo".to_string(), mode.to_string());
+            }
+            ("remote-cert-tls", [peer]) => {
+                data.insert("remote-cert-tls".to_string(), peer.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if remotes.is_empty() {
+        bail!("Missing remote directive");
+    }
+    data.insert("remote".to_string(), remotes.join(", "));
+
+    let has_cert = data.contains_key("cert");
+    let connection_type = match (password_auth, has_cert) {
+        (true, true) => "password-tls",
+        (true, false) => "password",
+        _ => "tls",
+    };
+    data.insert("connection-type".to_string(), connection_type.to_string());
+    if password_auth {
+        data.insert(
+            "password-flags".to_string(),
+            SECRET_FLAG_NOT_SAVED.to_string(),
+        );
+    }
+    if has_cert && data.contains_key("key") {
+        data.insert(
+            "cert-pass-flags".to_string(),
+            SECRET_FLAG_NOT_SAVED.to_string(),
+        );
+    }
+
+    Ok(HashMap::from([
+        ("connection", connection(name, "vpn", None)),
+        (
+            "vpn",
+            HashMap::from([
+                (
+                    "service-type",
+                    Value::from("org.freedesktop.NetworkManager.openvpn"),
+                ),
+                ("data", Value::from(data)),
+            ]),
+        ),
+        ("ipv4", HashMap::from([("method", Value::from("auto"))])),
+        ("ipv6", HashMap::from([("method", Value::from("auto"))])),
+    ]))
+}
+
+fn connection(
+    name: &str,
+    connection_type: &'static str,
+    interface_name: Option<String>,
+) -> HashMap<&'static str, Value<'static>> {
+    let mut connection = HashMap::from([
+        ("id", Value::from(name.to_string())),
+        ("type", Value::from(connection_type)),
+        ("autoconnect", Value::Bool(false)),
+    ]);
+    if let Some(interface_name) = interface_name {
+        connection.insert("interface-name", Value::from(interface_name));
+    }
+
+    connection
+}
+
+fn ip_settings(
+    addresses: Vec<HashMap<&'static str, Value<'static>>>,
+) -> HashMap<&'static str, Value<'static>> {
+    if addresses.is_empty() {
+        HashMap::from([("method", Value::from("disabled"))])
+    } else {
+        HashMap::from([
+            ("method", Value::from("manual")),
+            ("address-data", Value::from(addresses)),
+        ])
+    }
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+fn resolve(dir: &Path, file: &str) -> String {
+    dir.join(file).to_string_lossy().into_owned()
+}
+
+/// Private keys are inlined too, the files are readable only by the user
+fn write_inline_file(name: &str, tag: &str, content: &str) -> anyhow::Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME not set")?;
+    let dir = Path::new(&home).join(".cert").join("nm-openvpn");
+    DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+    fs::set_permissions(&dir, Permissions::from_mode(0o700))?;
+
+    // never overwrite the files of a connection imported before with the same name
+    for suffix in std::iter::once(String::new()).chain((1..100).map(|n| format!("-{}", n))) {
+        let path = dir.join(format!("{}-{}{}.pem", name, tag, suffix));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path);
+
+        match file {
+            Ok(mut file) => {
+                file.write_all(format!("{}\n", content).as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    bail!("Too many {} files for {}", tag, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_wireguard() {
+        let content = "# home\n[Interface]\nPrivateKey = abc=\nAddress = 10.0.0.2/32\n";
+        assert_eq!(detect_format(content), Some(VpnFormat::WireGuard));
+    }
+
+    #[test]
+    fn detect_openvpn() {
+        let content = "# office\nclient\ndev tun\nremote vpn.example.com 1194\n";
+        assert_eq!(detect_format(content), Some(VpnFormat::OpenVpn));
+
+        let content = "dev tun\nremote vpn.example.com 1194 udp\n";
+        assert_eq!(detect_format(content), Some(VpnFormat::OpenVpn));
+    }
+
+    #[test]
+    fn detect_unknown() {
+        assert_eq!(detect_format("[connection]\nid=home\n"), None);
+    }
+}
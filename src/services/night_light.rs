@@ -0,0 +1,390 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Utc};
+use log::{debug, info, warn};
+use std::{
+    f64::consts::PI,
+    fs::File,
+    io::{Seek, Write},
+    os::fd::{AsFd, FromRawFd},
+};
+use wayland_client::{
+    protocol::{
+        wl_display::WlDisplay,
+        wl_output::WlOutput,
+        wl_registry::{self, WlRegistry},
+    },
+    Connection, Dispatch, DispatchError, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::gamma_control::v1::client::{
+    zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1,
+    zwlr_gamma_control_v1::{self, ZwlrGammaControlV1},
+};
+
+pub const NEUTRAL_TEMPERATURE: u32 = 6500;
+pub const MIN_TEMPERATURE: u32 = 2500;
+
+pub struct NightLightManager {
+    _connection: Connection,
+    _display: WlDisplay,
+    _registry: WlRegistry,
+    event_queue: EventQueue<NightLightManagerData>,
+    handle: QueueHandle<NightLightManagerData>,
+    data: NightLightManagerData,
+}
+
+impl NightLightManager {
+    pub fn new() -> Option<Self> {
+        let init = || -> anyhow::Result<Self> {
+            let connection = Connection::connect_to_env()?;
+            let display = connection.display();
+            let event_queue = connection.new_event_queue();
+            let handle = event_queue.handle();
+            let registry = display.get_registry(&handle, ());
+
+            let mut obj = Self {
+                _connection: connection,
+                _display: display,
+                _registry: registry,
+                event_queue,
+                handle,
+                data: NightLightManagerData::default(),
+            };
+
+            obj.roundtrip()?;
+
+            if obj.data.gamma_manager.is_none() {
+                anyhow::bail!("the compositor doesn't support wlr-gamma-control");
+            }
+
+            Ok(obj)
+        };
+
+        match init() {
+            Ok(obj) => Some(obj),
+            Err(err) => {
+                warn!("Failed to initialize night light: {}", err);
+                None
+            }
+        }
+    }
+
+    fn roundtrip(&mut self) -> anyhow::Result<usize, DispatchError> {
+        self.event_queue.roundtrip(&mut self.data)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.data.temperature.is_some()
+    }
+
+    /// Apply the color temperature to every output, the gamma ramps are
+    /// set as soon as the compositor sends the gamma size of each output
+    pub fn enable(&mut self, temperature: u32) {
+        self.data.temperature = Some(temperature);
+
+        for output in self.data.outputs.iter_mut() {
+            match (&output.control, output.gamma_size) {
+                (Some(control), Some(size)) => set_gamma(control, size, temperature),
+                (None, _) => output.create_control(&self.data.gamma_manager, &self.handle),
+                _ => {}
+            }
+        }
+
+        if let Err(err) = self.roundtrip() {
+            warn!("Failed to enable night light: {}", err);
+        } else {
+            info!("Night light enabled at {}K", temperature);
+        }
+    }
+
+    /// Destroying the gamma controls restores the original gamma tables
+    pub fn disable(&mut self) {
+        self.data.temperature = None;
+
+        for output in self.data.outputs.iter_mut() {
+            output.destroy_control();
+        }
+
+        if let Err(err) = self.roundtrip() {
+            warn!("Failed to disable night light: {}", err);
+        } else {
+            info!("Night light disabled");
+        }
+    }
+
+    /// Process the pending events, e.g. outputs plugged in while the night light is on
+    pub fn refresh(&mut self) {
+        if let Err(err) = self.roundtrip() {
+            warn!("Failed to refresh night light outputs: {}", err);
+        }
+    }
+}
+
+struct GammaOutput {
+    name: u32,
+    output: WlOutput,
+    control: Option<ZwlrGammaControlV1>,
+    gamma_size: Option<u32>,
+}
+
+impl GammaOutput {
+    fn create_control(
+        &mut self,
+        gamma_manager: &Option<(ZwlrGammaControlManagerV1, u32)>,
+        handle: &QueueHandle<NightLightManagerData>,
+    ) {
+        if let Some((gamma_manager, _)) = gamma_manager {
+            self.control = Some(gamma_manager.get_gamma_control(&self.output, handle, self.name));
+        }
+    }
+
+    fn destroy_control(&mut self) {
+        if let Some(control) = self.control.take() {
+            control.destroy();
+        }
+        self.gamma_size = None;
+    }
+}
+
+/// Gamma tables are passed to the compositor through a file with the red,
+/// green and blue ramps of `size` u16 values each
+fn set_gamma(control: &ZwlrGammaControlV1, size: u32, temperature: u32) {
+    let write_ramps = || -> anyhow::Result<File> {
+        let fd = unsafe { libc::memfd_create(c"ashell-gamma".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let mut file = unsafe { File::from_raw_fd(fd) };
+
+        let (r, g, b) = temperature_to_rgb(temperature);
+        let size = size as usize;
+        let mut ramps = Vec::with_capacity(size * 3 * 2);
+        for factor in [r, g, b] {
+            for i in 0..size {
+                let value = i as f64 / (size.max(2) - 1) as f64 * factor;
+                ramps.extend_from_slice(&((value * u16::MAX as f64) as u16).to_ne_bytes());
+            }
+        }
+        file.write_all(&ramps)?;
+        file.rewind()?;
+
+        Ok(file)
+    };
+
+    match write_ramps() {
+        Ok(file) => control.set_gamma(file.as_fd()),
+        Err(err) => warn!("Failed to write gamma ramps: {}", err),
+    }
+}
+
+/// White point of a black body at the given temperature, approximation of
+/// the Planckian locus used by most blue light filters
+fn temperature_to_rgb(temperature: u32) -> (f64, f64, f64) {
+    let t = temperature.clamp(1000, 40000) as f64 / 100.;
+
+    let r = if t <= 66. {
+        255.
+    } else {
+        329.698727446 * (t - 60.).powf(-0.1332047592)
+    };
+    let g = if t <= 66. {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.).powf(-0.0755148492)
+    };
+    let b = if t >= 66. {
+        255.
+    } else if t <= 19. {
+        0.
+    } else {
+        138.5177312231 * (t - 10.).ln() - 305.0447927307
+    };
+
+    (
+        (r / 255.).clamp(0., 1.),
+        (g / 255.).clamp(0., 1.),
+        (b / 255.).clamp(0., 1.),
+    )
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SunTimes {
+    Daylight {
+        sunrise: DateTime<Local>,
+        sunset: DateTime<Local>,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+impl SunTimes {
+    /// Sunrise and sunset of the given day computed with the sunrise equation
+    pub fn new(date: NaiveDate, latitude: f64, longitude: f64) -> Self {
+        const J2000: f64 = 2451545.;
+        const UNIX_EPOCH_JULIAN: f64 = 2440587.5;
+
+        let to_radians = |degrees: f64| degrees * PI / 180.;
+        let noon = Utc
+            .with_ymd_and_hms(date.year(), date.month(), date.day(), 12, 0, 0)
+            .unwrap();
+
+        let day = (noon.timestamp() as f64 / 86400. + UNIX_EPOCH_JULIAN - J2000 + 0.0008).round();
+        let mean_solar_time = day - longitude / 360.;
+        let anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.);
+        let center = 1.9148 * to_radians(anomaly).sin()
+            + 0.02 * to_radians(2. * anomaly).sin()
+            + 0.0003 * to_radians(3. * anomaly).sin();
+        let ecliptic_longitude = (anomaly + center + 180. + 102.9372).rem_euclid(360.);
+        let transit = J2000 + mean_solar_time + 0.0053 * to_radians(anomaly).sin()
+            - 0.0069 * to_radians(2. * ecliptic_longitude).sin();
+
+        let declination_sin = to_radians(ecliptic_longitude).sin() * to_radians(23.4397).sin();
+        let declination_cos = declination_sin.asin().cos();
+        let hour_angle_cos = (to_radians(-0.833).sin()
+            - to_radians(latitude).sin() * declination_sin)
+            / (to_radians(latitude).cos() * declination_cos);
+
+        if hour_angle_cos < -1. {
+            return SunTimes::PolarDay;
+        } else if hour_angle_cos > 1. {
+            return SunTimes::PolarNight;
+        }
+
+        let hour_angle = hour_angle_cos.acos() * 180. / PI;
+        let to_local = |julian: f64| {
+            Local
+                .timestamp_opt(((julian - UNIX_EPOCH_JULIAN) * 86400.) as i64, 0)
+                .unwrap()
+        };
+
+        SunTimes::Daylight {
+            sunrise: to_local(transit - hour_angle / 360.),
+            sunset: to_local(transit + hour_angle / 360.),
+        }
+    }
+
+    pub fn is_night(&self, now: DateTime<Local>) -> bool {
+        match self {
+            SunTimes::Daylight { sunrise, sunset } => now < *sunrise || now >= *sunset,
+            SunTimes::PolarDay => false,
+            SunTimes::PolarNight => true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct NightLightManagerData {
+    gamma_manager: Option<(ZwlrGammaControlManagerV1, u32)>,
+    outputs: Vec<GammaOutput>,
+    temperature: Option<u32>,
+}
+
+impl Dispatch<WlRegistry, ()> for NightLightManagerData {
+    fn event(
+        state: &mut Self,
+        proxy: &WlRegistry,
+        event: <WlRegistry as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &wayland_client::Connection,
+        handle: &wayland_client::QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } => {
+                if interface == WlOutput::interface().name {
+                    debug!(target: "NightLight::WlRegistry::Event::Global", "Adding Output with name {name} and version {version}");
+                    let mut output = GammaOutput {
+                        name,
+                        output: proxy.bind(name, version.min(4), handle, ()),
+                        control: None,
+                        gamma_size: None,
+                    };
+                    if state.temperature.is_some() {
+                        output.create_control(&state.gamma_manager, handle);
+                    }
+
+                    state.outputs.push(output);
+                } else if interface == ZwlrGammaControlManagerV1::interface().name
+                    && state.gamma_manager.is_none()
+                {
+                    debug!(target: "NightLight::WlRegistry::Event::Global", "Adding GammaControlManager with name {name} and version {version}");
+                    state.gamma_manager = Some((proxy.bind(name, version, handle, ()), name));
+                }
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state.outputs.iter().position(|o| o.name == name) {
+                    debug!(target: "NightLight::GlobalRemove", "Output {name} was removed");
+                    let mut output = state.outputs.remove(index);
+                    output.destroy_control();
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+                } else if let Some((_, gamma_manager_name)) = &state.gamma_manager {
+                    if name == *gamma_manager_name {
+                        warn!(target: "NightLight::GlobalRemove", "GammaControlManager was removed!");
+
+                        state.gamma_manager = None;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for NightLightManagerData {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlOutput,
+        _event: <WlOutput as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrGammaControlManagerV1, ()> for NightLightManagerData {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwlrGammaControlManagerV1,
+        _event: <ZwlrGammaControlManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+    } // This interface has no events.
+}
+
+impl Dispatch<ZwlrGammaControlV1, u32> for NightLightManagerData {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrGammaControlV1,
+        event: <ZwlrGammaControlV1 as Proxy>::Event,
+        output_name: &u32,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.outputs.iter_mut().find(|o| o.name == *output_name) else {
+            return;
+        };
+
+        match event {
+            zwlr_gamma_control_v1::Event::GammaSize { size } => {
+                debug!(target: "NightLight::GammaControl", "Output {output_name} has gamma size {size}");
+                output.gamma_size = Some(size);
+
+                if let Some(temperature) = state.temperature {
+                    set_gamma(proxy, size, temperature);
+                }
+            }
+            zwlr_gamma_control_v1::Event::Failed => {
+                // another client owns the gamma tables or the output doesn't support them
+                warn!(target: "NightLight::GammaControl", "Gamma control failed for output {output_name}");
+                output.destroy_control();
+            }
+            _ => {}
+        }
+    }
+}
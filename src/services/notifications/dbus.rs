@@ -0,0 +1,129 @@
+use super::{Notification, NotificationEvent};
+use crate::utils::notifications::Urgency;
+use chrono::Local;
+use iced::futures::channel::mpsc::UnboundedSender;
+use std::collections::HashMap;
+use zbus::{interface, object_server::SignalEmitter, zvariant::OwnedValue};
+
+pub const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+pub const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Reasons of the `NotificationClosed` signal defined by the specification
+pub const CLOSED_DISMISSED: u32 = 2;
+pub const CLOSED_BY_CALL: u32 = 3;
+
+pub struct NotificationsServer {
+    next_id: u32,
+    sender: UnboundedSender<NotificationEvent>,
+}
+
+impl NotificationsServer {
+    pub fn new(sender: UnboundedSender<NotificationEvent>) -> Self {
+        Self { next_id: 1, sender }
+    }
+}
+
+/// Drop the markup some clients send even when the server doesn't advertise it
+fn strip_markup(body: &str) -> String {
+    let mut text = String::with_capacity(body.len());
+    let mut in_tag = false;
+
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl NotificationsServer {
+    fn get_capabilities(&self) -> Vec<&str> {
+        vec!["actions", "body", "persistence"]
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &mut self,
+        app_name: String,
+        replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        let id = if replaces_id > 0 {
+            replaces_id
+        } else {
+            let id = self.next_id;
+            self.next_id = self.next_id.wrapping_add(1).max(1);
+            id
+        };
+
+        let urgency = hints
+            .get("urgency")
+            .and_then(|value| u8::try_from(value).ok())
+            .map(Urgency::from)
+            .unwrap_or(Urgency::Normal);
+
+        // actions come as a flat list of key and label pairs
+        let actions = actions
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+
+        let _ = self
+            .sender
+            .unbounded_send(NotificationEvent::Received(Notification {
+                id,
+                app_name,
+                summary,
+                body: strip_markup(&body),
+                actions,
+                urgency,
+                expire_timeout,
+                timestamp: Local::now(),
+            }));
+
+        id
+    }
+
+    async fn close_notification(
+        &self,
+        id: u32,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        let _ = self.sender.unbounded_send(NotificationEvent::Closed(id));
+        Self::notification_closed(&emitter, id, CLOSED_BY_CALL).await?;
+
+        Ok(())
+    }
+
+    fn get_server_information(&self) -> (&str, &str, &str, &str) {
+        ("ashell", "ashell", env!("CARGO_PKG_VERSION"), "1.2")
+    }
+
+    #[zbus(signal)]
+    pub async fn notification_closed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn action_invoked(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+}
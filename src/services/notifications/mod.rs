@@ -0,0 +1,241 @@
+use super::{ReadOnlyService, Service, ServiceEvent};
+use crate::utils::notifications::Urgency;
+use chrono::{DateTime, Local};
+use dbus::{NotificationsServer, CLOSED_DISMISSED, NOTIFICATIONS_NAME, NOTIFICATIONS_PATH};
+use iced::{
+    futures::{
+        channel::mpsc::{unbounded, Sender, UnboundedReceiver, UnboundedSender},
+        stream::pending,
+        SinkExt, StreamExt,
+    },
+    stream::channel,
+    Subscription, Task,
+};
+use log::{debug, error, info, warn};
+use std::{any::TypeId, ops::Deref, time::Duration};
+use zbus::fdo::{RequestNameFlags, RequestNameReply};
+
+mod dbus;
+
+const HISTORY_LENGTH: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u32,
+    pub app_name: String,
+    pub summary: String,
+    pub body: String,
+    /// Pairs of action key and label
+    pub actions: Vec<(String, String)>,
+    pub urgency: Urgency,
+    pub expire_timeout: i32,
+    pub timestamp: DateTime<Local>,
+}
+
+impl Notification {
+    /// How long the popup stays on screen, `None` until dismissed
+    pub fn timeout(&self, default: Duration) -> Option<Duration> {
+        match self.expire_timeout {
+            // critical notifications ignore the server default
+            -1 if self.urgency == Urgency::Critical => None,
+            -1 => Some(default),
+            0 => None,
+            timeout => Some(Duration::from_millis(timeout.unsigned_abs() as u64)),
+        }
+    }
+
+    /// Whether clicking the notification itself invokes an action
+    pub fn has_default_action(&self) -> bool {
+        self.actions.iter().any(|(key, _)| key == "default")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NotificationsData {
+    /// Newest first
+    pub notifications: Vec<Notification>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationsService {
+    data: NotificationsData,
+    conn: zbus::Connection,
+}
+
+impl Deref for NotificationsService {
+    type Target = NotificationsData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl NotificationsService {
+    async fn start_server(
+        sender: UnboundedSender<NotificationEvent>,
+    ) -> anyhow::Result<zbus::Connection> {
+        let conn = zbus::connection::Builder::session()?
+            .serve_at(NOTIFICATIONS_PATH, NotificationsServer::new(sender))?
+            .build()
+            .await?;
+
+        match conn
+            .request_name_with_flags(NOTIFICATIONS_NAME, RequestNameFlags::DoNotQueue.into())
+            .await?
+        {
+            RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => Ok(conn),
+            _ => Err(anyhow::anyhow!(
+                "{} is owned by another notification daemon",
+                NOTIFICATIONS_NAME
+            )),
+        }
+    }
+
+    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+        match state {
+            State::Init => {
+                let (sender, receiver) = unbounded();
+
+                match Self::start_server(sender).await {
+                    Ok(conn) => {
+                        let _ = output
+                            .send(ServiceEvent::Init(NotificationsService {
+                                data: NotificationsData::default(),
+                                conn,
+                            }))
+                            .await;
+
+                        State::Active(receiver)
+                    }
+                    Err(err) => {
+                        warn!("Failed to start the notification daemon: {}", err);
+
+                        State::Error
+                    }
+                }
+            }
+            State::Active(mut receiver) => {
+                info!("Listening for notifications");
+
+                while let Some(event) = receiver.next().await {
+                    let _ = output.send(ServiceEvent::Update(event)).await;
+                }
+
+                State::Error
+            }
+            State::Error => {
+                let _ = pending::<u8>().next().await;
+
+                State::Error
+            }
+        }
+    }
+
+    async fn emit(conn: &zbus::Connection, command: NotificationsCommand) -> anyhow::Result<()> {
+        let server = conn
+            .object_server()
+            .interface::<_, NotificationsServer>(NOTIFICATIONS_PATH)
+            .await?;
+        let emitter = server.signal_emitter();
+
+        match command {
+            NotificationsCommand::Dismiss(id) => {
+                NotificationsServer::notification_closed(emitter, id, CLOSED_DISMISSED).await?;
+            }
+            NotificationsCommand::DismissAll(ids) => {
+                for id in ids {
+                    NotificationsServer::notification_closed(emitter, id, CLOSED_DISMISSED).await?;
+                }
+            }
+            NotificationsCommand::InvokeAction(id, key) => {
+                NotificationsServer::action_invoked(emitter, id, &key).await?;
+                NotificationsServer::notification_closed(emitter, id, CLOSED_DISMISSED).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    Init,
+    Active(UnboundedReceiver<NotificationEvent>),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    Received(Notification),
+    Closed(u32),
+    Cleared,
+}
+
+impl ReadOnlyService for NotificationsService {
+    type UpdateEvent = NotificationEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        let notifications = &mut self.data.notifications;
+
+        match event {
+            NotificationEvent::Received(notification) => {
+                match notifications.iter_mut().find(|n| n.id == notification.id) {
+                    Some(current) => *current = notification,
+                    None => {
+                        notifications.insert(0, notification);
+                        notifications.truncate(HISTORY_LENGTH);
+                    }
+                }
+            }
+            NotificationEvent::Closed(id) => notifications.retain(|n| n.id != id),
+            NotificationEvent::Cleared => notifications.clear(),
+        }
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(100, |mut output| async move {
+                let mut state = State::Init;
+
+                loop {
+                    state = NotificationsService::start_listening(state, &mut output).await;
+                }
+            }),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationsCommand {
+    Dismiss(u32),
+    DismissAll(Vec<u32>),
+    InvokeAction(u32, String),
+}
+
+impl Service for NotificationsService {
+    type Command = NotificationsCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        let conn = self.conn.clone();
+        // the history drops the notification even when the client can't be told
+        let event = match &command {
+            NotificationsCommand::Dismiss(id) | NotificationsCommand::InvokeAction(id, _) => {
+                NotificationEvent::Closed(*id)
+            }
+            NotificationsCommand::DismissAll(_) => NotificationEvent::Cleared,
+        };
+
+        Task::perform(
+            async move {
+                debug!("Notifications command: {:?}", command);
+                if let Err(err) = Self::emit(&conn, command).await {
+                    error!("Failed to emit notification signal: {}", err);
+                }
+            },
+            move |_| ServiceEvent::Update(event.clone()),
+        )
+    }
+}
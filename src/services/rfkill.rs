@@ -0,0 +1,196 @@
+use iced::futures::{stream::unfold, Stream};
+use log::{debug, warn};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    os::unix::fs::OpenOptionsExt,
+};
+use tokio::io::unix::AsyncFd;
+
+const RFKILL_DEVICE_PATH: &str = "/dev/rfkill";
+// Size of the original `struct rfkill_event`, newer kernels append fields
+// but always return the old layout to readers asking for these bytes
+const RFKILL_EVENT_SIZE: usize = 8;
+
+const RFKILL_OP_ADD: u8 = 0;
+const RFKILL_OP_DEL: u8 = 1;
+const RFKILL_OP_CHANGE: u8 = 2;
+const RFKILL_OP_CHANGE_ALL: u8 = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RfkillType {
+    Wlan,
+    Bluetooth,
+    Wwan,
+}
+
+impl RfkillType {
+    fn from_raw(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(RfkillType::Wlan),
+            2 => Some(RfkillType::Bluetooth),
+            5 => Some(RfkillType::Wwan),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> u8 {
+        match self {
+            RfkillType::Wlan => 1,
+            RfkillType::Bluetooth => 2,
+            RfkillType::Wwan => 5,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RfkillBlock {
+    pub soft: bool,
+    pub hard: bool,
+}
+
+impl RfkillBlock {
+    pub fn blocked(&self) -> bool {
+        self.soft || self.hard
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct RfkillDevice {
+    kind: RfkillType,
+    block: RfkillBlock,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RfkillState {
+    devices: HashMap<u32, RfkillDevice>,
+}
+
+impl RfkillState {
+    /// Block state of a radio type, a type is blocked when any of its devices is,
+    /// `None` when no device of the type exists
+    pub fn get(&self, kind: RfkillType) -> Option<RfkillBlock> {
+        self.devices
+            .values()
+            .filter(|device| device.kind == kind)
+            .map(|device| device.block)
+            .reduce(|a, b| RfkillBlock {
+                soft: a.soft || b.soft,
+                hard: a.hard || b.hard,
+            })
+    }
+
+    pub fn soft_blocked(&self, kind: RfkillType) -> bool {
+        self.get(kind).is_some_and(|block| block.soft)
+    }
+
+    pub fn blocked(&self, kind: RfkillType) -> bool {
+        self.get(kind).is_some_and(|block| block.blocked())
+    }
+
+    /// Airplane mode is on when every Bluetooth, WLAN and WWAN radio present is blocked
+    pub fn airplane_mode(&self) -> bool {
+        !self.devices.is_empty() && self.devices.values().all(|device| device.block.blocked())
+    }
+
+    fn apply(&mut self, event: &[u8; RFKILL_EVENT_SIZE]) {
+        let idx = u32::from_ne_bytes([event[0], event[1], event[2], event[3]]);
+        let Some(kind) = RfkillType::from_raw(event[4]) else {
+            return;
+        };
+        let block = RfkillBlock {
+            soft: event[6] != 0,
+            hard: event[7] != 0,
+        };
+
+        match event[5] {
+            RFKILL_OP_ADD | RFKILL_OP_CHANGE => {
+                self.devices.insert(idx, RfkillDevice { kind, block });
+            }
+            RFKILL_OP_DEL => {
+                self.devices.remove(&idx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Read all the queued events, returns once the device has nothing more to read
+    fn read_events(&mut self, file: &mut File) -> std::io::Result<()> {
+        let mut event = [0; RFKILL_EVENT_SIZE];
+
+        loop {
+            match file.read(&mut event) {
+                Ok(RFKILL_EVENT_SIZE) => self.apply(&event),
+                Ok(0) => return Ok(()),
+                Ok(size) => {
+                    warn!("Unexpected rfkill event size: {}", size);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+fn open(write: bool) -> std::io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(write)
+        .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+        .open(RFKILL_DEVICE_PATH)
+}
+
+/// Current block state of every radio, the kernel sends an add event for each
+/// existing device as soon as /dev/rfkill is opened
+pub fn state() -> anyhow::Result<RfkillState> {
+    let mut file = open(false)?;
+    let mut state = RfkillState::default();
+    state.read_events(&mut file)?;
+
+    Ok(state)
+}
+
+/// Emits the whole block state every time a radio is added, removed or changes state
+pub fn listen() -> anyhow::Result<impl Stream<Item = RfkillState>> {
+    let mut file = open(false)?;
+    let mut state = RfkillState::default();
+    state.read_events(&mut file)?;
+
+    let fd = AsyncFd::new(file)?;
+
+    Ok(unfold((fd, state), |(mut fd, mut state)| async move {
+        let mut guard = match fd.readable_mut().await {
+            Ok(guard) => guard,
+            Err(err) => {
+                warn!("Failed to wait for rfkill events: {}", err);
+                return None;
+            }
+        };
+
+        if let Err(err) = state.read_events(guard.get_inner_mut()) {
+            warn!("Failed to read rfkill events: {}", err);
+            return None;
+        }
+        guard.clear_ready();
+        drop(guard);
+
+        debug!("Rfkill state changed: {:?}", state);
+        Some((state.clone(), (fd, state)))
+    }))
+}
+
+/// Soft block or unblock every device of a radio type
+pub fn set_soft_block(kind: RfkillType, blocked: bool) -> anyhow::Result<()> {
+    let mut file = open(true)?;
+
+    let mut event = [0; RFKILL_EVENT_SIZE];
+    event[4] = kind.raw();
+    event[5] = RFKILL_OP_CHANGE_ALL;
+    event[6] = blocked as u8;
+
+    file.write_all(&event)?;
+
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use iced::futures::{stream_select, Stream, StreamExt};
+use std::collections::HashMap;
+use zbus::{
+    fdo::DBusProxy,
+    interface,
+    message::Header,
+    object_server::SignalEmitter,
+    proxy,
+    proxy::CacheProperties,
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+    MatchRule, MessageStream,
+};
+
+pub const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+pub const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+pub const ITEM_PATH: &str = "/StatusNotifierItem";
+
+/// The `StatusNotifierWatcher` served when no other watcher is running
+#[derive(Debug, Default)]
+pub struct Watcher {
+    items: Vec<String>,
+    hosts: Vec<String>,
+}
+
+impl Watcher {
+    /// Forget the items and hosts of a bus name that left the bus
+    pub async fn remove_name(
+        &mut self,
+        emitter: &SignalEmitter<'_>,
+        name: &str,
+    ) -> zbus::Result<()> {
+        let (removed, items) = self
+            .items
+            .drain(..)
+            .partition::<Vec<_>, _>(|item| item_destination(item).0 == name);
+        self.items = items;
+
+        for item in removed.iter() {
+            Self::status_notifier_item_unregistered(emitter, item).await?;
+        }
+        if !removed.is_empty() {
+            self.registered_status_notifier_items_changed(emitter)
+                .await?;
+        }
+
+        let hosts = self.hosts.len();
+        self.hosts.retain(|host| host != name);
+        if self.hosts.len() != hosts && self.hosts.is_empty() {
+            Self::status_notifier_host_unregistered(emitter).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[interface(name = "org.kde.StatusNotifierWatcher")]
+impl Watcher {
+    async fn register_status_notifier_item(
+        &mut self,
+        service: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        // items register either their bus name or, like libappindicator, their object path
+        let item = if service.starts_with('/') {
+            let sender = header
+                .sender()
+                .ok_or_else(|| zbus::fdo::Error::InvalidArgs("missing sender".to_owned()))?;
+
+            format!("{}{}", sender, service)
+        } else {
+            format!("{}{}", service, ITEM_PATH)
+        };
+
+        if !self.items.contains(&item) {
+            self.items.push(item.clone());
+            Self::status_notifier_item_registered(&emitter, &item).await?;
+            self.registered_status_notifier_items_changed(&emitter)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn register_status_notifier_host(
+        &mut self,
+        service: &str,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        if !self.hosts.iter().any(|host| host == service) {
+            self.hosts.push(service.to_owned());
+            Self::status_notifier_host_registered(&emitter).await?;
+        }
+
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn registered_status_notifier_items(&self) -> Vec<String> {
+        self.items.clone()
+    }
+
+    #[zbus(property)]
+    fn is_status_notifier_host_registered(&self) -> bool {
+        !self.hosts.is_empty()
+    }
+
+    #[zbus(property)]
+    fn protocol_version(&self) -> i32 {
+        0
+    }
+
+    #[zbus(signal)]
+    async fn status_notifier_item_registered(
+        emitter: &SignalEmitter<'_>,
+        service: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn status_notifier_item_unregistered(
+        emitter: &SignalEmitter<'_>,
+        service: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn status_notifier_host_registered(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn status_notifier_host_unregistered(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+/// Split a registered item, `:1.42/org/ayatana/NotificationItem/foo`, in bus name and object path
+pub fn item_destination(item: &str) -> (&str, &str) {
+    match item.find('/') {
+        Some(index) => item.split_at(index),
+        None => (item, ITEM_PATH),
+    }
+}
+
+#[proxy(
+    interface = "org.kde.StatusNotifierWatcher",
+    default_service = "org.kde.StatusNotifierWatcher",
+    default_path = "/StatusNotifierWatcher"
+)]
+pub trait StatusNotifierWatcher {
+    fn register_status_notifier_host(&self, service: &str) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn registered_status_notifier_items(&self) -> zbus::Result<Vec<String>>;
+}
+
+pub async fn watcher(
+    conn: &zbus::Connection,
+) -> anyhow::Result<StatusNotifierWatcherProxy<'static>> {
+    // the registered items change without PropertiesChanged from some watchers
+    let watcher = StatusNotifierWatcherProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    Ok(watcher)
+}
+
+#[proxy(interface = "org.kde.StatusNotifierItem")]
+pub trait StatusNotifierItem {
+    fn activate(&self, x: i32, y: i32) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn title(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn status(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn icon_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn icon_pixmap(&self) -> zbus::Result<Vec<(i32, i32, Vec<u8>)>>;
+
+    #[zbus(property)]
+    fn attention_icon_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn attention_icon_pixmap(&self) -> zbus::Result<Vec<(i32, i32, Vec<u8>)>>;
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn item_is_menu(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn menu(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+pub async fn item(
+    conn: &zbus::Connection,
+    item: &str,
+) -> anyhow::Result<StatusNotifierItemProxy<'static>> {
+    let (name, path) = item_destination(item);
+    let item = StatusNotifierItemProxy::builder(conn)
+        .destination(name.to_owned())?
+        .path(path.to_owned())?
+        .build()
+        .await?;
+
+    Ok(item)
+}
+
+/// Root of a menu layout, the children are `(ia{sv}av)` structures wrapped in variants
+pub type MenuLayout = (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>);
+
+#[proxy(interface = "com.canonical.dbusmenu")]
+pub trait DBusMenu {
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: &[&str],
+    ) -> zbus::Result<(u32, MenuLayout)>;
+
+    fn event(&self, id: i32, event_id: &str, data: &Value<'_>, timestamp: u32) -> zbus::Result<()>;
+
+    fn about_to_show(&self, id: i32) -> zbus::Result<bool>;
+}
+
+pub async fn menu(
+    conn: &zbus::Connection,
+    name: &str,
+    path: &OwnedObjectPath,
+) -> anyhow::Result<DBusMenuProxy<'static>> {
+    let menu = DBusMenuProxy::builder(conn)
+        .destination(name.to_owned())?
+        .path(path.clone())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    Ok(menu)
+}
+
+#[derive(Debug, Clone)]
+pub enum TrayChange {
+    /// An item registered or unregistered in the watcher
+    Registered,
+    /// An item, identified by the sender unique name and object path, changed its icon,
+    /// status or title
+    Item(String, String),
+    /// The dbusmenu of the sender unique name and object path changed
+    Menu(String, String),
+    /// A bus name left the bus, its items are gone
+    NameLost(String),
+}
+
+fn signal_source(message: zbus::Result<zbus::Message>) -> Option<(String, String)> {
+    let message = message.ok()?;
+    let header = message.header();
+
+    Some((header.sender()?.to_string(), header.path()?.to_string()))
+}
+
+/// Emits every time an item is registered, changes or its owner leaves the bus
+pub async fn items_changed(
+    conn: &zbus::Connection,
+) -> anyhow::Result<impl Stream<Item = TrayChange>> {
+    let dbus = DBusProxy::new(conn).await?;
+    let owners = dbus
+        .receive_name_owner_changed()
+        .await?
+        .filter_map(|signal| async move {
+            signal
+                .args()
+                .ok()
+                .filter(|args| args.new_owner.is_none())
+                .map(|args| TrayChange::NameLost(args.name.to_string()))
+        })
+        .boxed();
+
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.kde.StatusNotifierWatcher")?
+        .build();
+
+    let watcher = MessageStream::for_match_rule(rule, conn, None)
+        .await?
+        .map(|_| TrayChange::Registered);
+
+    // NewIcon, NewAttentionIcon, NewStatus, NewTitle and friends
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.kde.StatusNotifierItem")?
+        .build();
+
+    let items = MessageStream::for_match_rule(rule, conn, None)
+        .await?
+        .filter_map(|message| async move {
+            signal_source(message).map(|(sender, path)| TrayChange::Item(sender, path))
+        })
+        .boxed();
+
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("com.canonical.dbusmenu")?
+        .build();
+
+    let menus = MessageStream::for_match_rule(rule, conn, None)
+        .await?
+        .filter_map(|message| async move {
+            signal_source(message).map(|(sender, path)| TrayChange::Menu(sender, path))
+        })
+        .boxed();
+
+    Ok(stream_select!(owners, watcher, items, menus))
+}
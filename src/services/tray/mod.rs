@@ -0,0 +1,730 @@
+use super::{ReadOnlyService, Service, ServiceEvent};
+use dbus::{TrayChange, Watcher, WATCHER_NAME, WATCHER_PATH};
+use iced::{
+    futures::{channel::mpsc::Sender, stream::pending, SinkExt, StreamExt},
+    stream::channel,
+    widget::image,
+    Subscription, Task,
+};
+use log::{debug, error, info, warn};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    env,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+use zbus::{
+    fdo::{DBusProxy, RequestNameFlags, RequestNameReply},
+    zvariant::{OwnedObjectPath, Value},
+};
+
+mod dbus;
+
+/// Icon sizes tried in order, the bar shows icons at about 16-24px
+const ICON_SIZES: [&str; 10] = [
+    "scalable", "symbolic", "24x24", "22x22", "32x32", "16x16", "48x48", "64x64", "128x128",
+    "256x256",
+];
+const ICON_CATEGORIES: [&str; 4] = ["status", "apps", "devices", "panel"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrayItemStatus {
+    Passive,
+    Active,
+    NeedsAttention,
+}
+
+impl From<String> for TrayItemStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Passive" => TrayItemStatus::Passive,
+            "NeedsAttention" => TrayItemStatus::NeedsAttention,
+            _ => TrayItemStatus::Active,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TrayIcon {
+    /// A themed icon resolved to its file
+    File(PathBuf),
+    /// Decoded once, a new handle for each view would upload the pixels again,
+    /// `hash` lets a refresh keep the handle of an unchanged pixmap
+    Pixmap { handle: image::Handle, hash: u64 },
+}
+
+impl TrayIcon {
+    /// Pick the pixmap closest to the bar icon size and convert it from ARGB32 to RGBA
+    fn from_pixmaps(
+        pixmaps: Vec<(i32, i32, Vec<u8>)>,
+        previous: Option<&TrayIcon>,
+    ) -> Option<Self> {
+        let (width, height, argb) = pixmaps
+            .into_iter()
+            .filter(|(width, height, data)| {
+                *width > 0 && *height > 0 && data.len() == (*width * *height * 4) as usize
+            })
+            .min_by_key(|(width, _, _)| (width - 24).abs())?;
+
+        let mut hasher = DefaultHasher::new();
+        (width, height, &argb).hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if let Some(icon @ TrayIcon::Pixmap { hash: previous, .. }) = previous {
+            if *previous == hash {
+                return Some(icon.clone());
+            }
+        }
+
+        let rgba: Vec<u8> = argb
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[1], pixel[2], pixel[3], pixel[0]])
+            .collect();
+
+        Some(TrayIcon::Pixmap {
+            handle: image::Handle::from_rgba(width as u32, height as u32, rgba),
+            hash,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayMenuItem {
+    pub id: i32,
+    pub label: String,
+    pub enabled: bool,
+    pub visible: bool,
+    pub separator: bool,
+    /// Check state of checkmark and radio items
+    pub toggle: Option<bool>,
+    pub children: Vec<TrayMenuItem>,
+}
+
+/// Drop the access key markers, `_File` is shown as `File` and `__` as `_`
+fn strip_mnemonic(label: &str) -> String {
+    let mut text = String::with_capacity(label.len());
+    let mut chars = label.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '_' => text.extend(chars.next()),
+            c => text.push(c),
+        }
+    }
+
+    text
+}
+
+fn unwrap_variant<'a>(value: &'a Value<'a>) -> &'a Value<'a> {
+    match value {
+        Value::Value(value) => unwrap_variant(value),
+        value => value,
+    }
+}
+
+impl TrayMenuItem {
+    fn new<'a>(
+        id: i32,
+        properties: impl Iterator<Item = (&'a str, &'a Value<'a>)>,
+        children: impl Iterator<Item = &'a Value<'a>>,
+    ) -> Self {
+        let mut item = TrayMenuItem {
+            id,
+            label: String::new(),
+            enabled: true,
+            visible: true,
+            separator: false,
+            toggle: None,
+            children: children.filter_map(TrayMenuItem::from_value).collect(),
+        };
+        let mut toggle_type = false;
+        let mut toggle_state = false;
+
+        for (key, value) in properties {
+            match (key, unwrap_variant(value)) {
+                ("label", Value::Str(label)) => item.label = strip_mnemonic(label),
+                ("enabled", Value::Bool(enabled)) => item.enabled = *enabled,
+                ("visible", Value::Bool(visible)) => item.visible = *visible,
+                ("type", Value::Str(kind)) => item.separator = kind.as_str() == "separator",
+                ("toggle-type", Value::Str(kind)) => toggle_type = !kind.is_empty(),
+                ("toggle-state", Value::I32(state)) => toggle_state = *state == 1,
+                _ => {}
+            }
+        }
+        item.toggle = toggle_type.then_some(toggle_state);
+
+        item
+    }
+
+    fn from_value<'a>(value: &'a Value<'a>) -> Option<Self> {
+        let Value::Structure(structure) = unwrap_variant(value) else {
+            return None;
+        };
+        let [Value::I32(id), Value::Dict(properties), Value::Array(children)] = structure.fields()
+        else {
+            return None;
+        };
+
+        Some(TrayMenuItem::new(
+            *id,
+            properties.iter().filter_map(|(key, value)| match key {
+                Value::Str(key) => Some((key.as_str(), value)),
+                _ => None,
+            }),
+            children.iter(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayMenu {
+    pub path: OwnedObjectPath,
+    pub items: Vec<TrayMenuItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayItem {
+    /// Bus name and object path as registered in the watcher
+    pub address: String,
+    pub id: String,
+    pub title: Option<String>,
+    pub status: TrayItemStatus,
+    pub icon: Option<TrayIcon>,
+    pub item_is_menu: bool,
+    pub menu: Option<TrayMenu>,
+    /// Unique bus name of the item, the sender of its signals
+    owner: String,
+}
+
+impl TrayItem {
+    fn is_source(&self, sender: &str, path: &str) -> bool {
+        self.owner == sender && dbus::item_destination(&self.address).1 == path
+    }
+
+    fn is_menu_source(&self, sender: &str, path: &str) -> bool {
+        self.owner == sender
+            && self
+                .menu
+                .as_ref()
+                .is_some_and(|menu| menu.path.as_str() == path)
+    }
+
+    pub fn name(&self) -> String {
+        self.title
+            .clone()
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| self.id.clone())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrayData {
+    pub items: Vec<TrayItem>,
+}
+
+/// Base directories of the icon themes, see the freedesktop icon theme specification
+fn icon_dirs() -> Vec<PathBuf> {
+    let home = env::var("HOME").unwrap_or_default();
+    let data_home = env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
+    let data_dirs =
+        env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_owned());
+
+    let mut dirs = vec![PathBuf::from(format!("{}/.icons", home))];
+    dirs.extend(
+        std::iter::once(data_home.as_str())
+            .chain(data_dirs.split(':'))
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| Path::new(dir).join("icons")),
+    );
+
+    dirs
+}
+
+fn find_icon_in_theme(theme: &Path, name: &str) -> Option<PathBuf> {
+    ICON_SIZES.iter().find_map(|size| {
+        ICON_CATEGORIES.iter().find_map(|category| {
+            // most themes use `<size>/<category>`, breeze uses `<category>/<pixels>`
+            let pixels = size.split('x').next().unwrap_or(size);
+
+            [
+                theme.join(size).join(category),
+                theme.join(category).join(pixels),
+            ]
+            .iter()
+            .flat_map(|dir| {
+                ["svg", "png"].map(|extension| dir.join(format!("{}.{}", name, extension)))
+            })
+            .find(|path| path.is_file())
+        })
+    })
+}
+
+/// Icon name and theme path of the item to the resolved file
+type IconCache = HashMap<(String, Option<String>), Option<PathBuf>>;
+
+/// Resolve a themed icon name to a file, the lookups are cached for the whole session
+fn find_icon(name: &str, theme_path: Option<&str>) -> Option<PathBuf> {
+    static CACHE: OnceLock<Mutex<IconCache>> = OnceLock::new();
+
+    if Path::new(name).is_absolute() {
+        return Some(PathBuf::from(name)).filter(|path| path.is_file());
+    }
+
+    let key = (name.to_owned(), theme_path.map(str::to_owned));
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(path) = cache.lock().ok().and_then(|cache| cache.get(&key).cloned()) {
+        return path;
+    }
+
+    let mut themes = Vec::new();
+    if let Some(theme_path) = theme_path.filter(|path| !path.is_empty()) {
+        let theme_path = Path::new(theme_path);
+        themes.push(theme_path.join("hicolor"));
+        themes.push(theme_path.to_path_buf());
+    }
+    for dir in icon_dirs() {
+        let mut dir_themes = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir() && !path.ends_with("hicolor"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        dir_themes.sort();
+        themes.extend(dir_themes);
+    }
+    // hicolor is the fallback of every theme
+    themes.extend(icon_dirs().into_iter().map(|dir| dir.join("hicolor")));
+
+    let path = themes
+        .iter()
+        .find_map(|theme| find_icon_in_theme(theme, name))
+        .or_else(|| {
+            theme_path
+                .into_iter()
+                .chain(["/usr/share/pixmaps"])
+                .flat_map(|dir| {
+                    ["svg", "png"]
+                        .map(|extension| Path::new(dir).join(format!("{}.{}", name, extension)))
+                })
+                .find(|path| path.is_file())
+        });
+
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(key, path.clone());
+    }
+
+    path
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayService {
+    data: TrayData,
+    conn: zbus::Connection,
+}
+
+impl Deref for TrayService {
+    type Target = TrayData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl TrayService {
+    /// Serve the watcher unless another one runs, then register as a host
+    async fn start_host(conn: &zbus::Connection) -> anyhow::Result<()> {
+        conn.object_server()
+            .at(WATCHER_PATH, Watcher::default())
+            .await?;
+
+        match conn
+            .request_name_with_flags(WATCHER_NAME, RequestNameFlags::DoNotQueue.into())
+            .await?
+        {
+            RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {
+                info!("Serving the StatusNotifierWatcher");
+            }
+            _ => {
+                info!("Using the running StatusNotifierWatcher");
+                conn.object_server()
+                    .remove::<Watcher, _>(WATCHER_PATH)
+                    .await?;
+            }
+        }
+
+        let host = format!("org.kde.StatusNotifierHost-{}", std::process::id());
+        conn.request_name(host.as_str()).await?;
+        dbus::watcher(conn)
+            .await?
+            .register_status_notifier_host(&host)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read the registered items, the ones already in `previous` are kept as they are
+    async fn initialize_data(
+        conn: &zbus::Connection,
+        previous: &TrayData,
+    ) -> anyhow::Result<TrayData> {
+        let mut items = Vec::new();
+
+        for address in dbus::watcher(conn)
+            .await?
+            .registered_status_notifier_items()
+            .await?
+        {
+            if let Some(item) = previous.items.iter().find(|item| item.address == address) {
+                items.push(item.clone());
+                continue;
+            }
+
+            // items can quit between the listing and the property read
+            match Self::item_data(conn, address.clone(), None).await {
+                Ok(item) => items.push(item),
+                Err(err) => debug!("Failed to read tray item {}: {}", address, err),
+            }
+        }
+
+        Ok(TrayData { items })
+    }
+
+    /// Read the properties of an item, a refresh of a `previous` item keeps its menu
+    async fn item_data(
+        conn: &zbus::Connection,
+        address: String,
+        previous: Option<&TrayItem>,
+    ) -> anyhow::Result<TrayItem> {
+        let (name, _) = dbus::item_destination(&address);
+        let owner = match previous {
+            Some(previous) => previous.owner.clone(),
+            None if name.starts_with(':') => name.to_owned(),
+            None => DBusProxy::new(conn)
+                .await?
+                .get_name_owner(name.try_into()?)
+                .await?
+                .to_string(),
+        };
+
+        let item = dbus::item(conn, &address).await?;
+        let status = item
+            .status()
+            .await
+            .map(TrayItemStatus::from)
+            .unwrap_or(TrayItemStatus::Active);
+        let theme_path = item.icon_theme_path().await.ok();
+
+        let (icon_name, icon_pixmap) = if status == TrayItemStatus::NeedsAttention {
+            (
+                item.attention_icon_name().await,
+                item.attention_icon_pixmap().await,
+            )
+        } else {
+            (item.icon_name().await, item.icon_pixmap().await)
+        };
+        let icon = icon_name
+            .ok()
+            .filter(|name| !name.is_empty())
+            .and_then(|name| find_icon(&name, theme_path.as_deref()))
+            .map(TrayIcon::File)
+            .or_else(|| {
+                icon_pixmap.ok().and_then(|pixmaps| {
+                    TrayIcon::from_pixmaps(pixmaps, previous.and_then(|item| item.icon.as_ref()))
+                })
+            });
+
+        let menu = match (previous, item.menu().await) {
+            (Some(previous), _) => previous.menu.clone(),
+            (None, Ok(path)) => Self::menu_data(conn, &address, path).await.ok(),
+            (None, Err(_)) => None,
+        };
+
+        Ok(TrayItem {
+            id: item.id().await.unwrap_or_default(),
+            title: item.title().await.ok(),
+            item_is_menu: item.item_is_menu().await.unwrap_or_default(),
+            status,
+            icon,
+            menu,
+            address,
+            owner,
+        })
+    }
+
+    async fn menu_data(
+        conn: &zbus::Connection,
+        address: &str,
+        path: OwnedObjectPath,
+    ) -> anyhow::Result<TrayMenu> {
+        let (name, _) = dbus::item_destination(address);
+        let (_, (_, _, children)) = dbus::menu(conn, name, &path)
+            .await?
+            .get_layout(0, -1, &[])
+            .await?;
+
+        Ok(TrayMenu {
+            items: children
+                .iter()
+                .filter_map(|child| TrayMenuItem::from_value(child))
+                .collect(),
+            path,
+        })
+    }
+
+    async fn remove_name(conn: &zbus::Connection, name: &str) -> anyhow::Result<()> {
+        // the interface is there only while ashell is the watcher
+        if let Ok(watcher) = conn
+            .object_server()
+            .interface::<_, Watcher>(WATCHER_PATH)
+            .await
+        {
+            watcher
+                .get_mut()
+                .await
+                .remove_name(watcher.signal_emitter(), name)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
+        match state {
+            State::Init => match zbus::Connection::session().await {
+                Ok(conn) => {
+                    if let Err(err) = Self::start_host(&conn).await {
+                        warn!("Failed to start the tray host: {}", err);
+
+                        return State::Error;
+                    }
+
+                    match Self::initialize_data(&conn, &TrayData::default()).await {
+                        Ok(data) => {
+                            let _ = output
+                                .send(ServiceEvent::Init(TrayService {
+                                    data: data.clone(),
+                                    conn: conn.clone(),
+                                }))
+                                .await;
+
+                            State::Active(conn, data)
+                        }
+                        Err(err) => {
+                            warn!("Failed to initialize tray items: {}", err);
+
+                            State::Error
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!("Failed to connect to session bus: {}", err);
+
+                    State::Error
+                }
+            },
+            State::Active(conn, mut data) => {
+                info!("Listening for tray events");
+
+                match dbus::items_changed(&conn).await {
+                    Ok(mut events) => {
+                        while let Some(event) = events.next().await {
+                            let changed = match event {
+                                // the watcher signals the removed items, that triggers the refresh
+                                TrayChange::NameLost(name) => {
+                                    if let Err(err) = Self::remove_name(&conn, &name).await {
+                                        warn!(
+                                            "Failed to unregister tray items of {}: {}",
+                                            name, err
+                                        );
+                                    }
+                                    false
+                                }
+                                TrayChange::Registered => {
+                                    match Self::initialize_data(&conn, &data).await {
+                                        Ok(new_data) => {
+                                            data = new_data;
+                                            true
+                                        }
+                                        Err(err) => {
+                                            warn!("Failed to refresh tray items: {}", err);
+                                            false
+                                        }
+                                    }
+                                }
+                                TrayChange::Item(sender, path) => {
+                                    let mut changed = false;
+                                    for item in data
+                                        .items
+                                        .iter_mut()
+                                        .filter(|item| item.is_source(&sender, &path))
+                                    {
+                                        match Self::item_data(
+                                            &conn,
+                                            item.address.clone(),
+                                            Some(item),
+                                        )
+                                        .await
+                                        {
+                                            Ok(new_item) => {
+                                                *item = new_item;
+                                                changed = true;
+                                            }
+                                            Err(err) => debug!(
+                                                "Failed to refresh tray item {}: {}",
+                                                item.address, err
+                                            ),
+                                        }
+                                    }
+                                    changed
+                                }
+                                TrayChange::Menu(sender, path) => {
+                                    let mut changed = false;
+                                    for item in data
+                                        .items
+                                        .iter_mut()
+                                        .filter(|item| item.is_menu_source(&sender, &path))
+                                    {
+                                        let Some(menu_path) =
+                                            item.menu.as_ref().map(|menu| menu.path.clone())
+                                        else {
+                                            continue;
+                                        };
+
+                                        match Self::menu_data(&conn, &item.address, menu_path).await
+                                        {
+                                            Ok(menu) => {
+                                                item.menu = Some(menu);
+                                                changed = true;
+                                            }
+                                            Err(err) => debug!(
+                                                "Failed to refresh the menu of {}: {}",
+                                                item.address, err
+                                            ),
+                                        }
+                                    }
+                                    changed
+                                }
+                            };
+
+                            if changed {
+                                let _ = output
+                                    .send(ServiceEvent::Update(TrayEvent(data.clone())))
+                                    .await;
+                            }
+                        }
+
+                        State::Active(conn, data)
+                    }
+                    Err(err) => {
+                        error!("Failed to listen for tray events: {}", err);
+
+                        State::Error
+                    }
+                }
+            }
+            State::Error => {
+                let _ = pending::<u8>().next().await;
+
+                State::Error
+            }
+        }
+    }
+
+    async fn item_command(conn: &zbus::Connection, command: TrayCommand) -> anyhow::Result<()> {
+        match command {
+            TrayCommand::Activate(address) => {
+                dbus::item(conn, &address).await?.activate(0, 0).await?;
+            }
+            TrayCommand::AboutToShow(address, path) => {
+                let (name, _) = dbus::item_destination(&address);
+                dbus::menu(conn, name, &path)
+                    .await?
+                    .about_to_show(0)
+                    .await?;
+            }
+            TrayCommand::MenuItemClicked(address, path, id) => {
+                let (name, _) = dbus::item_destination(&address);
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|time| time.as_secs() as u32)
+                    .unwrap_or_default();
+
+                dbus::menu(conn, name, &path)
+                    .await?
+                    .event(id, "clicked", &Value::I32(0), timestamp)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    Init,
+    /// The listener keeps its own copy of the items to refresh them one by one
+    Active(zbus::Connection, TrayData),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayEvent(TrayData);
+
+impl ReadOnlyService for TrayService {
+    type UpdateEvent = TrayEvent;
+    type Error = ();
+
+    fn update(&mut self, event: Self::UpdateEvent) {
+        self.data = event.0;
+    }
+
+    fn subscribe() -> Subscription<ServiceEvent<Self>> {
+        let id = TypeId::of::<Self>();
+
+        Subscription::run_with_id(
+            id,
+            channel(100, |mut output| async move {
+                let mut state = State::Init;
+
+                loop {
+                    state = TrayService::start_listening(state, &mut output).await;
+                }
+            }),
+        )
+    }
+}
+
+/// Item commands, each one targets the item with the given watcher address
+#[derive(Debug, Clone)]
+pub enum TrayCommand {
+    Activate(String),
+    /// Let the application refresh the menu before it's shown
+    AboutToShow(String, OwnedObjectPath),
+    MenuItemClicked(String, OwnedObjectPath, i32),
+}
+
+impl Service for TrayService {
+    type Command = TrayCommand;
+
+    fn command(&mut self, command: Self::Command) -> Task<ServiceEvent<Self>> {
+        let conn = self.conn.clone();
+
+        Task::perform(
+            async move {
+                debug!("Tray command: {:?}", command);
+                if let Err(err) = Self::item_command(&conn, command).await {
+                    error!("Failed to send tray command: {}", err);
+                }
+            },
+            |_| (),
+        )
+        // the applications signal their changes, the listener refreshes them
+        .discard()
+    }
+}
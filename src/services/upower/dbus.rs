@@ -1,9 +1,6 @@
+use iced::futures::{stream_select, Stream, StreamExt};
 use std::ops::Deref;
-use zbus::{
-    proxy,
-    zvariant::{ObjectPath, OwnedObjectPath},
-    Result,
-};
+use zbus::{proxy, zvariant::OwnedObjectPath, MatchRule, MessageStream, Result};
 
 pub struct UPowerDbus<'a>(UPowerProxy<'a>);
 
@@ -22,30 +19,24 @@ impl<'a> UPowerDbus<'a> {
         Ok(Self(nm))
     }
 
-    pub async fn get_battery_device(&self) -> anyhow::Result<Option<DeviceProxy>> {
-        let devices = self.enumerate_devices().await?;
+    /// Every device reported by UPower, the aggregated display device is not included
+    pub async fn get_devices(&self) -> anyhow::Result<Vec<DeviceProxy<'static>>> {
+        let paths = self.enumerate_devices().await?;
 
-        for device in devices {
+        let mut devices = Vec::with_capacity(paths.len());
+        for path in paths {
             let device = DeviceProxy::builder(self.inner().connection())
-                .path(device)?
+                .path(path)?
                 .build()
                 .await?;
 
-            let device_type = device.device_type().await?;
-            let power_supply = device.power_supply().await?;
-
-            if device_type == 2 && power_supply {
-                return Ok(Some(device));
-            }
+            devices.push(device);
         }
 
-        Ok(None)
+        Ok(devices)
     }
 
-    pub async fn get_device(
-        &self,
-        path: &ObjectPath<'static>,
-    ) -> anyhow::Result<DeviceProxy<'static>> {
+    pub async fn get_device(&self, path: OwnedObjectPath) -> anyhow::Result<DeviceProxy<'static>> {
         let device = DeviceProxy::builder(self.inner().connection())
             .path(path)?
             .build()
@@ -53,6 +44,25 @@ impl<'a> UPowerDbus<'a> {
 
         Ok(device)
     }
+
+    /// Emits every time a property of a device changes
+    pub async fn devices_changed(&self) -> anyhow::Result<impl Stream<Item = ()>> {
+        let rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.UPower")?
+            .interface("org.freedesktop.DBus.Properties")?
+            .member("PropertiesChanged")?
+            .arg(0, "org.freedesktop.UPower.Device")?
+            .build();
+
+        let properties = MessageStream::for_match_rule(rule, self.inner().connection(), None)
+            .await?
+            .map(|_| ());
+        let added = self.receive_device_added().await?.map(|_| ());
+        let removed = self.receive_device_removed().await?.map(|_| ());
+
+        Ok(stream_select!(properties, added, removed))
+    }
 }
 
 #[proxy(
@@ -65,6 +75,9 @@ pub trait UPower {
 
     #[zbus(signal)]
     fn device_added(&self) -> Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn device_removed(&self) -> Result<OwnedObjectPath>;
 }
 
 #[proxy(
@@ -79,6 +92,47 @@ pub trait Device {
     #[zbus(property)]
     fn power_supply(&self) -> Result<bool>;
 
+    #[zbus(property)]
+    fn is_present(&self) -> Result<bool>;
+
+    #[zbus(property)]
+    fn model(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn energy(&self) -> Result<f64>;
+
+    #[zbus(property)]
+    fn energy_full(&self) -> Result<f64>;
+
+    #[zbus(property)]
+    fn energy_rate(&self) -> Result<f64>;
+
+    #[zbus(property)]
+    fn energy_full_design(&self) -> Result<f64>;
+
+    #[zbus(property)]
+    fn capacity(&self) -> Result<f64>;
+
+    #[zbus(property)]
+    fn charge_cycles(&self) -> Result<i32>;
+
+    #[zbus(property)]
+    fn native_path(&self) -> Result<String>;
+
+    #[zbus(property)]
+    fn charge_start_threshold(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn charge_end_threshold(&self) -> Result<u32>;
+
+    #[zbus(property)]
+    fn charge_threshold_enabled(&self) -> Result<bool>;
+
+    #[zbus(property)]
+    fn charge_threshold_supported(&self) -> Result<bool>;
+
+    fn enable_charge_threshold(&self, charge_threshold: bool) -> Result<()>;
+
     #[zbus(property)]
     fn time_to_empty(&self) -> Result<i64>;
 
@@ -1,15 +1,16 @@
 use super::{ReadOnlyService, Service, ServiceEvent};
 use crate::{components::icons::Icons, utils::IndicatorState};
-use dbus::{PowerProfilesProxy, UPowerDbus};
+use dbus::{DeviceProxy, PowerProfilesProxy, UPowerDbus};
 use iced::{
-    futures::stream::{once, pending},
+    futures::stream::pending,
     futures::{channel::mpsc::Sender, stream_select, SinkExt, Stream, StreamExt},
     stream::channel,
     Subscription,
 };
-use log::{error, warn};
-use std::{any::TypeId, time::Duration};
-use zbus::zvariant::ObjectPath;
+use log::{debug, error, warn};
+use std::{any::TypeId, fs, time::Duration};
+use tokio::process::Command;
+use zbus::zvariant::OwnedObjectPath;
 
 mod dbus;
 
@@ -61,10 +62,86 @@ impl BatteryData {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerDeviceKind {
+    Battery,
+    Mouse,
+    Keyboard,
+    Audio,
+    GamingInput,
+    Phone,
+    Tablet,
+    Other,
+}
+
+impl From<u32> for PowerDeviceKind {
+    fn from(device_type: u32) -> Self {
+        match device_type {
+            2 => PowerDeviceKind::Battery,
+            5 | 14 => PowerDeviceKind::Mouse,
+            6 => PowerDeviceKind::Keyboard,
+            17..=19 | 21 => PowerDeviceKind::Audio,
+            12 => PowerDeviceKind::GamingInput,
+            8 => PowerDeviceKind::Phone,
+            10 | 13 => PowerDeviceKind::Tablet,
+            _ => PowerDeviceKind::Other,
+        }
+    }
+}
+
+/// End threshold the charge limit helper is expected to set
+pub const HELPER_CHARGE_LIMIT: u32 = 80;
+
+#[derive(Clone, Debug)]
+pub struct BatteryDetails {
+    pub path: OwnedObjectPath,
+    /// Full charge energy compared to the design one
+    pub health: Option<f64>,
+    pub cycles: Option<i32>,
+    /// Charge or discharge rate in W
+    pub energy_rate: f64,
+    /// Start and end charge thresholds in percentage
+    pub charge_thresholds: Option<(u32, u32)>,
+    pub charge_limit_enabled: bool,
+    pub charge_limit_supported: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct PowerDevice {
+    pub kind: PowerDeviceKind,
+    pub name: String,
+    pub battery: BatteryData,
+    /// Only available for system batteries
+    pub details: Option<BatteryDetails>,
+}
+
+impl PowerDevice {
+    pub fn get_icon(&self) -> Icons {
+        match self.kind {
+            PowerDeviceKind::Battery => self.battery.get_icon(),
+            PowerDeviceKind::Mouse => Icons::Mouse,
+            PowerDeviceKind::Keyboard => Icons::Keyboard,
+            PowerDeviceKind::Audio => Icons::Headphones1,
+            PowerDeviceKind::GamingInput => Icons::Gamepad,
+            PowerDeviceKind::Phone | PowerDeviceKind::Tablet => Icons::Phone,
+            PowerDeviceKind::Other => Icons::Battery4,
+        }
+    }
+}
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+// Energy values of a system battery in Wh and W, used to build the aggregated battery
+#[derive(Clone, Copy, Debug, Default)]
+struct Energy {
+    energy: f64,
+    energy_full: f64,
+    energy_rate: f64,
+}
+
 #[derive(Debug, Clone)]
 pub enum UPowerEvent {
-    UpdateBattery(BatteryData),
-    NoBattery,
+    UpdateDevices(Option<BatteryData>, Vec<PowerDevice>),
     UpdatePowerProfile(PowerProfile),
 }
 
@@ -109,13 +186,14 @@ impl From<PowerProfile> for Icons {
 #[derive(Debug, Clone)]
 pub struct UPowerService {
     pub battery: Option<BatteryData>,
+    pub devices: Vec<PowerDevice>,
     pub power_profile: PowerProfile,
     conn: zbus::Connection,
 }
 
 enum State {
     Init,
-    Active(zbus::Connection, Option<ObjectPath<'static>>),
+    Active(zbus::Connection),
     Error,
 }
 
@@ -125,11 +203,9 @@ impl ReadOnlyService for UPowerService {
 
     fn update(&mut self, event: Self::UpdateEvent) {
         match event {
-            UPowerEvent::UpdateBattery(data) => {
-                self.battery.replace(data);
-            }
-            UPowerEvent::NoBattery => {
-                self.battery = None;
+            UPowerEvent::UpdateDevices(battery, devices) => {
+                self.battery = battery;
+                self.devices = devices;
             }
             UPowerEvent::UpdatePowerProfile(profile) => {
                 self.power_profile = profile;
@@ -156,22 +232,16 @@ impl ReadOnlyService for UPowerService {
 impl UPowerService {
     async fn initialize_data(
         conn: &zbus::Connection,
-    ) -> anyhow::Result<(Option<(BatteryData, ObjectPath<'static>)>, PowerProfile)> {
-        let battery = UPowerService::initialize_battery_data(conn).await?;
+    ) -> anyhow::Result<(Option<BatteryData>, Vec<PowerDevice>, PowerProfile)> {
+        let (battery, devices) = UPowerService::initialize_devices_data(conn).await?;
         let power_profile = UPowerService::initialize_power_profile_data(conn).await;
 
-        match (battery, power_profile) {
-            (Some(battery), Ok(power_profile)) => Ok((Some((battery.0, battery.1)), power_profile)),
-            (Some(battery), Err(err)) => {
-                warn!("Failed to get power profile: {}", err);
-
-                Ok((Some((battery.0, battery.1)), PowerProfile::Unknown))
-            }
-            (None, Ok(power_profile)) => Ok((None, power_profile)),
-            (None, Err(err)) => {
+        match power_profile {
+            Ok(power_profile) => Ok((battery, devices, power_profile)),
+            Err(err) => {
                 warn!("Failed to get power profile: {}", err);
 
-                Ok((None, PowerProfile::Unknown))
+                Ok((battery, devices, PowerProfile::Unknown))
             }
         }
     }
@@ -189,89 +259,239 @@ impl UPowerService {
         Ok(profile)
     }
 
-    async fn initialize_battery_data(
+    fn battery_status(state: u32, time_to_full: i64, time_to_empty: i64) -> BatteryStatus {
+        match state {
+            1 => BatteryStatus::Charging(Duration::from_secs(time_to_full.max(0) as u64)),
+            2 => BatteryStatus::Discharging(Duration::from_secs(time_to_empty.max(0) as u64)),
+            4 => BatteryStatus::Full,
+            _ => BatteryStatus::Discharging(Duration::from_secs(0)),
+        }
+    }
+
+    /// Batteries and peripherals with a battery, each system battery comes with its energy
+    async fn initialize_devices_data(
         conn: &zbus::Connection,
-    ) -> anyhow::Result<Option<(BatteryData, ObjectPath<'static>)>> {
+    ) -> anyhow::Result<(Option<BatteryData>, Vec<PowerDevice>)> {
         let upower = UPowerDbus::new(conn).await?;
-        let battery = upower.get_battery_device().await?;
-
-        if let Some(battery) = battery {
-            let state = battery.state().await?;
-            let state = match state {
-                1 => BatteryStatus::Charging(Duration::from_secs(
-                    battery.time_to_full().await.unwrap_or_default() as u64,
-                )),
-                2 => BatteryStatus::Discharging(Duration::from_secs(
-                    battery.time_to_empty().await.unwrap_or_default() as u64,
-                )),
-                4 => BatteryStatus::Full,
-                _ => BatteryStatus::Discharging(Duration::from_secs(0)),
+
+        let mut batteries = Vec::new();
+        let mut devices = Vec::new();
+        for device in upower.get_devices().await? {
+            // unknown devices and AC adapters have no battery
+            let device_type = device.device_type().await?;
+            if device_type <= 1 || !device.is_present().await.unwrap_or(true) {
+                continue;
+            }
+            let kind = PowerDeviceKind::from(device_type);
+
+            let battery = BatteryData {
+                capacity: device.percentage().await.unwrap_or_default().round() as i64,
+                status: UPowerService::battery_status(
+                    device.state().await.unwrap_or_default(),
+                    device.time_to_full().await.unwrap_or_default(),
+                    device.time_to_empty().await.unwrap_or_default(),
+                ),
             };
-            let percentage = battery.percentage().await.unwrap_or_default() as i64;
+            let model = device.model().await.unwrap_or_default();
+
+            let mut details = None;
+            if kind == PowerDeviceKind::Battery && device.power_supply().await.unwrap_or_default() {
+                details = Some(UPowerService::battery_details(&device).await);
+                batteries.push((
+                    battery,
+                    Energy {
+                        energy: device.energy().await.unwrap_or_default(),
+                        energy_full: device.energy_full().await.unwrap_or_default(),
+                        energy_rate: device.energy_rate().await.unwrap_or_default().abs(),
+                    },
+                ));
+            }
 
-            Ok(Some((
-                BatteryData {
-                    capacity: percentage,
-                    status: state,
+            devices.push(PowerDevice {
+                kind,
+                name: if model.is_empty() {
+                    "Battery".to_string()
+                } else {
+                    model
                 },
-                battery.inner().path().to_owned(),
-            )))
-        } else {
-            Ok(None)
+                battery,
+                details,
+            });
         }
+
+        // system batteries first, then peripherals by name
+        devices.sort_by(|a, b| {
+            (b.kind == PowerDeviceKind::Battery)
+                .cmp(&(a.kind == PowerDeviceKind::Battery))
+                .then(a.name.cmp(&b.name))
+        });
+
+        Ok((UPowerService::aggregate_batteries(&batteries), devices))
     }
 
-    async fn events(
+    async fn battery_details(device: &DeviceProxy<'static>) -> BatteryDetails {
+        let native_path = device.native_path().await.unwrap_or_default();
+
+        let health = match device.capacity().await {
+            Ok(capacity) if capacity > 0. => Some(capacity),
+            _ => {
+                let energy_full = device.energy_full().await.unwrap_or_default();
+                let energy_full_design = device.energy_full_design().await.unwrap_or_default();
+
+                (energy_full_design > 0.).then(|| energy_full / energy_full_design * 100.)
+            }
+        };
+
+        // UPower exposes the thresholds since 1.90, older versions only have the sysfs files
+        let charge_limit_supported = device
+            .charge_threshold_supported()
+            .await
+            .unwrap_or_default();
+        let charge_thresholds = match (
+            device.charge_start_threshold().await,
+            device.charge_end_threshold().await,
+        ) {
+            (Ok(start), Ok(end)) if charge_limit_supported && end > 0 => Some((start, end)),
+            _ => read_sysfs_threshold(&native_path, "end").map(|end| {
+                (
+                    read_sysfs_threshold(&native_path, "start").unwrap_or(0),
+                    end,
+                )
+            }),
+        };
+        let charge_limit_enabled = match device.charge_threshold_enabled().await {
+            Ok(enabled) if charge_limit_supported => enabled,
+            _ => read_sysfs_threshold(&native_path, "end").is_some_and(|end| end < 100),
+        };
+
+        BatteryDetails {
+            path: device.inner().path().to_owned().into(),
+            health,
+            cycles: device
+                .charge_cycles()
+                .await
+                .ok()
+                .filter(|cycles| *cycles >= 0),
+            energy_rate: device.energy_rate().await.unwrap_or_default().abs(),
+            charge_thresholds,
+            charge_limit_enabled,
+            charge_limit_supported,
+        }
+    }
+
+    /// Enable the charge limit through UPower, which asks polkit for the permission,
+    /// or through the configured helper command that gets the battery (e.g. BAT0)
+    /// and "on" or "off" as arguments
+    async fn set_charge_limit(
         conn: &zbus::Connection,
-        battery_path: &Option<ObjectPath<'static>>,
-    ) -> anyhow::Result<impl Stream<Item = UPowerEvent>> {
-        let battery_event = if let Some(battery_path) = battery_path {
-            let upower = UPowerDbus::new(conn).await?;
-            let device = upower.get_device(battery_path).await?;
-
-            let combined = stream_select!(
-                device.receive_state_changed().await.map(|_| ()),
-                device.receive_percentage_changed().await.map(|_| ()),
-                device.receive_time_to_full_changed().await.map(|_| ()),
-                device.receive_time_to_empty_changed().await.map(|_| ()),
-            )
-            .map(move |_| {
-                let state = device
-                    .cached_state()
-                    .unwrap_or_default()
-                    .unwrap_or_default();
-                let state = match state {
-                    1 => BatteryStatus::Charging(Duration::from_secs(
-                        device
-                            .cached_time_to_full()
-                            .unwrap_or_default()
-                            .unwrap_or_default() as u64,
-                    )),
-                    2 => BatteryStatus::Discharging(Duration::from_secs(
-                        device
-                            .cached_time_to_empty()
-                            .unwrap_or_default()
-                            .unwrap_or_default() as u64,
-                    )),
-                    4 => BatteryStatus::Full,
-                    _ => BatteryStatus::Discharging(Duration::from_secs(0)),
-                };
-
-                UPowerEvent::UpdateBattery(BatteryData {
-                    capacity: device
-                        .cached_percentage()
-                        .unwrap_or_default()
-                        .unwrap_or_default() as i64,
-                    status: state,
-                })
-            })
-            .boxed();
+        device: OwnedObjectPath,
+        enabled: bool,
+        helper_cmd: Option<String>,
+    ) -> anyhow::Result<()> {
+        let upower = UPowerDbus::new(conn).await?;
+        let device = upower.get_device(device).await?;
+
+        match helper_cmd {
+            Some(cmd) => {
+                let battery = device.native_path().await?;
+                let status = Command::new("bash")
+                    .arg("-c")
+                    .arg(format!(
+                        "{} {} {}",
+                        cmd,
+                        battery,
+                        if enabled { "on" } else { "off" }
+                    ))
+                    .status()
+                    .await?;
+
+                if !status.success() {
+                    anyhow::bail!("{} exited with {}", cmd, status);
+                }
+            }
+            None => device.enable_charge_threshold(enabled).await?,
+        }
 
-            combined
+        Ok(())
+    }
+
+    /// Combine the system batteries weighting each one by its energy, like a single bigger battery
+    fn aggregate_batteries(batteries: &[(BatteryData, Energy)]) -> Option<BatteryData> {
+        if batteries.len() <= 1 {
+            return batteries.first().map(|(battery, _)| *battery);
+        }
+
+        let total = batteries
+            .iter()
+            .fold(Energy::default(), |total, (_, energy)| Energy {
+                energy: total.energy + energy.energy,
+                energy_full: total.energy_full + energy.energy_full,
+                energy_rate: total.energy_rate + energy.energy_rate,
+            });
+
+        let capacity = if total.energy_full > 0. {
+            (total.energy / total.energy_full * 100.).round() as i64
+        } else {
+            batteries
+                .iter()
+                .map(|(battery, _)| battery.capacity)
+                .sum::<i64>()
+                / batteries.len() as i64
+        };
+
+        let seconds = |energy: f64| {
+            if total.energy_rate > 0. {
+                Duration::from_secs((energy / total.energy_rate * 3600.) as u64)
+            } else {
+                Duration::from_secs(0)
+            }
+        };
+
+        let status = if batteries
+            .iter()
+            .any(|(battery, _)| matches!(battery.status, BatteryStatus::Charging(_)))
+        {
+            BatteryStatus::Charging(seconds(total.energy_full - total.energy))
+        } else if batteries
+            .iter()
+            .all(|(battery, _)| matches!(battery.status, BatteryStatus::Full))
+        {
+            BatteryStatus::Full
         } else {
-            once(async {}).map(|_| UPowerEvent::NoBattery).boxed()
+            BatteryStatus::Discharging(seconds(total.energy))
         };
 
+        Some(BatteryData {
+            capacity: capacity.clamp(0, 100),
+            status,
+        })
+    }
+
+    async fn events(conn: &zbus::Connection) -> anyhow::Result<impl Stream<Item = UPowerEvent>> {
+        let upower = UPowerDbus::new(conn).await?;
+        let devices_event = upower
+            .devices_changed()
+            .await?
+            .filter_map({
+                let conn = conn.clone();
+                move |_| {
+                    let conn = conn.clone();
+                    async move {
+                        match UPowerService::initialize_devices_data(&conn).await {
+                            Ok((battery, devices)) => {
+                                Some(UPowerEvent::UpdateDevices(battery, devices))
+                            }
+                            Err(err) => {
+                                warn!("Failed to read upower devices: {}", err);
+
+                                None
+                            }
+                        }
+                    }
+                }
+            })
+            .boxed();
+
         let powerprofiles = PowerProfilesProxy::new(conn).await?;
         let power_profile_event =
             powerprofiles
@@ -286,19 +506,16 @@ impl UPowerService {
                     )
                 });
 
-        Ok(stream_select!(battery_event, power_profile_event))
+        Ok(stream_select!(devices_event, power_profile_event))
     }
 
     async fn start_listening(state: State, output: &mut Sender<ServiceEvent<Self>>) -> State {
         match state {
             State::Init => match zbus::Connection::system().await {
                 Ok(conn) => {
-                    let (battery, battery_path, power_profile) =
+                    let (battery, devices, power_profile) =
                         match UPowerService::initialize_data(&conn).await {
-                            Ok((Some((battery_data, battery_path)), power_profile)) => {
-                                (Some(battery_data), Some(battery_path), power_profile)
-                            }
-                            Ok((None, power_profile)) => (None, None, power_profile),
+                            Ok(data) => data,
                             Err(err) => {
                                 error!("Failed to initialize upower service: {}", err);
 
@@ -308,34 +525,33 @@ impl UPowerService {
 
                     let service = UPowerService {
                         battery,
+                        devices,
                         power_profile,
                         conn: conn.clone(),
                     };
                     let _ = output.send(ServiceEvent::Init(service)).await;
 
-                    State::Active(conn, battery_path)
+                    State::Active(conn)
                 }
                 Err(err) => {
                     error!("Failed to connect to system bus for upower: {}", err);
                     State::Error
                 }
             },
-            State::Active(conn, battery_path) => {
-                match UPowerService::events(&conn, &battery_path).await {
-                    Ok(mut events) => {
-                        while let Some(event) = events.next().await {
-                            let _ = output.send(ServiceEvent::Update(event)).await;
-                        }
-
-                        State::Active(conn, battery_path)
+            State::Active(conn) => match UPowerService::events(&conn).await {
+                Ok(mut events) => {
+                    while let Some(event) = events.next().await {
+                        let _ = output.send(ServiceEvent::Update(event)).await;
                     }
-                    Err(err) => {
-                        error!("Failed to listen for upower events: {}", err);
 
-                        State::Error
-                    }
+                    State::Active(conn)
                 }
-            }
+                Err(err) => {
+                    error!("Failed to listen for upower events: {}", err);
+
+                    State::Error
+                }
+            },
             State::Error => {
                 let _ = pending::<u8>().next().await;
 
@@ -345,49 +561,90 @@ impl UPowerService {
     }
 }
 
-pub enum PowerProfileCommand {
-    Toggle,
+pub enum UPowerCommand {
+    TogglePowerProfile,
+    SetChargeLimit(OwnedObjectPath, bool, Option<String>),
 }
 
 impl Service for UPowerService {
-    type Command = PowerProfileCommand;
+    type Command = UPowerCommand;
 
     fn command(&mut self, command: Self::Command) -> iced::Task<ServiceEvent<Self>> {
-        iced::Task::perform(
-            {
-                let conn = self.conn.clone();
+        let conn = self.conn.clone();
+
+        match command {
+            UPowerCommand::TogglePowerProfile => {
                 let power_profile = self.power_profile;
-                async move {
-                    let powerprofiles = PowerProfilesProxy::new(&conn)
-                        .await
-                        .expect("Failed to create PowerProfilesProxy");
-
-                    match command {
-                        PowerProfileCommand::Toggle => {
-                            let current_profile = power_profile;
-                            match current_profile {
-                                PowerProfile::Balanced => {
-                                    let _ = powerprofiles.set_active_profile("performance").await;
-
-                                    PowerProfile::Performance
-                                }
-                                PowerProfile::Performance => {
-                                    let _ = powerprofiles.set_active_profile("power-saver").await;
-
-                                    PowerProfile::PowerSaver
-                                }
-                                PowerProfile::PowerSaver => {
-                                    let _ = powerprofiles.set_active_profile("balanced").await;
-
-                                    PowerProfile::Balanced
-                                }
-                                PowerProfile::Unknown => PowerProfile::Unknown,
+
+                iced::Task::perform(
+                    async move {
+                        let powerprofiles = PowerProfilesProxy::new(&conn)
+                            .await
+                            .expect("Failed to create PowerProfilesProxy");
+
+                        match power_profile {
+                            PowerProfile::Balanced => {
+                                let _ = powerprofiles.set_active_profile("performance").await;
+
+                                PowerProfile::Performance
+                            }
+                            PowerProfile::Performance => {
+                                let _ = powerprofiles.set_active_profile("power-saver").await;
+
+                                PowerProfile::PowerSaver
+                            }
+                            PowerProfile::PowerSaver => {
+                                let _ = powerprofiles.set_active_profile("balanced").await;
+
+                                PowerProfile::Balanced
                             }
+                            PowerProfile::Unknown => PowerProfile::Unknown,
                         }
-                    }
-                }
-            },
-            |power_profile| ServiceEvent::Update(UPowerEvent::UpdatePowerProfile(power_profile)),
-        )
+                    },
+                    |power_profile| {
+                        ServiceEvent::Update(UPowerEvent::UpdatePowerProfile(power_profile))
+                    },
+                )
+            }
+            UPowerCommand::SetChargeLimit(device, enabled, helper_cmd) => {
+                let battery = self.battery;
+                let devices = self.devices.clone();
+
+                iced::Task::perform(
+                    async move {
+                        debug!("Setting charge limit to: {}", enabled);
+                        if let Err(err) =
+                            UPowerService::set_charge_limit(&conn, device, enabled, helper_cmd)
+                                .await
+                        {
+                            error!("Failed to set charge limit: {}", err);
+                        }
+
+                        // the sysfs thresholds changed by a helper don't emit any signal
+                        UPowerService::initialize_devices_data(&conn)
+                            .await
+                            .unwrap_or((battery, devices))
+                    },
+                    |(battery, devices)| {
+                        ServiceEvent::Update(UPowerEvent::UpdateDevices(battery, devices))
+                    },
+                )
+            }
+        }
+    }
+}
+
+fn read_sysfs_threshold(native_path: &str, kind: &str) -> Option<u32> {
+    if native_path.is_empty() {
+        return None;
     }
+
+    fs::read_to_string(format!(
+        "{}/{}/charge_control_{}_threshold",
+        POWER_SUPPLY_PATH, native_path, kind
+    ))
+    .ok()?
+    .trim()
+    .parse()
+    .ok()
 }
@@ -144,6 +144,7 @@ pub fn left_header_pills(theme: &Theme) -> container::Style {
 
 pub enum HeaderButtonStyle {
     Full,
+    Left,
     Right,
 }
 
@@ -156,6 +157,7 @@ impl HeaderButtonStyle {
                     width: 0.0,
                     radius: match self {
                         HeaderButtonStyle::Full => 12.0.into(),
+                        HeaderButtonStyle::Left => Radius::default().left(12),
                         HeaderButtonStyle::Right => Radius::default().right(12),
                     },
                     color: Color::TRANSPARENT,
@@ -229,6 +231,44 @@ impl OutlineButtonStyle {
     }
 }
 
+pub struct CalendarDayButtonStyle {
+    pub today: bool,
+    pub selected: bool,
+    pub outside: bool,
+}
+
+impl CalendarDayButtonStyle {
+    pub fn into_style<'a>(self) -> button::StyleFn<'a, Theme> {
+        Box::new(move |theme, status| {
+            let mut base = button::Style {
+                background: self
+                    .today
+                    .then(|| theme.extended_palette().primary.base.color.into()),
+                border: Border {
+                    width: if self.selected { 1.0 } else { 0.0 },
+                    radius: 16.0.into(),
+                    color: theme.palette().text,
+                },
+                text_color: if self.today {
+                    theme.extended_palette().primary.base.text
+                } else if self.outside {
+                    theme.extended_palette().secondary.base.color
+                } else {
+                    theme.palette().text
+                },
+                ..button::Style::default()
+            };
+            match status {
+                Status::Hovered if !self.today => {
+                    base.background = Some(theme.extended_palette().background.weak.color.into());
+                    base
+                }
+                _ => base,
+            }
+        })
+    }
+}
+
 pub struct ConfirmButtonStyle;
 
 impl ConfirmButtonStyle {
@@ -0,0 +1,562 @@
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use log::warn;
+use std::{fs, path::Path};
+
+/// How deep vdir collections are searched for `.ics` files
+const MAX_DEPTH: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    until: Option<NaiveDate>,
+    count: Option<u32>,
+    weekdays: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    uid: String,
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub all_day: bool,
+    /// Last day covered by the first occurrence
+    last_day: NaiveDate,
+    recurrence: Option<Recurrence>,
+    exceptions: Vec<NaiveDate>,
+}
+
+impl CalendarEvent {
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        let start = self.start.date();
+        if date < start || self.exceptions.contains(&date) {
+            return false;
+        }
+
+        let Some(rule) = &self.recurrence else {
+            return date <= self.last_day;
+        };
+        if rule.until.is_some_and(|until| date > until) {
+            return false;
+        }
+
+        let interval = rule.interval.max(1);
+        let index = match rule.frequency {
+            Frequency::Daily => {
+                let days = (date - start).num_days() as u32;
+                (days / interval * interval == days).then_some(days / interval)
+            }
+            Frequency::Weekly if !rule.weekdays.is_empty() => {
+                let week_start = |date: NaiveDate| {
+                    date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+                };
+                let weeks = ((week_start(date) - week_start(start)).num_days() / 7) as u32;
+
+                // the count of a BYDAY rule spans several days per week, it is not enforced
+                return weeks / interval * interval == weeks
+                    && rule.weekdays.contains(&date.weekday());
+            }
+            Frequency::Weekly => {
+                let days = (date - start).num_days() as u32;
+                let step = 7 * interval;
+                (days / step * step == days).then_some(days / step)
+            }
+            Frequency::Monthly | Frequency::Yearly => {
+                let step = match rule.frequency {
+                    Frequency::Yearly => 12 * interval,
+                    _ => interval,
+                };
+                let months = (date.year() * 12 + date.month0() as i32)
+                    - (start.year() * 12 + start.month0() as i32);
+                let months = months as u32;
+
+                // adding months clamps to the end of short months, RFC 5545 skips them instead
+                (months / step * step == months
+                    && date.day() == start.day()
+                    && (rule.frequency == Frequency::Monthly || date.month() == start.month()))
+                .then_some(months / step)
+            }
+        };
+
+        match (index, rule.count) {
+            (Some(index), Some(count)) => index < count,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Read the events of every `.ics` file and vdir directory in `paths`
+pub fn load_events(paths: &[String]) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+
+    for path in paths {
+        let path = match path.strip_prefix("~/") {
+            Some(rest) => match std::env::var("HOME") {
+                Ok(home) => Path::new(&home).join(rest),
+                Err(_) => Path::new(path).to_path_buf(),
+            },
+            None => Path::new(path).to_path_buf(),
+        };
+
+        read_path(&path, 0, &mut events);
+    }
+
+    events
+}
+
+fn read_path(path: &Path, depth: usize, events: &mut Vec<CalendarEvent>) {
+    if path.is_dir() {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.is_dir() || path.extension().is_some_and(|ext| ext == "ics") {
+                        read_path(&path, depth + 1, events);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to read calendar directory {:?}: {}", path, e),
+        }
+    } else {
+        match fs::read_to_string(path) {
+            Ok(content) => events.extend(parse(&content)),
+            Err(e) => warn!("Failed to read calendar {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Parse the `VEVENT`s of an iCalendar document
+///
+/// Times with a `TZID` are read as local time, UTC times are converted to it.
+pub fn parse(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+
+    // moved or changed occurrences of a recurring event, by uid
+    let mut overrides = Vec::new();
+
+    let mut in_event = false;
+    let mut uid = String::new();
+    let mut summary = String::new();
+    let mut start: Option<(NaiveDateTime, bool)> = None;
+    let mut end: Option<(NaiveDateTime, bool)> = None;
+    let mut rule: Option<String> = None;
+    let mut recurrence_id = None;
+    let mut exceptions = Vec::new();
+
+    for line in unfold(content) {
+        let Some((name, params, value)) = split_line(&line) else {
+            continue;
+        };
+
+        match (name.to_ascii_uppercase().as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                in_event = true;
+                uid.clear();
+                summary.clear();
+                start = None;
+                end = None;
+                rule = None;
+                recurrence_id = None;
+                exceptions.clear();
+            }
+            ("END", "VEVENT") if in_event => {
+                in_event = false;
+
+                let Some((start, all_day)) = start else {
+                    continue;
+                };
+                // a rule that can't be followed would show the event on wrong days
+                let recurrence = match rule.take() {
+                    Some(rule) => match parse_rule(&rule, start.date()) {
+                        Some(recurrence) => Some(recurrence),
+                        None => {
+                            warn!("Skipping event {:?}, unsupported rule {}", summary, rule);
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                if let Some(date) = recurrence_id.take() {
+                    overrides.push((uid.clone(), date));
+                }
+                let last_day = match end {
+                    // the end is exclusive
+                    Some((end, _)) if end > start => {
+                        if all_day || end.time() == chrono::NaiveTime::MIN {
+                            end.date().pred_opt().unwrap_or(end.date())
+                        } else {
+                            end.date()
+                        }
+                    }
+                    _ => start.date(),
+                };
+
+                events.push(CalendarEvent {
+                    uid: uid.clone(),
+                    summary: summary.clone(),
+                    start,
+                    all_day,
+                    last_day,
+                    recurrence,
+                    exceptions: exceptions.clone(),
+                });
+            }
+            _ if !in_event => {}
+            ("UID", value) => uid = value.to_owned(),
+            ("SUMMARY", value) => summary = unescape(value),
+            ("DTSTART", value) => start = parse_date_time(params, value),
+            ("DTEND", value) => end = parse_date_time(params, value),
+            ("RRULE", value) => rule = Some(value.to_owned()),
+            ("RECURRENCE-ID", value) => {
+                recurrence_id = parse_date_time(params, value).map(|(date, _)| date.date())
+            }
+            ("EXDATE", value) => exceptions.extend(
+                value
+                    .split(',')
+                    .filter_map(|value| parse_date_time(params, value))
+                    .map(|(date, _)| date.date()),
+            ),
+            _ => {}
+        }
+    }
+
+    // the override is shown instead of the occurrence it replaces
+    for (uid, date) in overrides {
+        for event in events
+            .iter_mut()
+            .filter(|event| event.uid == uid && event.recurrence.is_some())
+        {
+            event.exceptions.push(date);
+        }
+    }
+
+    events
+}
+
+/// Join the continuation lines, they start with a space or a tab
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    lines
+}
+
+/// Split `NAME;PARAM=x:VALUE`, a quoted parameter can contain a colon
+fn split_line(line: &str) -> Option<(&str, &str, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+
+    Some((name, params, value))
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push(' '),
+                Some(c) => result.push(c),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn parse_date_time(params: &str, value: &str) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+
+    if (params.to_ascii_uppercase().contains("VALUE=DATE") && !value.contains('T'))
+        || value.len() == 8
+    {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(|date| (date.and_time(chrono::NaiveTime::MIN), true));
+    }
+
+    match value.strip_suffix('Z') {
+        Some(value) => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|date| {
+                (
+                    Utc.from_utc_datetime(&date)
+                        .with_timezone(&Local)
+                        .naive_local(),
+                    false,
+                )
+            }),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|date| (date, false)),
+    }
+}
+
+/// Parse a `RRULE` of the event starting on `start`
+///
+/// Only the parts that don't move the occurrences away from the start date are
+/// supported, `BYDAY` only for weekly rules, other rules return `None`.
+fn parse_rule(value: &str, start: NaiveDate) -> Option<Recurrence> {
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut until = None;
+    let mut count = None;
+    let mut weekdays = Vec::new();
+    let mut month = None;
+    let mut month_day = None;
+
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => interval = value.parse().ok()?,
+            "UNTIL" => until = Some(parse_date_time("", value)?.0.date()),
+            "COUNT" => count = Some(value.parse().ok()?),
+            // the week start only matters for weekly rules with several days and an interval
+            "WKST" => {}
+            "BYDAY" => {
+                // ordinals like 1MO select a day of the month, they aren't supported
+                weekdays = value
+                    .split(',')
+                    .map(|day| match day.to_ascii_uppercase().as_str() {
+                        "MO" => Some(Weekday::Mon),
+                        "TU" => Some(Weekday::Tue),
+                        "WE" => Some(Weekday::Wed),
+                        "TH" => Some(Weekday::Thu),
+                        "FR" => Some(Weekday::Fri),
+                        "SA" => Some(Weekday::Sat),
+                        "SU" => Some(Weekday::Sun),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?
+            }
+            "BYMONTH" => month = Some(value.parse::<u32>().ok()?),
+            "BYMONTHDAY" => month_day = Some(value.parse::<u32>().ok()?),
+            // BYSETPOS, BYYEARDAY, BYWEEKNO, BYHOUR...
+            _ => return None,
+        }
+    }
+
+    let frequency = frequency?;
+
+    // some clients repeat the start date in the rule, any other value moves the occurrences
+    let same_day = match frequency {
+        Frequency::Daily | Frequency::Weekly => month.is_none() && month_day.is_none(),
+        Frequency::Monthly => month.is_none() && month_day.unwrap_or(start.day()) == start.day(),
+        Frequency::Yearly => {
+            month.unwrap_or(start.month()) == start.month()
+                && month_day.unwrap_or(start.day()) == start.day()
+        }
+    };
+    if !same_day || (frequency != Frequency::Weekly && !weekdays.is_empty()) {
+        return None;
+    }
+
+    Some(Recurrence {
+        frequency,
+        interval,
+        until,
+        count,
+        weekdays,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn event(start: &str, rule: &str) -> CalendarEvent {
+        let content = format!(
+            "BEGIN:VEVENT\nUID:1\nSUMMARY:Test\nDTSTART;VALUE=DATE:{}\nRRULE:{}\nEND:VEVENT\n",
+            start, rule
+        );
+        let mut events = parse(&content);
+
+        assert_eq!(events.len(), 1, "{}", rule);
+        events.remove(0)
+    }
+
+    #[test]
+    fn parse_rule_weekly_days() {
+        let rule = parse_rule("FREQ=WEEKLY;BYDAY=MO,WE;WKST=MO", date(2024, 1, 1)).unwrap();
+
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.weekdays, vec![Weekday::Mon, Weekday::Wed]);
+    }
+
+    #[test]
+    fn parse_rule_limits() {
+        let rule = parse_rule("FREQ=DAILY;INTERVAL=2;COUNT=3", date(2024, 1, 1)).unwrap();
+        assert_eq!((rule.interval, rule.count), (2, Some(3)));
+
+        let rule = parse_rule("FREQ=DAILY;UNTIL=20240110T120000Z", date(2024, 1, 1)).unwrap();
+        assert_eq!(rule.until, Some(date(2024, 1, 10)));
+    }
+
+    #[test]
+    fn parse_rule_start_date_parts() {
+        let start = date(2024, 3, 14);
+
+        assert!(parse_rule("FREQ=MONTHLY;BYMONTHDAY=14", start).is_some());
+        assert!(parse_rule("FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=14", start).is_some());
+        assert!(parse_rule("FREQ=MONTHLY;BYMONTHDAY=15", start).is_none());
+        assert!(parse_rule("FREQ=YEARLY;BYMONTH=4", start).is_none());
+    }
+
+    #[test]
+    fn parse_rule_unsupported() {
+        let start = date(2024, 1, 9);
+
+        assert!(parse_rule("FREQ=MONTHLY;BYDAY=2TU", start).is_none());
+        assert!(parse_rule("FREQ=MONTHLY;BYDAY=TU;BYSETPOS=2", start).is_none());
+        assert!(parse_rule("FREQ=YEARLY;BYDAY=TU", start).is_none());
+        assert!(parse_rule("FREQ=WEEKLY;BYDAY=1TU", start).is_none());
+        assert!(parse_rule("FREQ=DAILY;BYHOUR=9", start).is_none());
+        assert!(parse_rule("FREQ=HOURLY", start).is_none());
+        assert!(parse_rule("INTERVAL=2", start).is_none());
+    }
+
+    #[test]
+    fn occurs_on_single_and_multi_day() {
+        let content = "BEGIN:VEVENT\nSUMMARY:Trip\nDTSTART;VALUE=DATE:20240110\n\
+                       DTEND;VALUE=DATE:20240113\nEND:VEVENT\n";
+        let event = &parse(content)[0];
+
+        assert!(!event.occurs_on(date(2024, 1, 9)));
+        assert!(event.occurs_on(date(2024, 1, 10)));
+        assert!(event.occurs_on(date(2024, 1, 12)));
+        assert!(!event.occurs_on(date(2024, 1, 13)));
+    }
+
+    #[test]
+    fn occurs_on_daily_count_and_interval() {
+        let event = event("20240101", "FREQ=DAILY;INTERVAL=2;COUNT=3");
+
+        assert!(event.occurs_on(date(2024, 1, 1)));
+        assert!(!event.occurs_on(date(2024, 1, 2)));
+        assert!(event.occurs_on(date(2024, 1, 5)));
+        assert!(!event.occurs_on(date(2024, 1, 7)));
+    }
+
+    #[test]
+    fn occurs_on_weekly_days() {
+        let event = event(
+            "20240101",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20240131",
+        );
+
+        assert!(event.occurs_on(date(2024, 1, 3)));
+        assert!(!event.occurs_on(date(2024, 1, 8)));
+        assert!(event.occurs_on(date(2024, 1, 15)));
+        assert!(!event.occurs_on(date(2024, 1, 16)));
+        assert!(!event.occurs_on(date(2024, 2, 12)));
+    }
+
+    #[test]
+    fn occurs_on_monthly_skips_short_months() {
+        let event = event("20240131", "FREQ=MONTHLY");
+
+        assert!(event.occurs_on(date(2024, 1, 31)));
+        assert!(!event.occurs_on(date(2024, 2, 29)));
+        assert!(event.occurs_on(date(2024, 3, 31)));
+        assert!(!event.occurs_on(date(2024, 4, 30)));
+        assert!(!event.occurs_on(date(2024, 5, 30)));
+    }
+
+    #[test]
+    fn occurs_on_yearly_leap_day() {
+        let event = event("20240229", "FREQ=YEARLY");
+
+        assert!(!event.occurs_on(date(2025, 2, 28)));
+        assert!(!event.occurs_on(date(2025, 3, 1)));
+        assert!(event.occurs_on(date(2028, 2, 29)));
+    }
+
+    #[test]
+    fn occurs_on_exceptions() {
+        let content = "BEGIN:VEVENT\nSUMMARY:Standup\nDTSTART:20240101T090000\n\
+                       RRULE:FREQ=DAILY\nEXDATE:20240102T090000,20240104T090000\nEND:VEVENT\n";
+        let event = &parse(content)[0];
+
+        assert!(event.occurs_on(date(2024, 1, 1)));
+        assert!(!event.occurs_on(date(2024, 1, 2)));
+        assert!(event.occurs_on(date(2024, 1, 3)));
+        assert!(!event.occurs_on(date(2024, 1, 4)));
+    }
+
+    #[test]
+    fn parse_skips_unsupported_rules() {
+        let content = "BEGIN:VEVENT\nSUMMARY:Meeting\nDTSTART;VALUE=DATE:20240109\n\
+                       RRULE:FREQ=MONTHLY;BYDAY=2TU\nEND:VEVENT\n";
+
+        assert!(parse(content).is_empty());
+    }
+
+    #[test]
+    fn parse_moves_overridden_occurrence() {
+        let content = "BEGIN:VEVENT\nUID:weekly\nSUMMARY:Sync\nDTSTART:20240101T100000\n\
+                       RRULE:FREQ=WEEKLY\nEND:VEVENT\n\
+                       BEGIN:VEVENT\nUID:weekly\nSUMMARY:Sync\n\
+                       RECURRENCE-ID:20240108T100000\nDTSTART:20240109T140000\nEND:VEVENT\n";
+        let events = parse(content);
+        let on = |day| {
+            events
+                .iter()
+                .filter(|event| event.occurs_on(date(2024, 1, day)))
+                .count()
+        };
+
+        assert_eq!(on(1), 1);
+        assert_eq!(on(8), 0);
+        assert_eq!(on(9), 1);
+        assert_eq!(on(15), 1);
+    }
+}
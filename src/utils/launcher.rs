@@ -22,6 +22,17 @@ pub fn suspend() {
     });
 }
 
+pub fn hibernate() {
+    tokio::spawn(async move {
+        let _ = Command::new("bash")
+            .arg("-c")
+            .arg("systemctl hibernate")
+            .spawn()
+            .expect("Failed to execute command.")
+            .wait();
+    });
+}
+
 pub fn shutdown() {
     tokio::spawn(async move {
         let _ = Command::new("bash")
@@ -1,6 +1,8 @@
 use std::time::Duration;
 
+pub mod ical;
 pub mod launcher;
+pub mod notifications;
 
 pub enum IndicatorState {
     Normal,
@@ -18,3 +20,19 @@ pub fn format_duration(duration: &Duration) -> String {
         format!("{:>2}m", m)
     }
 }
+
+/// Clock-like duration, "M:SS" or "H:MM:SS" from an hour
+pub fn format_clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,86 @@
+use log::{debug, error};
+use std::collections::HashMap;
+use zbus::{proxy, zvariant::Value};
+
+const APP_NAME: &str = "ashell";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl From<Urgency> for u8 {
+    fn from(urgency: Urgency) -> u8 {
+        match urgency {
+            Urgency::Low => 0,
+            Urgency::Normal => 1,
+            Urgency::Critical => 2,
+        }
+    }
+}
+
+impl From<u8> for Urgency {
+    fn from(urgency: u8) -> Urgency {
+        match urgency {
+            0 => Urgency::Low,
+            2 => Urgency::Critical,
+            _ => Urgency::Normal,
+        }
+    }
+}
+
+async fn send(summary: &str, body: &str, icon: &str, urgency: Urgency) -> anyhow::Result<u32> {
+    let conn = zbus::Connection::session().await?;
+    let notifications = NotificationsProxy::new(&conn).await?;
+
+    let mut hints = HashMap::new();
+    hints.insert("urgency", Value::U8(urgency.into()));
+
+    let id = notifications
+        .notify(
+            APP_NAME,
+            0,
+            icon,
+            summary,
+            body,
+            &[],
+            hints,
+            // critical notifications stay until dismissed
+            if urgency == Urgency::Critical { 0 } else { -1 },
+        )
+        .await?;
+
+    Ok(id)
+}
+
+/// Show a desktop notification in the background, failures are only logged
+pub fn notify(summary: String, body: String, icon: &'static str, urgency: Urgency) {
+    tokio::spawn(async move {
+        match send(&summary, &body, icon, urgency).await {
+            Ok(id) => debug!("Notification {} sent: {}", id, summary),
+            Err(err) => error!("Failed to send notification: {}", err),
+        }
+    });
+}
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}